@@ -0,0 +1,308 @@
+use image_wrapper::ImageDataHDR;
+use openexr::{write_exr, ExrChannel, ExrCompression, ExrPixelType};
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use vector_math::vec3::Vec3;
+
+/// Arbitrary output variables: per-pixel data rendered alongside the beauty image.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AovPass {
+    /// Mean distance from the camera to the first hit; infinite where every sample missed.
+    Depth,
+    /// Mean world-space shading normal at the first hit, facing the camera.
+    Normal,
+    /// Mean base color of the first surface hit.
+    Albedo,
+    /// 1-based index of the first object hit by the pixel's first sample, 0 for the background.
+    ObjectId,
+    /// Material of the first sample's hit, numbered from 1 in order of first appearance.
+    MaterialId,
+    /// Unbiased sample variance of the pixel's radiance samples, per channel.
+    Variance,
+}
+
+impl AovPass {
+    pub const ALL: [AovPass; 6] = [
+        AovPass::Depth,
+        AovPass::Normal,
+        AovPass::Albedo,
+        AovPass::ObjectId,
+        AovPass::MaterialId,
+        AovPass::Variance,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AovPass::Depth => "depth",
+            AovPass::Normal => "normal",
+            AovPass::Albedo => "albedo",
+            AovPass::ObjectId => "object-id",
+            AovPass::MaterialId => "material-id",
+            AovPass::Variance => "variance",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        AovPass::ALL
+            .iter()
+            .cloned()
+            .find(|pass| pass.name() == name)
+    }
+
+    /// OpenEXR layer name and the channels stored from each pixel's x, y and z.
+    fn exr_layer(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            AovPass::Depth => ("depth", &["Z"]),
+            AovPass::Normal => ("normal", &["X", "Y", "Z"]),
+            AovPass::Albedo => ("albedo", &["R", "G", "B"]),
+            AovPass::ObjectId => ("objectId", &["id"]),
+            AovPass::MaterialId => ("materialId", &["id"]),
+            AovPass::Variance => ("variance", &["R", "G", "B"]),
+        }
+    }
+
+    /// Maps pass values into `[0, 1]` for 8-bit previews.
+    pub fn visualize(self, image: &ImageDataHDR) -> ImageDataHDR {
+        let max_depth = image
+            .pixels
+            .iter()
+            .map(|p| p.x)
+            .filter(|d| d.is_finite())
+            .fold(0.0, f32::max);
+
+        let mut preview = ImageDataHDR::new(image.width, image.height);
+        for (out, p) in preview.pixels.iter_mut().zip(&image.pixels) {
+            *out = match self {
+                AovPass::Depth if p.x.is_finite() && max_depth > 0.0 => {
+                    let d = 1.0 - p.x / max_depth;
+                    Vec3::new(d, d, d)
+                }
+                AovPass::Depth => Vec3::zero(),
+                AovPass::Normal => 0.5 * (*p + Vec3::new(1.0, 1.0, 1.0)),
+                AovPass::ObjectId | AovPass::MaterialId => id_color(p.x as u32),
+                AovPass::Albedo | AovPass::Variance => *p,
+            };
+        }
+        preview
+    }
+}
+
+/// A distinct, stable color for each ID, with black for 0.
+fn id_color(id: u32) -> Vec3 {
+    if id == 0 {
+        return Vec3::zero();
+    }
+    let hash = id.wrapping_mul(0x9E37_79B1);
+    let channel = |shift: u32| 0.2 + 0.8 * ((hash >> shift) & 0xff) as f32 / 255.0;
+    Vec3::new(channel(24), channel(16), channel(8))
+}
+
+/// First-hit data for one camera sample.
+#[derive(Copy, Clone, Debug)]
+pub struct SurfaceSample {
+    pub distance: f32,
+    pub normal: Vec3,
+    pub albedo: Vec3,
+    pub object_id: u32,
+    pub material_id: u32,
+}
+
+/// Running per-pixel statistics for every pass.
+#[derive(Copy, Clone, Debug)]
+pub struct AovAccumulator {
+    sample_count: u32,
+    hit_count: u32,
+    distance_sum: f32,
+    normal_sum: Vec3,
+    albedo_sum: Vec3,
+    first: Option<SurfaceSample>,
+    first_recorded: bool,
+    mean: Vec3,
+    squared_deviations: Vec3,
+}
+
+impl Default for AovAccumulator {
+    fn default() -> Self {
+        AovAccumulator {
+            sample_count: 0,
+            hit_count: 0,
+            distance_sum: 0.0,
+            normal_sum: Vec3::zero(),
+            albedo_sum: Vec3::zero(),
+            first: None,
+            first_recorded: false,
+            mean: Vec3::zero(),
+            squared_deviations: Vec3::zero(),
+        }
+    }
+}
+
+impl AovAccumulator {
+    pub fn add(&mut self, color: Vec3, surface: Option<SurfaceSample>) {
+        self.sample_count += 1;
+
+        // Welford's update keeps the variance accurate over many samples.
+        let delta = color - self.mean;
+        self.mean += delta / self.sample_count as f32;
+        self.squared_deviations += delta * (color - self.mean);
+
+        if !self.first_recorded {
+            self.first = surface;
+            self.first_recorded = true;
+        }
+        if let Some(surface) = surface {
+            self.hit_count += 1;
+            self.distance_sum += surface.distance;
+            self.normal_sum += surface.normal;
+            self.albedo_sum += surface.albedo;
+        }
+    }
+
+    /// The pass value for this pixel.
+    pub fn value(&self, pass: AovPass) -> Vec3 {
+        let splat = |x: f32| Vec3::new(x, x, x);
+        match pass {
+            AovPass::Depth if self.hit_count == 0 => splat(f32::INFINITY),
+            AovPass::Depth => splat(self.distance_sum / self.hit_count as f32),
+            AovPass::Normal if self.normal_sum.magnitude() > 0.0 => self.normal_sum.normalized(),
+            AovPass::Normal => Vec3::zero(),
+            AovPass::Albedo if self.sample_count > 0 => self.albedo_sum / self.sample_count as f32,
+            AovPass::Albedo => Vec3::zero(),
+            AovPass::ObjectId => splat(self.first.map_or(0, |s| s.object_id) as f32),
+            AovPass::MaterialId => splat(self.first.map_or(0, |s| s.material_id) as f32),
+            AovPass::Variance if self.sample_count > 1 => {
+                self.squared_deviations / (self.sample_count - 1) as f32
+            }
+            AovPass::Variance => Vec3::zero(),
+        }
+    }
+}
+
+/// Writes the beauty image as `R`, `G`, `B` plus one `layer.channel` group per pass.
+pub fn save_exr_with_aovs(
+    filename: &str,
+    beauty: &ImageDataHDR,
+    aovs: &[(AovPass, ImageDataHDR)],
+    pixel_type: ExrPixelType,
+    compression: ExrCompression,
+) -> Result<(), io::Error> {
+    let component = |image: &ImageDataHDR, axis: usize| -> Vec<f32> {
+        image.pixels.iter().map(|p| p[axis]).collect()
+    };
+
+    let mut names = vec!["R".to_string(), "G".to_string(), "B".to_string()];
+    let mut values = vec![
+        component(beauty, 0),
+        component(beauty, 1),
+        component(beauty, 2),
+    ];
+    for (pass, image) in aovs {
+        let (layer, channels) = pass.exr_layer();
+        for (axis, channel) in channels.iter().enumerate() {
+            names.push(format!("{}.{}", layer, channel));
+            values.push(component(image, axis));
+        }
+    }
+
+    let channels: Vec<ExrChannel> = names
+        .iter()
+        .zip(&values)
+        .map(|(name, values)| ExrChannel { name, values })
+        .collect();
+
+    let mut writer = BufWriter::new(File::create(filename)?);
+    write_exr(
+        &mut writer,
+        beauty.width,
+        beauty.height,
+        &channels,
+        pixel_type,
+        compression,
+    )?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(distance: f32, normal: Vec3, object_id: u32) -> SurfaceSample {
+        SurfaceSample {
+            distance,
+            normal,
+            albedo: Vec3::new(0.5, 0.25, 1.0),
+            object_id,
+            material_id: 3,
+        }
+    }
+
+    #[test]
+    fn accumulates_passes() {
+        let mut pixel = AovAccumulator::default();
+        pixel.add(
+            Vec3::new(1.0, 0.0, 2.0),
+            Some(surface(2.0, Vec3::new(1.0, 0.0, 0.0), 7)),
+        );
+        pixel.add(
+            Vec3::new(3.0, 0.0, 2.0),
+            Some(surface(4.0, Vec3::new(0.0, 1.0, 0.0), 8)),
+        );
+        pixel.add(Vec3::new(2.0, 0.0, 2.0), None);
+
+        assert_eq!(pixel.value(AovPass::Depth), Vec3::new(3.0, 3.0, 3.0));
+        let normal = pixel.value(AovPass::Normal);
+        assert!((normal - Vec3::new(1.0, 1.0, 0.0).normalized()).magnitude() < 1e-6);
+        assert!(
+            (pixel.value(AovPass::Albedo) - Vec3::new(1.0 / 3.0, 1.0 / 6.0, 2.0 / 3.0)).magnitude()
+                < 1e-6
+        );
+        assert_eq!(pixel.value(AovPass::ObjectId), Vec3::new(7.0, 7.0, 7.0));
+        assert_eq!(pixel.value(AovPass::MaterialId), Vec3::new(3.0, 3.0, 3.0));
+        assert_eq!(pixel.value(AovPass::Variance), Vec3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn misses_report_background_values() {
+        let mut pixel = AovAccumulator::default();
+        pixel.add(Vec3::new(1.0, 1.0, 1.0), None);
+        pixel.add(
+            Vec3::new(1.0, 1.0, 1.0),
+            Some(surface(1.0, Vec3::new(0.0, 0.0, 1.0), 3)),
+        );
+
+        // IDs come from the first sample, which missed.
+        assert_eq!(pixel.value(AovPass::ObjectId), Vec3::zero());
+        assert_eq!(pixel.value(AovPass::MaterialId), Vec3::zero());
+        assert_eq!(pixel.value(AovPass::Variance), Vec3::zero());
+
+        let empty = AovAccumulator::default();
+        assert_eq!(empty.value(AovPass::Depth).x, f32::INFINITY);
+        assert_eq!(empty.value(AovPass::Normal), Vec3::zero());
+    }
+
+    #[test]
+    fn pass_names_round_trip() {
+        for &pass in &AovPass::ALL {
+            assert_eq!(AovPass::from_name(pass.name()), Some(pass));
+        }
+        assert_eq!(AovPass::from_name("beauty"), None);
+    }
+
+    #[test]
+    fn depth_preview_is_normalized() {
+        let mut depth = ImageDataHDR::new(3, 1);
+        depth.pixels = vec![
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(4.0, 4.0, 4.0),
+            Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        ];
+        let preview = AovPass::Depth.visualize(&depth);
+        assert_eq!(
+            preview.pixels,
+            vec![Vec3::new(0.75, 0.75, 0.75), Vec3::zero(), Vec3::zero()]
+        );
+        assert_eq!(id_color(0), Vec3::zero());
+        assert_ne!(id_color(1), id_color(2));
+    }
+}
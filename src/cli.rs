@@ -0,0 +1,648 @@
+use ray_tracing_one_weekend_rs::{AovPass, ExrCompression, ExrPixelType, ToneMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// An option that takes a value, with its help text and how the value updates the settings.
+struct Flag {
+    name: &'static str,
+    value: &'static str,
+    help: &'static str,
+    /// Paths are taken as written; every other value is a keyword or number and is lowercased
+    /// first, so that keywords are not case-sensitive.
+    path: bool,
+    apply: fn(&mut Parsed, String) -> Result<(), String>,
+}
+
+const FLAGS: &[Flag] = &[
+    Flag {
+        name: "--scene",
+        value: "NAME|FILE",
+        help: "Built-in scene (`cover` or `test`) or a .toml scene file [default: cover]",
+        path: true,
+        apply: |parsed, value| {
+            parsed.options.scene = match value.to_ascii_lowercase().as_str() {
+                "cover" => SceneChoice::BuiltIn(BuiltInScene::Cover),
+                "test" => SceneChoice::BuiltIn(BuiltInScene::Test),
+                _ if value.ends_with(".toml") || Path::new(&value).is_file() => {
+                    SceneChoice::File(value.into())
+                }
+                _ => {
+                    return Err(format!(
+                        "unknown scene '{}': expected `cover`, `test` or a .toml scene file",
+                        value
+                    ))
+                }
+            };
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--width",
+        value: "N",
+        help: "Image width in pixels [default: scene setting, or 200]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.width = Some(parse_value("--width", &value, true)?);
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--height",
+        value: "N",
+        help: "Image height in pixels [default: scene setting, or 100]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.height = Some(parse_value("--height", &value, true)?);
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--samples",
+        value: "N",
+        help: "Samples per pixel [default: scene setting, or 100]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.sample_count = Some(parse_value("--samples", &value, true)?);
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--max-depth",
+        value: "N",
+        help: "Maximum bounces per path [default: scene setting, or 50]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.max_depth = Some(parse_value("--max-depth", &value, false)?);
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--seed",
+        value: "N",
+        help: "Random seed [default: scene setting, or 0]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.seed = Some(parse_value("--seed", &value, false)?);
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--threads",
+        value: "N",
+        help: "Worker threads [default: number of CPUs]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.thread_count = Some(parse_value("--threads", &value, true)?);
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--output",
+        value: "PATH",
+        help: "Output image path [default: output/image.png]",
+        path: true,
+        apply: |parsed, value| {
+            parsed.options.output = PathBuf::from(value);
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--format",
+        value: "FORMAT",
+        help: "Output format: png, hdr, pfm or exr [default: from the output extension]",
+        path: false,
+        apply: |parsed, value| {
+            let format = OutputFormat::from_name(&value)
+                .ok_or_else(|| format!("unknown format '{}'", value))?;
+            parsed.format = Some(format);
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--exr-type",
+        value: "TYPE",
+        help: "OpenEXR sample type: half or float [default: half]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.exr_pixel_type = match value.as_str() {
+                "half" => ExrPixelType::Half,
+                "float" => ExrPixelType::Float,
+                _ => {
+                    return Err(format!(
+                        "unknown OpenEXR type '{}': expected `half` or `float`",
+                        value
+                    ))
+                }
+            };
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--exr-compression",
+        value: "C",
+        help: "OpenEXR compression: none or zip [default: zip]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.exr_compression = match value.as_str() {
+                "none" => ExrCompression::None,
+                "zip" => ExrCompression::Zip,
+                _ => {
+                    return Err(format!(
+                        "unknown OpenEXR compression '{}': expected `none` or `zip`",
+                        value
+                    ))
+                }
+            };
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--aov",
+        value: "PASSES",
+        help: "Comma-separated extra passes, or `all`: depth, normal, albedo, object-id,
+material-id, variance. Written as layers of an OpenEXR output, otherwise
+next to the output as <name>.<pass>.<extension>",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.passes = parse_passes(&value)?;
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--tone-map",
+        value: "NAME",
+        help: "Tone mapping operator: clamp, reinhard or aces [default: clamp]",
+        path: false,
+        apply: |parsed, value| {
+            let tone_map = ToneMap::from_name(&value)
+                .ok_or_else(|| format!("unknown tone map '{}'", value))?;
+            parsed.options.tone_map = tone_map;
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--exposure",
+        value: "STOPS",
+        help: "Exposure adjustment in stops, applied before tone mapping [default: 0]",
+        path: false,
+        apply: |parsed, value| {
+            parsed.options.exposure = match value.parse::<f32>() {
+                Ok(stops) if stops.is_finite() => stops,
+                _ => {
+                    return Err(format!(
+                        "invalid value '{}' for --exposure: expected a number",
+                        value
+                    ))
+                }
+            };
+            Ok(())
+        },
+    },
+    Flag {
+        name: "--light-sampling",
+        value: "M",
+        help: "How paths find light: bsdf (scattered rays only), light (shadow rays toward
+emitters that support it) or mis (both, weighted) [default: mis]",
+        path: false,
+        apply: |parsed, value| {
+            let sampling = LightSampling::from_name(&value).ok_or_else(|| {
+                format!(
+                    "unknown light sampling '{}': expected `bsdf`, `light` or `mis`",
+                    value
+                )
+            })?;
+            parsed.options.light_sampling = sampling;
+            Ok(())
+        },
+    },
+];
+
+/// Help text listing every option in `FLAGS`.
+pub fn usage() -> String {
+    const HELP_COLUMN: usize = 23;
+    let mut usage = "Usage: ray-tracing-one-weekend-rs [OPTIONS]\n\nOptions:\n".to_string();
+    let mut line = |left: String, help: &str| {
+        let indent = format!("\n{:1$}", "", HELP_COLUMN);
+        usage += &format!(
+            "  {:<2$} {}\n",
+            left,
+            help.replace('\n', &indent),
+            HELP_COLUMN - 3
+        );
+    };
+    for flag in FLAGS {
+        line(format!("{} <{}>", flag.name, flag.value), flag.help);
+    }
+    line("-h, --help".to_string(), "Print this help");
+    usage
+        + "\nValues may also be written as --option=value. \
+           Names such as formats and passes are not case-sensitive."
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BuiltInScene {
+    Cover,
+    Test,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneChoice {
+    BuiltIn(BuiltInScene),
+    File(PathBuf),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// Tone-mapped 8-bit sRGB.
+    Png,
+    /// Linear radiance formats.
+    Hdr,
+    Pfm,
+    Exr,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(OutputFormat::Png),
+            "hdr" => Some(OutputFormat::Hdr),
+            "pfm" => Some(OutputFormat::Pfm),
+            "exr" => Some(OutputFormat::Exr),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(OutputFormat::from_name)
+    }
+}
+
+/// Strategy for estimating light arriving directly from emitters and the background.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LightSampling {
+    /// Only scattered rays that happen to hit a light.
+    Bsdf,
+    /// Shadow rays toward lights, which scattered rays then ignore.
+    Light,
+    /// Both, combined with multiple importance sampling.
+    Mis,
+}
+
+impl LightSampling {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bsdf" => Some(LightSampling::Bsdf),
+            "light" => Some(LightSampling::Light),
+            "mis" => Some(LightSampling::Mis),
+            _ => None,
+        }
+    }
+}
+
+/// Command-line settings. Render settings left as `None` come from the scene.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub scene: SceneChoice,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub sample_count: Option<u32>,
+    pub max_depth: Option<u32>,
+    pub seed: Option<u64>,
+    pub thread_count: Option<usize>,
+    pub output: PathBuf,
+    pub format: OutputFormat,
+    pub exr_pixel_type: ExrPixelType,
+    pub exr_compression: ExrCompression,
+    pub passes: Vec<AovPass>,
+    pub tone_map: ToneMap,
+    pub exposure: f32,
+    pub light_sampling: LightSampling,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Render(Options),
+    Help,
+}
+
+/// Parses an integer flag value; `positive` also rejects zero.
+fn parse_value<T: FromStr + PartialEq + Default>(
+    flag: &str,
+    value: &str,
+    positive: bool,
+) -> Result<T, String> {
+    match value.parse::<T>() {
+        Ok(parsed) if positive && parsed == T::default() => {
+            Err(format!("{} must be greater than zero", flag))
+        }
+        Ok(parsed) => Ok(parsed),
+        Err(_) => Err(format!(
+            "invalid value '{}' for {}: expected a non-negative integer",
+            value, flag
+        )),
+    }
+}
+
+fn parse_passes(value: &str) -> Result<Vec<AovPass>, String> {
+    if value == "all" {
+        return Ok(AovPass::ALL.to_vec());
+    }
+
+    let mut passes = vec![];
+    for name in value.split(',').map(str::trim) {
+        let pass = AovPass::from_name(name).ok_or_else(|| format!("unknown pass '{}'", name))?;
+        if !passes.contains(&pass) {
+            passes.push(pass);
+        }
+    }
+    Ok(passes)
+}
+
+/// Images are indexed with `u32` arithmetic, up to three bytes per pixel for 8-bit output, so the
+/// byte count has to fit in a `u32`.
+pub fn check_image_size(width: u32, height: u32) -> Result<(), String> {
+    match width.checked_mul(height).and_then(|n| n.checked_mul(3)) {
+        Some(_) => Ok(()),
+        None => Err(format!("a {}x{} image is too large", width, height)),
+    }
+}
+
+/// Settings as they are parsed, before the output format is settled.
+struct Parsed {
+    options: Options,
+    format: Option<OutputFormat>,
+}
+
+/// Parses the arguments after the program name.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut parsed = Parsed {
+        options: Options {
+            scene: SceneChoice::BuiltIn(BuiltInScene::Cover),
+            width: None,
+            height: None,
+            sample_count: None,
+            max_depth: None,
+            seed: None,
+            thread_count: None,
+            output: PathBuf::from("output/image.png"),
+            format: OutputFormat::Png,
+            exr_pixel_type: ExrPixelType::Half,
+            exr_compression: ExrCompression::Zip,
+            passes: vec![],
+            tone_map: ToneMap::Clamp,
+            exposure: 0.0,
+            light_sampling: LightSampling::Mis,
+        },
+        format: None,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Ok(Command::Help);
+        }
+        if !arg.starts_with("--") {
+            return Err(format!("unexpected argument '{}'", arg));
+        }
+
+        let (name, inline_value) = match arg.find('=') {
+            Some(index) => (&arg[..index], Some(arg[index + 1..].to_string())),
+            None => (arg.as_str(), None),
+        };
+        let flag = match FLAGS.iter().find(|flag| flag.name == name) {
+            Some(flag) => flag,
+            None => return Err(format!("unknown option '{}'", name)),
+        };
+        let value = match inline_value.or_else(|| args.next()) {
+            Some(value) if flag.path => value,
+            Some(value) => value.to_ascii_lowercase(),
+            None => return Err(format!("{} needs a value", flag.name)),
+        };
+        (flag.apply)(&mut parsed, value)?;
+    }
+
+    let mut options = parsed.options;
+    if let (Some(width), Some(height)) = (options.width, options.height) {
+        check_image_size(width, height)?;
+    }
+    options.format = match parsed
+        .format
+        .or_else(|| OutputFormat::from_path(&options.output))
+    {
+        Some(format) => format,
+        None => {
+            return Err(format!(
+                "cannot tell the image format of '{}'; add --format",
+                options.output.display()
+            ))
+        }
+    };
+    Ok(Command::Render(options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    fn options(args: &[&str]) -> Options {
+        match parse(args) {
+            Ok(Command::Render(options)) => options,
+            other => panic!("expected render options, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn defaults_and_flags() {
+        let defaults = options(&[]);
+        assert_eq!(defaults.scene, SceneChoice::BuiltIn(BuiltInScene::Cover));
+        assert_eq!(defaults.width, None);
+        assert_eq!(defaults.output, PathBuf::from("output/image.png"));
+        assert_eq!(defaults.format, OutputFormat::Png);
+        assert_eq!(
+            (defaults.tone_map, defaults.exposure),
+            (ToneMap::Clamp, 0.0)
+        );
+        assert!(defaults.passes.is_empty());
+        assert_eq!(defaults.light_sampling, LightSampling::Mis);
+        assert_eq!(
+            (defaults.exr_pixel_type, defaults.exr_compression),
+            (ExrPixelType::Half, ExrCompression::Zip)
+        );
+
+        let set = options(&[
+            "--scene",
+            "test",
+            "--width=320",
+            "--height",
+            "240",
+            "--samples",
+            "8",
+            "--max-depth",
+            "0",
+            "--seed",
+            "7",
+            "--threads",
+            "3",
+            "--output",
+            "render.PNG",
+        ]);
+        assert_eq!(set.scene, SceneChoice::BuiltIn(BuiltInScene::Test));
+        assert_eq!((set.width, set.height), (Some(320), Some(240)));
+        assert_eq!(
+            (set.sample_count, set.max_depth, set.seed),
+            (Some(8), Some(0), Some(7))
+        );
+        assert_eq!(set.thread_count, Some(3));
+        assert_eq!(set.output, PathBuf::from("render.PNG"));
+
+        assert_eq!(
+            options(&["--scene", "scenes/mine.toml"]).scene,
+            SceneChoice::File("scenes/mine.toml".into())
+        );
+        assert_eq!(
+            options(&["--output", "image", "--format", "png"]).format,
+            OutputFormat::Png
+        );
+        assert_eq!(
+            options(&["--output", "beauty.hdr"]).format,
+            OutputFormat::Hdr
+        );
+        assert_eq!(
+            options(&["--output", "beauty.pfm"]).format,
+            OutputFormat::Pfm
+        );
+        let exr = options(&[
+            "--output",
+            "beauty.exr",
+            "--exr-type",
+            "float",
+            "--exr-compression",
+            "none",
+        ]);
+        assert_eq!(exr.format, OutputFormat::Exr);
+        assert_eq!(
+            (exr.exr_pixel_type, exr.exr_compression),
+            (ExrPixelType::Float, ExrCompression::None)
+        );
+        assert_eq!(
+            options(&["--aov", "depth, albedo,depth"]).passes,
+            vec![AovPass::Depth, AovPass::Albedo]
+        );
+        assert_eq!(options(&["--aov=all"]).passes, AovPass::ALL.to_vec());
+        let graded = options(&["--tone-map", "ACES", "--exposure=-1.5"]);
+        assert_eq!((graded.tone_map, graded.exposure), (ToneMap::Aces, -1.5));
+        assert_eq!(
+            options(&["--light-sampling", "bsdf"]).light_sampling,
+            LightSampling::Bsdf
+        );
+        assert_eq!(parse(&["--width", "10", "--help"]), Ok(Command::Help));
+        assert_eq!(parse(&["-h"]), Ok(Command::Help));
+    }
+
+    #[test]
+    fn keywords_ignore_case_but_paths_do_not() {
+        let shouted = options(&[
+            "--scene",
+            "Test",
+            "--output",
+            "Out/Beauty.EXR",
+            "--exr-type",
+            "FLOAT",
+            "--exr-compression=None",
+            "--aov",
+            "Depth,ALBEDO",
+            "--tone-map",
+            "Reinhard",
+            "--light-sampling",
+            "BSDF",
+        ]);
+        assert_eq!(shouted.scene, SceneChoice::BuiltIn(BuiltInScene::Test));
+        assert_eq!(
+            (shouted.output, shouted.format),
+            (PathBuf::from("Out/Beauty.EXR"), OutputFormat::Exr)
+        );
+        assert_eq!(
+            (shouted.exr_pixel_type, shouted.exr_compression),
+            (ExrPixelType::Float, ExrCompression::None)
+        );
+        assert_eq!(shouted.passes, vec![AovPass::Depth, AovPass::Albedo]);
+        assert_eq!(
+            (shouted.tone_map, shouted.light_sampling),
+            (ToneMap::Reinhard, LightSampling::Bsdf)
+        );
+        assert_eq!(
+            options(&["--scene", "Scenes/Mine.toml"]).scene,
+            SceneChoice::File("Scenes/Mine.toml".into())
+        );
+    }
+
+    #[test]
+    fn usage_lists_every_option() {
+        let usage = usage();
+        for flag in FLAGS {
+            assert!(
+                usage.contains(&format!("  {} <{}>", flag.name, flag.value)),
+                "{}",
+                flag.name
+            );
+        }
+        assert!(usage.contains("-h, --help"));
+    }
+
+    #[test]
+    fn invalid_arguments_are_errors() {
+        let error = |args: &[&str]| parse(args).unwrap_err();
+        assert_eq!(
+            error(&["--width", "abc"]),
+            "invalid value 'abc' for --width: expected a non-negative integer"
+        );
+        assert_eq!(
+            error(&["--samples", "-4"]),
+            "invalid value '-4' for --samples: expected a non-negative integer"
+        );
+        assert_eq!(
+            error(&["--height", "0"]),
+            "--height must be greater than zero"
+        );
+        assert_eq!(
+            error(&["--width", "70000", "--height=70000"]),
+            "a 70000x70000 image is too large"
+        );
+        assert!(parse(&["--width", "70000", "--height", "20000"]).is_ok());
+        assert_eq!(error(&["--seed"]), "--seed needs a value");
+        assert_eq!(error(&["--colour"]), "unknown option '--colour'");
+        assert_eq!(error(&["200"]), "unexpected argument '200'");
+        assert_eq!(
+            error(&["--scene", "garden"]),
+            "unknown scene 'garden': expected `cover`, `test` or a .toml scene file"
+        );
+        assert_eq!(error(&["--format", "gif"]), "unknown format 'gif'");
+        assert_eq!(
+            error(&["--exr-type", "double"]),
+            "unknown OpenEXR type 'double': expected `half` or `float`"
+        );
+        assert_eq!(error(&["--aov", "depth,beauty"]), "unknown pass 'beauty'");
+        assert_eq!(
+            error(&["--tone-map", "filmic"]),
+            "unknown tone map 'filmic'"
+        );
+        assert_eq!(
+            error(&["--light-sampling", "path"]),
+            "unknown light sampling 'path': expected `bsdf`, `light` or `mis`"
+        );
+        assert_eq!(
+            error(&["--exposure", "inf"]),
+            "invalid value 'inf' for --exposure: expected a number"
+        );
+        assert_eq!(
+            error(&["--output", "image.gif"]),
+            "cannot tell the image format of 'image.gif'; add --format"
+        );
+    }
+}
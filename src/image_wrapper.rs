@@ -1,21 +1,26 @@
-extern crate image;
-
-use self::image::ColorType;
-use self::image::png::PNGEncoder;
+use image::hdr::HDREncoder;
+use image::png::PNGEncoder;
+use image::ColorType;
+use image::Rgb;
+use openexr::{write_exr, ExrChannel, ExrCompression, ExrPixelType};
 use std::fs::File;
+use std::io::{BufWriter, Write};
+use tone_mapping::{to_srgb8, ToneMap};
+use vector_math::vec3::Vec3;
 
 pub struct ImageDataRGB {
     pub width: u32,
     pub height: u32,
-    pub pixels: Vec<u8>
+    pub pixels: Vec<u8>,
 }
 
 impl ImageDataRGB {
     pub fn new(width: u32, height: u32) -> Self {
         let pixels_len = (width * height * 3) as usize;
-        ImageDataRGB{
-            width, height,
-            pixels: vec!{0; pixels_len}
+        ImageDataRGB {
+            width,
+            height,
+            pixels: vec![0; pixels_len],
         }
     }
 
@@ -41,3 +46,186 @@ impl ImageDataRGB {
         Ok(())
     }
 }
+
+/// Linear floating-point RGB radiance, stored top row first like `ImageDataRGB`.
+pub struct ImageDataHDR {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Vec3>,
+}
+
+impl ImageDataHDR {
+    pub fn new(width: u32, height: u32) -> Self {
+        ImageDataHDR {
+            width,
+            height,
+            pixels: vec![Vec3::zero(); (width * height) as usize],
+        }
+    }
+
+    fn offset(&self, pixel: (u32, u32)) -> Option<usize> {
+        if pixel.0 >= self.width || pixel.1 >= self.height {
+            return None;
+        }
+
+        // Pixel rows count up from the bottom of the image, as in `ImageDataRGB::set_pixel`.
+        let y = self.height - pixel.1 - 1;
+        Some((pixel.0 + y * self.width) as usize)
+    }
+
+    pub fn set_pixel(&mut self, pixel: (u32, u32), color: Vec3) {
+        if let Some(offset) = self.offset(pixel) {
+            self.pixels[offset] = color;
+        }
+    }
+
+    pub fn get_pixel(&self, pixel: (u32, u32)) -> Vec3 {
+        self.offset(pixel)
+            .map_or(Vec3::zero(), |offset| self.pixels[offset])
+    }
+
+    /// Tone maps and sRGB-encodes into an 8-bit image.
+    pub fn to_rgb8(&self, tone_map: ToneMap, exposure_stops: f32) -> ImageDataRGB {
+        let mut pixels = Vec::with_capacity(self.pixels.len() * 3);
+        for color in &self.pixels {
+            let (r, g, b) = to_srgb8(*color, tone_map, exposure_stops);
+            pixels.extend_from_slice(&[r, g, b]);
+        }
+
+        ImageDataRGB {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+
+    /// Writes a Radiance RGBE `.hdr` file. RGBE cannot hold infinities or NaN, which become 0.
+    pub fn save_hdr(&self, filename: &str) -> Result<(), std::io::Error> {
+        let finite = |x: f32| if x.is_finite() { x } else { 0.0 };
+        let pixels: Vec<Rgb<f32>> = self
+            .pixels
+            .iter()
+            .map(|p| Rgb {
+                data: [finite(p.x), finite(p.y), finite(p.z)],
+            })
+            .collect();
+        let encoder = HDREncoder::new(BufWriter::new(File::create(filename)?));
+        encoder.encode(&pixels, self.width as usize, self.height as usize)
+    }
+
+    /// Writes a little-endian colour Portable Float Map.
+    pub fn save_pfm(&self, filename: &str) -> Result<(), std::io::Error> {
+        let mut writer = BufWriter::new(File::create(filename)?);
+        write!(writer, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+
+        // PFM rows run bottom to top.
+        for row in self.pixels.chunks(self.width as usize).rev() {
+            for p in row {
+                for value in &[p.x, p.y, p.z] {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+        writer.flush()
+    }
+
+    /// Writes an OpenEXR file with `R`, `G` and `B` channels.
+    pub fn save_exr(
+        &self,
+        filename: &str,
+        pixel_type: ExrPixelType,
+        compression: ExrCompression,
+    ) -> Result<(), std::io::Error> {
+        let red: Vec<f32> = self.pixels.iter().map(|p| p.x).collect();
+        let green: Vec<f32> = self.pixels.iter().map(|p| p.y).collect();
+        let blue: Vec<f32> = self.pixels.iter().map(|p| p.z).collect();
+        let channels = [
+            ExrChannel {
+                name: "R",
+                values: &red,
+            },
+            ExrChannel {
+                name: "G",
+                values: &green,
+            },
+            ExrChannel {
+                name: "B",
+                values: &blue,
+            },
+        ];
+
+        let mut writer = BufWriter::new(File::create(filename)?);
+        write_exr(
+            &mut writer,
+            self.width,
+            self.height,
+            &channels,
+            pixel_type,
+            compression,
+        )?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hdr_pixels_match_rgb_layout() {
+        let mut hdr = ImageDataHDR::new(2, 2);
+        hdr.set_pixel((1, 0), Vec3::new(4.0, 0.5, 0.0));
+        hdr.set_pixel((2, 0), Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(hdr.get_pixel((1, 0)), Vec3::new(4.0, 0.5, 0.0));
+
+        let mut rgb = ImageDataRGB::new(2, 2);
+        rgb.set_pixel(
+            (1, 0),
+            to_srgb8(Vec3::new(4.0, 0.5, 0.0), ToneMap::Clamp, 0.0),
+        );
+        assert_eq!(hdr.to_rgb8(ToneMap::Clamp, 0.0).pixels, rgb.pixels);
+    }
+
+    fn gradient() -> ImageDataHDR {
+        let mut image = ImageDataHDR::new(3, 2);
+        for (i, p) in image.pixels.iter_mut().enumerate() {
+            *p = Vec3::new(i as f32, 0.5, 16.0);
+        }
+        image
+    }
+
+    #[test]
+    fn pfm_round_trip() {
+        use scene::environment_map::read_pfm;
+        use std::io::BufReader;
+
+        let path = std::env::temp_dir().join("image_wrapper_pfm_round_trip.pfm");
+        let image = gradient();
+        image.save_pfm(path.to_str().unwrap()).unwrap();
+        let (width, height, pixels) = read_pfm(BufReader::new(File::open(&path).unwrap())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((width, height), (3, 2));
+        assert_eq!(pixels, image.pixels);
+    }
+
+    #[test]
+    fn hdr_round_trip() {
+        use image::hdr::HDRDecoder;
+        use std::io::BufReader;
+
+        let path = std::env::temp_dir().join("image_wrapper_hdr_round_trip.hdr");
+        let image = gradient();
+        image.save_hdr(path.to_str().unwrap()).unwrap();
+        let decoder = HDRDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+        let pixels = decoder.read_image_hdr().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // RGBE shares one exponent per pixel, so small channels lose precision.
+        assert_eq!(pixels.len(), 6);
+        for (decoded, original) in pixels.iter().zip(&image.pixels) {
+            assert!((decoded.data[2] - original.z).abs() < 0.1);
+            assert!((decoded.data[0] - original.x).abs() < 0.1);
+        }
+    }
+}
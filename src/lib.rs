@@ -0,0 +1,61 @@
+extern crate deflate;
+extern crate image;
+#[cfg(test)]
+extern crate inflate;
+extern crate rand;
+
+pub mod aov;
+pub mod image_wrapper;
+pub mod openexr;
+pub mod scene;
+pub mod scene_file;
+pub mod tone_mapping;
+pub mod vector_math;
+
+pub use aov::{save_exr_with_aovs, AovAccumulator, AovPass, SurfaceSample};
+pub use image_wrapper::*;
+pub use openexr::{ExrCompression, ExrPixelType};
+pub use scene::background::*;
+pub use scene::bvh_node::BvhNode;
+pub use scene::camera::Camera;
+pub use scene::conductor::Conductor;
+pub use scene::cone::Cone;
+pub use scene::constant_medium::ConstantMedium;
+pub use scene::cuboid::Cuboid;
+pub use scene::cylinder::Cylinder;
+pub use scene::dielectric::Dielectric;
+pub use scene::diffuse_light::DiffuseLight;
+pub use scene::disk::Disk;
+pub use scene::environment_map::EnvironmentMap;
+pub use scene::grid_medium::GridMedium;
+pub use scene::hitable::*;
+pub use scene::hitable_list::*;
+pub use scene::instance::Instance;
+pub use scene::lambertian::Lambertian;
+pub use scene::light::{Light, LightSample};
+pub use scene::light_ray::LightRay;
+pub use scene::material::Material;
+pub use scene::material_id::{MaterialId, MaterialIds};
+pub use scene::metal::Metal;
+pub use scene::moving_sphere::MovingSphere;
+pub use scene::noise_texture::{CloudsTexture, MarbleTexture, WoodTexture};
+pub use scene::obj_loader::{load_obj, ObjError};
+pub use scene::object_id::{assign_object_ids, ObjectId};
+pub use scene::phase_function::{HenyeyGreenstein, Isotropic};
+pub use scene::plane::Plane;
+pub use scene::rect::{AxisRect, RectPlane};
+pub use scene::sphere::Sphere;
+pub use scene::texture::{CheckerTexture, ConstantTexture, ImageTexture, Texture, WrapMode};
+pub use scene::torus::Torus;
+pub use scene::triangle::Triangle;
+pub use scene::triangle_mesh::TriangleMesh;
+pub use scene::voxel_grid::VoxelGrid;
+pub use scene_file::{
+    load_scene, parse_scene, CameraSettings, RenderOptions, SceneDescription, SceneError,
+};
+pub use tone_mapping::ToneMap;
+pub use vector_math::matrix4::Matrix4;
+pub use vector_math::noise::Perlin;
+pub use vector_math::random_methods::{random, seed_thread_rng};
+pub use vector_math::ray::*;
+pub use vector_math::vec3::*;
@@ -1,52 +1,204 @@
 extern crate rand;
+extern crate ray_tracing_one_weekend_rs;
+
+mod cli;
+
+use cli::{BuiltInScene, Command, LightSampling, OutputFormat, SceneChoice};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ray_tracing_one_weekend_rs::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+/// Everything a path can interact with: the scene geometry, its sampled lights and the background.
+struct World<'a> {
+    objects: &'a dyn Hitable,
+    lights: Vec<&'a dyn Light>,
+    background: &'a dyn Background,
+}
 
-mod image_wrapper;
-mod scene;
-mod vector_math;
-
-pub use image_wrapper::*;
-use rand::prelude::*;
-pub use scene::camera::Camera;
-pub use scene::dielectric::Dielectric;
-pub use scene::hitable::*;
-pub use scene::hitable_list::*;
-pub use scene::lambertian::Lambertian;
-pub use scene::light_ray::LightRay;
-pub use scene::material::Material;
-pub use scene::metal::Metal;
-pub use scene::sphere::Sphere;
-use std::str::FromStr;
-pub use vector_math::ray::*;
-pub use vector_math::vec3::*;
-
-fn get_color(path: &LightRay, scene: &HitableList, depth: u32, max_depth: u32) -> Vec3 {
-    let sky_color = || {
-        let unit_direction = path.ray.direction.normalized();
-        let t = 0.5 * (unit_direction.y + 1.0);
-        (1.0 - t) * Vec3::new(1.0, 1.0, 1.0) + t * Vec3::new(0.5, 0.7, 1.0)
-    };
+impl<'a> World<'a> {
+    fn new(objects: &'a dyn Hitable, background: &'a dyn Background) -> Self {
+        let mut lights = vec![];
+        objects.collect_lights(&mut lights);
+        World {
+            objects,
+            lights,
+            background,
+        }
+    }
+}
 
-    if depth >= max_depth {
-        return Vec3::zero();
+/// Shadow rays stop this fraction short of the light so that they do not hit the light itself.
+const SHADOW_EPSILON: f32 = 1e-3;
+
+/// Power heuristic (exponent 2) weight of a sample drawn with density `pdf`, when another strategy
+/// could have drawn the same direction with density `other_pdf`.
+fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b > 0.0 {
+        a / (a + b)
+    } else {
+        0.0
     }
+}
+
+/// Weight of light that a scattered ray found by chance, when shadow rays could have found it with
+/// density `light_pdf`.
+fn scattered_light_weight(sampling: LightSampling, scatter_pdf: f32, light_pdf: f32) -> f32 {
+    match sampling {
+        _ if light_pdf <= 0.0 => 1.0,
+        LightSampling::Bsdf => 1.0,
+        LightSampling::Light => 0.0,
+        LightSampling::Mis => power_heuristic(scatter_pdf, light_pdf),
+    }
+}
 
-    match scene.scatter(path, 0.001, std::f32::MAX) {
-        Some(new_path) => get_color(&new_path, &scene, depth + 1, max_depth),
-        None => {
-            if depth < max_depth {
-                path.color * sky_color()
-            } else {
-                Vec3::zero()
+/// Bounces every path takes before Russian roulette may end it.
+const ROULETTE_MIN_BOUNCES: u32 = 3;
+
+/// Radiance carried back along `path`, following at most `max_depth` rays. The camera ray's hit is
+/// stored in `first_hit` for AOV passes.
+fn get_color(
+    path: &LightRay,
+    world: &World,
+    sampling: LightSampling,
+    max_depth: u32,
+    first_hit: &mut Option<SurfaceSample>,
+) -> Vec3 {
+    let mut path = *path;
+    let mut color = Vec3::zero();
+    // Density with which the previous vertex chose `path`, set when that vertex also sent shadow
+    // rays toward the lights; emitters found by `path` are then weighted against those samples.
+    let mut scatter_pdf: Option<f32> = None;
+
+    for depth in 0..max_depth {
+        let direction = path.ray.direction.normalized();
+        let hit = match world.objects.hit_test(&path.ray, 0.001, f32::MAX) {
+            Some(hit) => hit,
+            None => {
+                let weight = match scatter_pdf {
+                    Some(pdf) => {
+                        scattered_light_weight(sampling, pdf, world.background.pdf(&direction))
+                    }
+                    None => 1.0,
+                };
+                color += weight * path.color * world.background.color(&path.ray);
+                break;
+            }
+        };
+
+        if depth == 0 {
+            *first_hit = Some(SurfaceSample {
+                distance: hit.t * path.ray.direction.magnitude(),
+                normal: hit.shading_normal,
+                albedo: hit.material.albedo(&hit),
+                object_id: hit.object_id,
+                material_id: hit.material.id(),
+            });
+        }
+
+        let weight = match (scatter_pdf, hit.light) {
+            (Some(pdf), Some(light)) => {
+                let light_pdf = light.pdf(&path.ray.origin, &direction) / world.lights.len() as f32;
+                scattered_light_weight(sampling, pdf, light_pdf)
             }
+            _ => 1.0,
+        };
+        color += weight * path.color * hit.material.emitted(&hit);
+
+        let sample_lights = sampling != LightSampling::Bsdf && !hit.material.is_specular();
+        if sample_lights {
+            color += path.color * direct_light(&path.ray, &hit, world, sampling);
         }
+
+        let mut next = match hit.material.scatter(&path, &hit) {
+            Some(next) => next,
+            None => break,
+        };
+        scatter_pdf = if sample_lights {
+            Some(
+                hit.material
+                    .pdf(&path.ray, &next.ray.direction.normalized(), &hit),
+            )
+        } else {
+            None
+        };
+
+        // Russian roulette: continue with probability equal to the path's throughput, and boost the
+        // survivors so that the expected contribution is unchanged.
+        if depth + 1 >= ROULETTE_MIN_BOUNCES {
+            let survival = next.color.x.max(next.color.y).max(next.color.z).min(1.0);
+            if survival <= 0.0 || random::<f32>() >= survival {
+                break;
+            }
+            next.color /= survival;
+        }
+        path = next;
+    }
+
+    color
+}
+
+/// Next-event estimate of the light reaching `hit` directly and scattered back along `incident`:
+/// one shadow ray toward a random light, and one toward the background if it can be sampled.
+fn direct_light(incident: &Ray, hit: &HitRecord, world: &World, sampling: LightSampling) -> Vec3 {
+    let mut color = Vec3::zero();
+    let mut add_sample = |direction: Vec3, distance: f32, radiance: Vec3, pdf: f32| {
+        let cos = if hit.material.is_volumetric() {
+            1.0
+        } else {
+            direction.dot(hit.shading_normal)
+        };
+        let bsdf = hit.material.eval(incident, &direction, hit);
+        if cos <= 0.0 || pdf <= 0.0 || bsdf == Vec3::zero() {
+            return;
+        }
+        let shadow_ray = Ray::at_time(hit.point, direction, incident.time);
+        let transmittance =
+            world
+                .objects
+                .transmittance(&shadow_ray, 0.001, distance * (1.0 - SHADOW_EPSILON));
+        if transmittance <= 0.0 {
+            return;
+        }
+        let weight = match sampling {
+            LightSampling::Mis => power_heuristic(pdf, hit.material.pdf(incident, &direction, hit)),
+            _ => 1.0,
+        };
+        color += bsdf * radiance * (transmittance * weight * cos / pdf);
+    };
+
+    if !world.lights.is_empty() {
+        let count = world.lights.len();
+        let light = world.lights[((random::<f32>() * count as f32) as usize).min(count - 1)];
+        if let Some(sample) = light.sample(&hit.point) {
+            add_sample(
+                sample.direction,
+                sample.distance,
+                sample.radiance,
+                sample.pdf / count as f32,
+            );
+        }
+    }
+
+    if let Some((direction, pdf)) = world.background.sample() {
+        let radiance = world.background.color(&Ray::new(hit.point, direction));
+        add_sample(direction, f32::INFINITY, radiance, pdf);
     }
+
+    color
 }
 
 fn test_scene() -> HitableList {
-    let mat1 = Box::new(Lambertian::new(0.8, 0.8, 0.0));
-    let mat2 = Box::new(Lambertian::new(0.1, 0.2, 0.8));
-    let mat3 = Box::new(Metal::new(0.8, 0.6, 0.2, 0.2));
-    let mat4 = Box::new(Dielectric::new(1.5));
+    let mut ids = MaterialIds::default();
+    let mat1 = ids.assign(Arc::new(Lambertian::new(0.8, 0.8, 0.0)));
+    let mat2 = ids.assign(Arc::new(Lambertian::new(0.1, 0.2, 0.8)));
+    let mat3 = ids.assign(Arc::new(Metal::new(0.8, 0.6, 0.2, 0.2)));
+    let mat4 = ids.assign(Arc::new(Dielectric::new(1.5)));
 
     let sphere1 = Box::new(Sphere::new(0.0, -100.5, 0.0, 100.0, mat1));
     let sphere2 = Box::new(Sphere::new(0.0, 0.0, 0.0, 0.5, mat2));
@@ -58,21 +210,26 @@ fn test_scene() -> HitableList {
     }
 }
 
-fn test_camera(aspect: f32) -> Camera {
+fn test_camera_settings() -> CameraSettings {
     let origin = Vec3::new(3.0, 3.0, 3.0);
     let look_at = Vec3::new(0.0, 0.0, 0.0);
-    let up = Vec3::new(0.0, 1.0, 0.0);
-    let fov = 20.0;
-    let dist_to_focus = (origin - look_at).magnitude();
-    let aperture = 2.0;
-    Camera::new(origin, look_at, up, fov, aspect, aperture, dist_to_focus)
+    CameraSettings {
+        origin,
+        look_at,
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: 20.0,
+        aperture: 2.0,
+        focus_distance: (origin - look_at).magnitude(),
+        shutter_open: 0.0,
+        shutter_close: 0.0,
+    }
 }
 
-fn cover_scene() -> HitableList {
-    let mut rng = thread_rng();
+fn cover_scene(seed: u64) -> HitableList {
+    let mut rng = StdRng::seed_from_u64(seed);
 
     // Random material generators
-    let rand_lambertian = |rng: &mut ThreadRng| {
+    let rand_lambertian = |rng: &mut StdRng| {
         let rands: Vec<f32> = rng
             .sample_iter(&rand::distributions::Standard)
             .take(6)
@@ -80,10 +237,10 @@ fn cover_scene() -> HitableList {
         let r = rands[0] * rands[1];
         let g = rands[2] * rands[3];
         let b = rands[4] * rands[5];
-        Box::new(Lambertian::new(r, g, b))
+        Arc::new(Lambertian::new(r, g, b))
     };
 
-    let rand_metal = |rng: &mut ThreadRng| {
+    let rand_metal = |rng: &mut StdRng| {
         let rands: Vec<f32> = rng
             .sample_iter(&rand::distributions::Standard)
             .take(4)
@@ -91,29 +248,30 @@ fn cover_scene() -> HitableList {
         let r = 0.5 * (1.0 + rands[0]);
         let g = 0.5 * (1.0 + rands[1]);
         let b = 0.5 * (1.0 + rands[2]);
-        Box::new(Metal::new(r, g, b, 0.5 * rands[3]))
+        Arc::new(Metal::new(r, g, b, 0.5 * rands[3]))
     };
 
-    let dielectric = || Box::new(Dielectric::new(1.5));
+    let dielectric = || Arc::new(Dielectric::new(1.5));
 
     // Scene
-    let mut hitables: Vec<Box<Hitable>> = vec![];
+    let mut hitables: Vec<Box<dyn Hitable>> = vec![];
+    let mut ids = MaterialIds::default();
 
     // Ground
-    let ground_material = Box::new(Lambertian::new(0.5, 0.5, 0.5));
+    let ground_material = ids.assign(Arc::new(Lambertian::new(0.5, 0.5, 0.5)));
     let ground = Sphere::new(0.0, -1000.0, 0.0, 1000.0, ground_material);
     hitables.push(Box::new(ground));
 
     // Large center spheres
-    let sphere1 = Sphere::new(0.0, 1.0, 0.0, 1.0, dielectric());
+    let sphere1 = Sphere::new(0.0, 1.0, 0.0, 1.0, ids.assign(dielectric()));
     hitables.push(Box::new(sphere1));
 
     let mat2 = Lambertian::new(0.4, 0.2, 0.1);
-    let sphere2 = Sphere::new(-4.0, 1.0, 0.0, 1.0, Box::new(mat2));
+    let sphere2 = Sphere::new(-4.0, 1.0, 0.0, 1.0, ids.assign(Arc::new(mat2)));
     hitables.push(Box::new(sphere2));
 
     let mat2 = Metal::new(0.7, 0.6, 0.5, 0.0);
-    let sphere3 = Sphere::new(4.0, 1.0, 0.0, 1.0, Box::new(mat2));
+    let sphere3 = Sphere::new(4.0, 1.0, 0.0, 1.0, ids.assign(Arc::new(mat2)));
     hitables.push(Box::new(sphere3));
 
     let min_center = Vec3::new(4.0, 0.2, 0.0);
@@ -132,13 +290,26 @@ fn cover_scene() -> HitableList {
                 let sphere: Sphere;
                 if chosen_material < 0.8 {
                     // diffuse
-                    sphere = Sphere::new(center.x, center.y, center.z, 0.2, rand_lambertian(&mut rng));
+                    sphere = Sphere::new(
+                        center.x,
+                        center.y,
+                        center.z,
+                        0.2,
+                        ids.assign(rand_lambertian(&mut rng)),
+                    );
                 } else if chosen_material < 0.95 {
                     // metal
-                    sphere = Sphere::new(center.x, center.y, center.z, 0.2, rand_metal(&mut rng));
+                    sphere = Sphere::new(
+                        center.x,
+                        center.y,
+                        center.z,
+                        0.2,
+                        ids.assign(rand_metal(&mut rng)),
+                    );
                 } else {
                     // glass
-                    sphere = Sphere::new(center.x, center.y, center.z, 0.2, dielectric());
+                    sphere =
+                        Sphere::new(center.x, center.y, center.z, 0.2, ids.assign(dielectric()));
                 }
 
                 hitables.push(Box::new(sphere));
@@ -149,86 +320,679 @@ fn cover_scene() -> HitableList {
     HitableList { list: hitables }
 }
 
-fn cover_camera(aspect: f32) -> Camera {
+fn cover_camera_settings() -> CameraSettings {
     let origin = Vec3::new(14.0, 2.0, 4.0);
     let look_at = Vec3::new(0.0, 0.0, 0.0);
-    let up = Vec3::new(0.0, 1.0, 0.0);
-    let fov = 20.0;
-    let dist_to_focus = (origin - look_at).magnitude();
-    let aperture = 0.05;
-    Camera::new(origin, look_at, up, fov, aspect, aperture, dist_to_focus)
+    CameraSettings {
+        origin,
+        look_at,
+        up: Vec3::new(0.0, 1.0, 0.0),
+        fov: 20.0,
+        aperture: 0.05,
+        focus_distance: (origin - look_at).magnitude(),
+        shutter_open: 0.0,
+        shutter_close: 0.0,
+    }
 }
 
-fn render(
+const TILE_SIZE: u32 = 16;
+
+#[derive(Copy, Clone, Debug)]
+struct Tile {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+fn make_tiles(width: u32, height: u32) -> Vec<Tile> {
+    let mut tiles = vec![];
+    for y in (0..height).step_by(TILE_SIZE as usize) {
+        for x in (0..width).step_by(TILE_SIZE as usize) {
+            tiles.push(Tile {
+                x,
+                y,
+                width: TILE_SIZE.min(width - x),
+                height: TILE_SIZE.min(height - y),
+            });
+        }
+    }
+    tiles
+}
+
+/// Mixes the render seed with the pixel index so every pixel draws an independent sequence.
+fn pixel_seed(seed: u64, pixel_index: u64) -> u64 {
+    let mut z = seed ^ pixel_index.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+struct RenderSettings {
     width: u32,
     height: u32,
     sample_count: u32,
     max_depth: u32,
-    scene: &HitableList,
-    camera: &Camera,
-) -> ImageDataRGB {
-    let mut image = ImageDataRGB::new(width, height);
+    seed: u64,
+    thread_count: usize,
+    passes: Vec<AovPass>,
+    light_sampling: LightSampling,
+}
 
-    let image_width = image.width as f32;
-    let image_height = image.height as f32;
+struct RenderOutput {
+    beauty: ImageDataHDR,
+    aovs: Vec<(AovPass, ImageDataHDR)>,
+}
 
-    let mut rng = thread_rng();
+fn render_tile(
+    tile: &Tile,
+    settings: &RenderSettings,
+    world: &World,
+    camera: &Camera,
+) -> Vec<(Vec3, AovAccumulator)> {
+    let image_width = settings.width as f32;
+    let image_height = settings.height as f32;
+    let mut pixels = Vec::with_capacity((tile.width * tile.height) as usize);
 
-    for j in 0..image.height {
-        println!("{}%", (100.0 * j as f32 / image_height) as u32);
+    for j in tile.y..tile.y + tile.height {
+        for i in tile.x..tile.x + tile.width {
+            seed_thread_rng(pixel_seed(settings.seed, (j * settings.width + i) as u64));
 
-        for i in 0..image.width {
             let mut color = Vec3::zero();
+            let mut aov = AovAccumulator::default();
 
-            for _ in 0..sample_count {
-                let u = (i as f32 + rng.gen::<f32>()) / image_width;
-                let v = (j as f32 + rng.gen::<f32>()) / image_height;
+            for _ in 0..settings.sample_count {
+                let u = (i as f32 + random::<f32>()) / image_width;
+                let v = (j as f32 + random::<f32>()) / image_height;
 
                 let r = camera.get_ray(u, v);
                 let path = LightRay::new(r, Vec3::new(1.0, 1.0, 1.0));
-                color += get_color(&path, &scene, 0, max_depth);
+                let mut first_hit = None;
+                let sample = get_color(
+                    &path,
+                    world,
+                    settings.light_sampling,
+                    settings.max_depth,
+                    &mut first_hit,
+                );
+                color += sample;
+                aov.add(sample, first_hit);
             }
 
-            color /= sample_count as f32;
-            color = Vec3::new(f32::sqrt(color.x), f32::sqrt(color.y), f32::sqrt(color.z));
+            pixels.push((color / settings.sample_count as f32, aov));
+        }
+    }
 
-            let r = (255.99 * color.x) as u8;
-            let g = (255.99 * color.y) as u8;
-            let b = (255.99 * color.z) as u8;
+    pixels
+}
 
-            image.set_pixel((i, j), (r, g, b));
+fn render(settings: &RenderSettings, world: &World, camera: &Camera) -> RenderOutput {
+    let mut image = ImageDataHDR::new(settings.width, settings.height);
+    let mut accumulators =
+        vec![AovAccumulator::default(); (settings.width * settings.height) as usize];
+
+    let tiles = make_tiles(settings.width, settings.height);
+    let next_tile = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|s| {
+        for _ in 0..settings.thread_count.max(1) {
+            let sender = sender.clone();
+            let tiles = &tiles;
+            let next_tile = &next_tile;
+            s.spawn(move || loop {
+                let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                if index >= tiles.len() {
+                    break;
+                }
+                let pixels = render_tile(&tiles[index], settings, world, camera);
+                sender.send((index, pixels)).unwrap();
+            });
         }
-    }
+        drop(sender);
+
+        for (finished, (index, pixels)) in receiver.iter().enumerate() {
+            let tile = &tiles[index];
+            let mut pixels = pixels.into_iter();
+            for j in tile.y..tile.y + tile.height {
+                for i in tile.x..tile.x + tile.width {
+                    let (color, aov) = pixels.next().unwrap();
+                    image.set_pixel((i, j), color);
+                    accumulators[(j * settings.width + i) as usize] = aov;
+                }
+            }
+            println!("{}%", (100 * (finished + 1)) / tiles.len());
+        }
+    });
 
-    println!("100%");
+    let aovs = settings
+        .passes
+        .iter()
+        .map(|&pass| (pass, aov_image(pass, settings, &accumulators)))
+        .collect();
 
-    image
+    RenderOutput {
+        beauty: image,
+        aovs,
+    }
 }
 
-fn parse_arg<T: FromStr>(offset: usize, default: T) -> T {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < offset + 1 {
-        return default;
+fn aov_image(
+    pass: AovPass,
+    settings: &RenderSettings,
+    accumulators: &[AovAccumulator],
+) -> ImageDataHDR {
+    let mut image = ImageDataHDR::new(settings.width, settings.height);
+    for j in 0..settings.height {
+        for i in 0..settings.width {
+            image.set_pixel(
+                (i, j),
+                accumulators[(j * settings.width + i) as usize].value(pass),
+            );
+        }
     }
+    image
+}
 
-    T::from_str(&args[offset]).unwrap_or(default)
+/// Prints `message` and exits with a failure status.
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(1);
 }
 
 fn main() {
-    let width = parse_arg(1, 200);
-    let height = parse_arg(2, 100);
-    let aspect = width as f32 / height as f32;
-    let sample_count = parse_arg(3, 100);
-    let max_depth = parse_arg(4, 50);
-
-    let scene = cover_scene();
-    let camera = cover_camera(aspect);
-
-    let image = render(width, height, sample_count, max_depth, &scene, &camera);
-    let image_name = "output/image.png";
-    let result = image.save(image_name);
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(Command::Render(options)) => options,
+        Ok(Command::Help) => {
+            println!("{}", cli::usage());
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, cli::usage());
+            std::process::exit(2);
+        }
+    };
+
+    let description = match &options.scene {
+        SceneChoice::File(path) => load_scene(path).unwrap_or_else(|err| fail(&err.to_string())),
+        SceneChoice::BuiltIn(scene) => {
+            let seed = options.seed.unwrap_or(0);
+            let (scene, camera) = match scene {
+                BuiltInScene::Cover => (cover_scene(seed), cover_camera_settings()),
+                BuiltInScene::Test => (test_scene(), test_camera_settings()),
+            };
+            SceneDescription {
+                render: RenderOptions::default(),
+                camera,
+                background: Box::new(GradientBackground::sky()),
+                objects: scene.list,
+            }
+        }
+    };
+
+    let defaults = description.render;
+    let settings = RenderSettings {
+        width: options.width.unwrap_or(defaults.width),
+        height: options.height.unwrap_or(defaults.height),
+        sample_count: options.sample_count.unwrap_or(defaults.sample_count),
+        max_depth: options.max_depth.unwrap_or(defaults.max_depth),
+        seed: options.seed.unwrap_or(defaults.seed),
+        thread_count: options.thread_count.unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        }),
+        passes: options.passes.clone(),
+        light_sampling: options.light_sampling,
+    };
+    // The scene may supply the other dimension.
+    if let Err(message) = cli::check_image_size(settings.width, settings.height) {
+        fail(&message);
+    }
+
+    let mut objects = description.objects;
+    if settings.passes.contains(&AovPass::ObjectId) {
+        objects = assign_object_ids(objects);
+    }
+
+    let camera = description
+        .camera
+        .build(settings.width as f32 / settings.height as f32);
+    let scene = BvhNode::build(objects);
+    let world = World::new(scene.as_ref(), description.background.as_ref());
+    let output = render(&settings, &world, &camera);
+
+    if let Some(directory) = options
+        .output
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+    {
+        if let Err(err) = std::fs::create_dir_all(directory) {
+            fail(&format!("{}: {}", directory.display(), err));
+        }
+    }
+
+    // OpenEXR stores passes as layers of one file; other formats get one file per pass.
+    if options.format == OutputFormat::Exr {
+        let result = save_exr_with_aovs(
+            &options.output.to_string_lossy(),
+            &output.beauty,
+            &output.aovs,
+            options.exr_pixel_type,
+            options.exr_compression,
+        );
+        report_saved(&options.output, result);
+        return;
+    }
+
+    report_saved(
+        &options.output,
+        save_image(&output.beauty, &options.output, &options),
+    );
+    for (pass, image) in &output.aovs {
+        let path = pass_path(&options.output, *pass);
+        let result = match options.format {
+            OutputFormat::Png => pass
+                .visualize(image)
+                .to_rgb8(ToneMap::Clamp, 0.0)
+                .save(&path.to_string_lossy()),
+            _ => save_image(image, &path, &options),
+        };
+        report_saved(&path, result);
+    }
+}
+
+fn save_image(
+    image: &ImageDataHDR,
+    path: &Path,
+    options: &cli::Options,
+) -> Result<(), std::io::Error> {
+    let path = path.to_string_lossy();
+    match options.format {
+        OutputFormat::Png => image
+            .to_rgb8(options.tone_map, options.exposure)
+            .save(&path),
+        OutputFormat::Hdr => image.save_hdr(&path),
+        OutputFormat::Pfm => image.save_pfm(&path),
+        OutputFormat::Exr => image.save_exr(&path, options.exr_pixel_type, options.exr_compression),
+    }
+}
+
+/// `output/image.png` becomes `output/image.depth.png`.
+fn pass_path(output: &Path, pass: AovPass) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map_or("image".into(), |s| s.to_string_lossy());
+    let name = match output.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, pass.name(), extension.to_string_lossy()),
+        None => format!("{}.{}", stem, pass.name()),
+    };
+    output.with_file_name(name)
+}
+
+fn report_saved(path: &Path, result: Result<(), std::io::Error>) {
     match result {
-        Ok(()) => println!("Image saved to {}", image_name),
-        Err(err) => println!("{:?}", err),
+        Ok(()) => println!("Image saved to {}", path.display()),
+        Err(err) => fail(&format!("{}: {}", path.display(), err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emission_is_weighted_by_path_color() {
+        let light = Sphere::new(
+            0.0,
+            0.0,
+            -2.0,
+            0.5,
+            Arc::new(DiffuseLight::new(4.0, 2.0, 1.0)),
+        );
+        let scene = HitableList {
+            list: vec![Box::new(light)],
+        };
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+        let path = LightRay::new(ray, Vec3::new(0.5, 0.5, 0.5));
+        let mut first_hit = None;
+        let world = World::new(&scene, &BlackBackground);
+        assert_eq!(
+            get_color(&path, &world, LightSampling::Mis, 4, &mut first_hit),
+            Vec3::new(2.0, 1.0, 0.5)
+        );
+        assert_eq!(first_hit.unwrap().distance, 1.5);
+    }
+
+    /// A large floor with a small spherical light at `light_center`.
+    fn lit_floor(floor: Arc<dyn Material>, light_center: Vec3) -> HitableList {
+        let floor = Sphere::new(0.0, -1000.0, 0.0, 1000.0, floor);
+        let lamp = Arc::new(DiffuseLight::new(4.0, 4.0, 4.0));
+        let light = Sphere::new(light_center.x, light_center.y, light_center.z, 0.5, lamp);
+        HitableList {
+            list: vec![Box::new(floor), Box::new(light)],
+        }
+    }
+
+    #[test]
+    fn direct_light_matches_analytic_irradiance() {
+        // A Lambertian floor lit by a sphere straight above receives irradiance pi L
+        // sin^2(theta_max), so it reflects albedo L sin^2(theta_max).
+        let lambertian = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let scene = lit_floor(lambertian.clone(), Vec3::new(0.0, 2.0, 0.0));
+        let world = World::new(&scene, &BlackBackground);
+        assert_eq!(world.lights.len(), 1);
+
+        let ray = Ray::new(Vec3::new(0.0, 1.0, 1.0), Vec3::new(0.0, -1.0, -1.0));
+        let hit = scene.hit_test(&ray, 0.001, f32::MAX).unwrap();
+        seed_thread_rng(11);
+        let n = 20_000;
+        let mut total = Vec3::zero();
+        for _ in 0..n {
+            total += direct_light(&ray, &hit, &world, LightSampling::Light);
+        }
+        let expected = 0.5 * 4.0 * (0.25 / 4.0);
+        let estimate = total.x / n as f32;
+        assert!(
+            (estimate - expected).abs() < 0.02 * expected,
+            "{} vs {}",
+            estimate,
+            expected
+        );
+
+        // Blocking the light leaves the floor in shadow.
+        let mut shadowed = lit_floor(lambertian, Vec3::new(0.0, 2.0, 0.0));
+        let blocker = Sphere::new(0.0, 1.0, 0.0, 0.6, Arc::new(Lambertian::new(0.5, 0.5, 0.5)));
+        shadowed.list.push(Box::new(blocker));
+        let world = World::new(&shadowed, &BlackBackground);
+        assert_eq!(
+            direct_light(&ray, &hit, &world, LightSampling::Light),
+            Vec3::zero()
+        );
+    }
+
+    #[test]
+    fn light_sampling_strategies_agree() {
+        assert_eq!(power_heuristic(1.0, 1.0), 0.5);
+        assert_eq!(power_heuristic(3.0, 1.0) + power_heuristic(1.0, 3.0), 1.0);
+        assert_eq!(power_heuristic(0.0, 0.0), 0.0);
+
+        // A glossy floor reflecting the light toward the camera. Each strategy counts every light
+        // path exactly once, so they converge to the same single-bounce illumination.
+        let metal = Arc::new(Metal::new(0.8, 0.8, 0.8, 0.3));
+        let scene = lit_floor(metal, Vec3::new(0.0, 2.0, -2.0));
+        let world = World::new(&scene, &BlackBackground);
+        let ray = Ray::new(Vec3::new(0.0, 1.0, 1.0), Vec3::new(0.0, -1.0, -1.0));
+
+        let n = 40_000;
+        let mean = |sampling: LightSampling| {
+            seed_thread_rng(5);
+            let mut total = Vec3::zero();
+            for _ in 0..n {
+                let path = LightRay::new(ray, Vec3::new(1.0, 1.0, 1.0));
+                total += get_color(&path, &world, sampling, 2, &mut None);
+            }
+            total.x / n as f32
+        };
+
+        let mis = mean(LightSampling::Mis);
+        for &sampling in &[LightSampling::Bsdf, LightSampling::Light] {
+            let other = mean(sampling);
+            assert!(
+                (other - mis).abs() < 0.03 * mis,
+                "{:?}: {} vs {}",
+                sampling,
+                other,
+                mis
+            );
+        }
+    }
+
+    /// A Lambertian surface that also glows, for furnace tests.
+    struct GlowingLambertian {
+        diffuse: Lambertian,
+        emit: Vec3,
+    }
+
+    impl Material for GlowingLambertian {
+        fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay> {
+            self.diffuse.scatter(incident, hit)
+        }
+
+        fn emitted(&self, _hit: &HitRecord) -> Vec3 {
+            self.emit
+        }
+    }
+
+    #[test]
+    fn russian_roulette_is_unbiased() {
+        use ray_tracing_one_weekend_rs::vector_math::random_methods::random_unit_vector;
+
+        // Inside a closed sphere whose walls reflect half the light and emit E, radiance is
+        // E / (1 - 0.5) after infinitely many bounces. Roulette ends most paths early without
+        // changing the mean.
+        let walls = GlowingLambertian {
+            diffuse: Lambertian::new(0.5, 0.5, 0.5),
+            emit: Vec3::new(1.0, 1.0, 1.0),
+        };
+        let scene = Sphere::new(0.0, 0.0, 0.0, 1.0, Arc::new(walls));
+        let world = World::new(&scene, &BlackBackground);
+
+        seed_thread_rng(2);
+        let n = 20_000;
+        let mut total = Vec3::zero();
+        for _ in 0..n {
+            let path = LightRay::new(
+                Ray::new(Vec3::zero(), random_unit_vector()),
+                Vec3::new(1.0, 1.0, 1.0),
+            );
+            total += get_color(&path, &world, LightSampling::Mis, 1_000_000, &mut None);
+        }
+        let estimate = total.x / n as f32;
+        assert!((estimate - 2.0).abs() < 0.04, "{}", estimate);
+    }
+
+    #[test]
+    fn render_is_independent_of_thread_count() {
+        let scene = BvhNode::build(test_scene().list);
+        let background = GradientBackground::sky();
+        let world = World::new(scene.as_ref(), &background);
+        let camera = test_camera_settings().build(2.0);
+        let mut settings = RenderSettings {
+            width: 40,
+            height: 20,
+            sample_count: 4,
+            max_depth: 8,
+            seed: 3,
+            thread_count: 1,
+            passes: AovPass::ALL.to_vec(),
+            light_sampling: LightSampling::Mis,
+        };
+
+        let single = render(&settings, &world, &camera);
+        settings.thread_count = 4;
+        let multi = render(&settings, &world, &camera);
+        assert_eq!(single.beauty.pixels, multi.beauty.pixels);
+        for ((_, a), (_, b)) in single.aovs.iter().zip(&multi.aovs) {
+            assert_eq!(a.pixels, b.pixels);
+        }
+
+        settings.seed = 4;
+        let reseeded = render(&settings, &world, &camera);
+        assert_ne!(single.beauty.pixels, reseeded.beauty.pixels);
+    }
+
+    #[test]
+    fn aov_passes_describe_first_hit() {
+        let scene = BvhNode::build(assign_object_ids(test_scene().list));
+        let camera = test_camera_settings().build(2.0);
+        let settings = RenderSettings {
+            width: 40,
+            height: 20,
+            sample_count: 4,
+            max_depth: 8,
+            seed: 1,
+            thread_count: 1,
+            passes: vec![
+                AovPass::ObjectId,
+                AovPass::MaterialId,
+                AovPass::Albedo,
+                AovPass::Depth,
+            ],
+            light_sampling: LightSampling::Mis,
+        };
+        let output = render(
+            &settings,
+            &World::new(scene.as_ref(), &GradientBackground::sky()),
+            &camera,
+        );
+        let pass = |index: usize, pixel: (u32, u32)| output.aovs[index].1.get_pixel(pixel);
+
+        // The camera looks straight at the blue sphere, the second object with the second material,
+        // and the ground with the first fills the top-left corner.
+        let center = (20, 10);
+        assert_eq!(pass(0, center).x, 2.0);
+        assert_eq!(pass(1, (0, 19)).x, 1.0);
+        assert_eq!(pass(1, center).x, 2.0);
+        assert_eq!(pass(2, center), Vec3::new(0.1, 0.2, 0.8));
+        let distance = pass(3, center).x;
+        assert!(
+            (distance - (27.0f32.sqrt() - 0.5)).abs() < 0.3,
+            "depth {}",
+            distance
+        );
+    }
+
+    fn assert_scene_file_matches(file: &str, scene: HitableList, camera: Camera) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("scenes")
+            .join(file);
+        let description = load_scene(&path).unwrap();
+        let settings = RenderSettings {
+            width: 40,
+            height: 20,
+            sample_count: 2,
+            max_depth: 8,
+            seed: description.render.seed,
+            thread_count: 1,
+            passes: vec![],
+            light_sampling: LightSampling::Mis,
+        };
+
+        let built_in_objects = BvhNode::build(scene.list);
+        let built_in = render(
+            &settings,
+            &World::new(built_in_objects.as_ref(), &GradientBackground::sky()),
+            &camera,
+        );
+        let file_objects = BvhNode::build(description.objects);
+        let file_world = World::new(file_objects.as_ref(), description.background.as_ref());
+        let from_file = render(&settings, &file_world, &description.camera.build(2.0));
+        assert_eq!(built_in.beauty.pixels, from_file.beauty.pixels);
+    }
+
+    #[test]
+    fn mis_beats_single_strategies_on_veach_plates() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("scenes")
+            .join("veach_mis.toml");
+        let description = load_scene(&path).unwrap();
+        let objects = BvhNode::build(description.objects);
+        let world = World::new(objects.as_ref(), description.background.as_ref());
+        assert_eq!(world.lights.len(), 4);
+
+        // The first triangle of each plate and the light centers, as in the scene file. For each
+        // pair, aim at the point where the plate mirrors the light toward the camera and compare
+        // the per-sample variance of the three strategies there.
+        let camera = Vec3::new(0.0, 2.0, 15.0);
+        let plates = [
+            [
+                Vec3::new(-4.0, -2.686, 3.565),
+                Vec3::new(4.0, -2.686, 3.565),
+                Vec3::new(4.0, -2.914, 4.435),
+            ],
+            [
+                Vec3::new(-4.0, -2.137, 2.18),
+                Vec3::new(4.0, -2.137, 2.18),
+                Vec3::new(4.0, -2.463, 3.02),
+            ],
+            [
+                Vec3::new(-4.0, -1.488, 1.003),
+                Vec3::new(4.0, -1.488, 1.003),
+                Vec3::new(4.0, -1.912, 1.797),
+            ],
+            [
+                Vec3::new(-4.0, -0.736, 0.036),
+                Vec3::new(4.0, -0.736, 0.036),
+                Vec3::new(4.0, -1.264, 0.764),
+            ],
+        ];
+        let lights = [-3.75, -1.25, 1.25, 3.75]
+            .iter()
+            .map(|&x| Vec3::new(x, 2.5, 0.0));
+
+        let n = 2000;
+        let variance = |ray: &Ray, sampling: LightSampling| {
+            seed_thread_rng(8);
+            let samples: Vec<f32> = (0..n)
+                .map(|_| {
+                    get_color(
+                        &LightRay::new(*ray, Vec3::new(1.0, 1.0, 1.0)),
+                        &world,
+                        sampling,
+                        2,
+                        &mut None,
+                    )
+                    .x
+                })
+                .collect();
+            let mean = samples.iter().sum::<f32>() / n as f32;
+            samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / (n - 1) as f32
+        };
+
+        // MIS must stay close to the better strategy at every pair. Where every scattered ray lands
+        // on a large light that strategy is noiseless, so there MIS only has to remove nearly all
+        // of the shadow-ray noise.
+        let mut mis_wins = 0;
+        for light in lights {
+            for &[a, b, c] in &plates {
+                let normal = (b - a).cross(c - a).normalized();
+                let mirrored = light - 2.0 * (light - a).dot(normal) * normal;
+                let toward = mirrored - camera;
+                let target = camera + ((a - camera).dot(normal) / toward.dot(normal)) * toward;
+                let ray = Ray::new(camera, (target - camera).normalized());
+
+                let mis = variance(&ray, LightSampling::Mis);
+                let bsdf = variance(&ray, LightSampling::Bsdf);
+                let light_only = variance(&ray, LightSampling::Light);
+                let (best, worst) = (bsdf.min(light_only), bsdf.max(light_only));
+                assert!(
+                    mis <= 1.5 * best + 0.01 * worst,
+                    "{:?}: {} vs {} and {}",
+                    light,
+                    mis,
+                    bsdf,
+                    light_only
+                );
+                if mis < best {
+                    mis_wins += 1;
+                }
+            }
+        }
+        assert!(
+            mis_wins >= 10,
+            "MIS had the lowest variance at {} of 16 pairs",
+            mis_wins
+        );
+    }
+
+    #[test]
+    fn example_scene_files_match_built_in_scenes() {
+        assert_scene_file_matches("test.toml", test_scene(), test_camera_settings().build(2.0));
+        assert_scene_file_matches(
+            "cover.toml",
+            cover_scene(0),
+            cover_camera_settings().build(2.0),
+        );
     }
 }
@@ -0,0 +1,389 @@
+use deflate::deflate_bytes_zlib;
+use std::io;
+use std::io::Write;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExrPixelType {
+    Half,
+    Float,
+}
+
+impl ExrPixelType {
+    fn id(self) -> i32 {
+        match self {
+            ExrPixelType::Half => 1,
+            ExrPixelType::Float => 2,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ExrPixelType::Half => 2,
+            ExrPixelType::Float => 4,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExrCompression {
+    None,
+    /// Deflate in blocks of 16 scanlines.
+    Zip,
+}
+
+impl ExrCompression {
+    fn id(self) -> u8 {
+        match self {
+            ExrCompression::None => 0,
+            ExrCompression::Zip => 3,
+        }
+    }
+
+    fn lines_per_block(self) -> u32 {
+        match self {
+            ExrCompression::None => 1,
+            ExrCompression::Zip => 16,
+        }
+    }
+}
+
+/// A named image channel, such as `R` or `normal.X`, with one value per pixel, top row first.
+pub struct ExrChannel<'a> {
+    pub name: &'a str,
+    pub values: &'a [f32],
+}
+
+/// Converts to IEEE 754 half precision, rounding to nearest even.
+pub fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    // Rounds `m >> shift` up when the dropped bits are over half, or half with an odd result.
+    let round = |m: u32, shift: u32| {
+        let halfway = 1 << (shift - 1);
+        let rounded = m >> shift;
+        if m & halfway != 0 && m & (3 * halfway - 1) != 0 {
+            rounded + 1
+        } else {
+            rounded
+        }
+    };
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        sign | 0x7c00
+    } else if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        // Subnormal: restore the implicit leading one and shift it into the 10-bit mantissa.
+        sign | round(mantissa | 0x0080_0000, (14 - half_exponent) as u32) as u16
+    } else {
+        // A mantissa carry correctly bumps the exponent, up to infinity.
+        sign | round(((half_exponent as u32) << 23) | mantissa, 13) as u16
+    }
+}
+
+fn write_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+/// Applies OpenEXR's ZIP filter: split even and odd bytes, delta encode, then deflate.
+fn zip_compress(raw: &[u8]) -> Vec<u8> {
+    let mut reordered: Vec<u8> = Vec::with_capacity(raw.len());
+    reordered.extend(raw.iter().step_by(2));
+    reordered.extend(raw.iter().skip(1).step_by(2));
+
+    for i in (1..reordered.len()).rev() {
+        reordered[i] = reordered[i]
+            .wrapping_sub(reordered[i - 1])
+            .wrapping_add(128);
+    }
+
+    deflate_bytes_zlib(&reordered)
+}
+
+/// Writes a single-part scanline OpenEXR image.
+pub fn write_exr<W: Write>(
+    mut writer: W,
+    width: u32,
+    height: u32,
+    channels: &[ExrChannel],
+    pixel_type: ExrPixelType,
+    compression: ExrCompression,
+) -> Result<(), io::Error> {
+    assert!(width > 0 && height > 0, "image is empty");
+    let pixel_count = (width * height) as usize;
+    assert!(
+        channels.iter().all(|c| c.values.len() == pixel_count),
+        "channel size does not match image"
+    );
+
+    // Readers expect channels in alphabetical order.
+    let mut channels: Vec<&ExrChannel> = channels.iter().collect();
+    channels.sort_by(|a, b| a.name.cmp(b.name));
+
+    let mut header = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+
+    let mut channel_list = vec![];
+    for channel in &channels {
+        channel_list.extend_from_slice(channel.name.as_bytes());
+        channel_list.push(0);
+        channel_list.extend_from_slice(&pixel_type.id().to_le_bytes());
+        channel_list.extend_from_slice(&[0, 0, 0, 0]); // pLinear and reserved
+        channel_list.extend_from_slice(&1i32.to_le_bytes());
+        channel_list.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channel_list.push(0);
+    write_attribute(&mut header, "channels", "chlist", &channel_list);
+
+    write_attribute(
+        &mut header,
+        "compression",
+        "compression",
+        &[compression.id()],
+    );
+    let mut window = vec![];
+    for v in &[0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend_from_slice(&v.to_le_bytes());
+    }
+    write_attribute(&mut header, "dataWindow", "box2i", &window);
+    write_attribute(&mut header, "displayWindow", "box2i", &window);
+    write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    write_attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1.0f32.to_le_bytes(),
+    );
+    write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    write_attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1.0f32.to_le_bytes(),
+    );
+    header.push(0);
+
+    let lines_per_block = compression.lines_per_block();
+    let block_count = height.div_ceil(lines_per_block) as usize;
+
+    let mut blocks = Vec::with_capacity(block_count);
+    for block in 0..block_count as u32 {
+        let first_line = block * lines_per_block;
+        let last_line = (first_line + lines_per_block).min(height);
+
+        let line_size = width as usize * channels.len() * pixel_type.size();
+        let mut raw = Vec::with_capacity((last_line - first_line) as usize * line_size);
+        for y in first_line..last_line {
+            let row = (y * width) as usize..((y + 1) * width) as usize;
+            for channel in &channels {
+                for &value in &channel.values[row.clone()] {
+                    match pixel_type {
+                        ExrPixelType::Half => {
+                            raw.extend_from_slice(&f32_to_half(value).to_le_bytes())
+                        }
+                        ExrPixelType::Float => raw.extend_from_slice(&value.to_le_bytes()),
+                    }
+                }
+            }
+        }
+
+        let data = match compression {
+            ExrCompression::None => raw,
+            ExrCompression::Zip => {
+                // Blocks that do not shrink are stored raw, which readers detect from the size.
+                let compressed = zip_compress(&raw);
+                if compressed.len() < raw.len() {
+                    compressed
+                } else {
+                    raw
+                }
+            }
+        };
+
+        let mut chunk = Vec::with_capacity(data.len() + 8);
+        chunk.extend_from_slice(&(first_line as i32).to_le_bytes());
+        chunk.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        chunk.extend_from_slice(&data);
+        blocks.push(chunk);
+    }
+
+    let mut offset = (header.len() + 8 * block_count) as u64;
+    for block in &blocks {
+        header.extend_from_slice(&offset.to_le_bytes());
+        offset += block.len() as u64;
+    }
+
+    writer.write_all(&header)?;
+    for block in &blocks {
+        writer.write_all(block)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use inflate::inflate_bytes_zlib;
+
+    #[test]
+    fn half_conversion() {
+        assert_eq!(f32_to_half(0.0), 0x0000);
+        assert_eq!(f32_to_half(-0.0), 0x8000);
+        assert_eq!(f32_to_half(1.0), 0x3c00);
+        assert_eq!(f32_to_half(-2.0), 0xc000);
+        assert_eq!(f32_to_half(0.333_333_34), 0x3555);
+        assert_eq!(f32_to_half(65504.0), 0x7bff);
+        assert_eq!(f32_to_half(65520.0), 0x7c00);
+        assert_eq!(f32_to_half(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_half(f32::NAN) & 0x7e00, 0x7e00);
+        // Smallest subnormal, and halfway below it rounding to even (zero).
+        assert_eq!(f32_to_half(5.960_464_5e-8), 0x0001);
+        assert_eq!(f32_to_half(2.980_232_2e-8), 0x0000);
+        assert_eq!(f32_to_half(6.097_555e-5), 0x03ff);
+        // 1 + 2^-11 is halfway between 1 and the next half; ties go to the even mantissa.
+        assert_eq!(f32_to_half(1.000_488_3), 0x3c00);
+        assert_eq!(f32_to_half(1.001_464_8), 0x3c02);
+    }
+
+    fn read_i32(data: &[u8], offset: usize) -> i32 {
+        i32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ])
+    }
+
+    /// Decodes the files `write_exr` produces into channel names and raw little-endian samples.
+    fn read_exr(data: &[u8], width: usize, height: usize) -> (Vec<String>, Vec<Vec<u8>>) {
+        assert_eq!(&data[..8], &[0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+
+        let mut position = 8;
+        let mut attributes = vec![];
+        let read_string = |position: &mut usize| {
+            let end = data[*position..].iter().position(|&b| b == 0).unwrap() + *position;
+            let s = String::from_utf8(data[*position..end].to_vec()).unwrap();
+            *position = end + 1;
+            s
+        };
+        loop {
+            let name = read_string(&mut position);
+            if name.is_empty() {
+                break;
+            }
+            let kind = read_string(&mut position);
+            let size = read_i32(data, position) as usize;
+            attributes.push((name, kind, data[position + 4..position + 4 + size].to_vec()));
+            position += 4 + size;
+        }
+
+        let (_, _, channel_list) = attributes.iter().find(|a| a.0 == "channels").unwrap();
+        let (_, _, compression) = attributes.iter().find(|a| a.0 == "compression").unwrap();
+        let mut names = vec![];
+        let mut sample_size = 0;
+        let mut i = 0;
+        while channel_list[i] != 0 {
+            let end = channel_list[i..].iter().position(|&b| b == 0).unwrap() + i;
+            names.push(String::from_utf8(channel_list[i..end].to_vec()).unwrap());
+            sample_size = if read_i32(channel_list, end + 1) == 1 {
+                2
+            } else {
+                4
+            };
+            i = end + 17;
+        }
+
+        let lines_per_block = if compression[0] == 3 { 16 } else { 1 };
+        let block_count = height.div_ceil(lines_per_block);
+        let mut channels = vec![vec![]; names.len()];
+        for block in 0..block_count {
+            let offset = read_i32(data, position + 8 * block) as usize;
+            assert_eq!(read_i32(data, offset) as usize, block * lines_per_block);
+            let size = read_i32(data, offset + 4) as usize;
+            let lines = lines_per_block.min(height - block * lines_per_block);
+            let expected = lines * width * names.len() * sample_size;
+
+            let mut raw = data[offset + 8..offset + 8 + size].to_vec();
+            if size < expected {
+                raw = inflate_bytes_zlib(&raw).unwrap();
+                for i in 1..raw.len() {
+                    raw[i] = raw[i].wrapping_add(raw[i - 1]).wrapping_sub(128);
+                }
+                let (even, odd) = raw.split_at(raw.len().div_ceil(2));
+                let mut interleaved = vec![];
+                for i in 0..raw.len() {
+                    interleaved.push(if i % 2 == 0 { even[i / 2] } else { odd[i / 2] });
+                }
+                raw = interleaved;
+            }
+            assert_eq!(raw.len(), expected);
+
+            for line in raw.chunks(width * names.len() * sample_size) {
+                for (channel, samples) in line.chunks(width * sample_size).enumerate() {
+                    channels[channel].extend_from_slice(samples);
+                }
+            }
+        }
+
+        (names, channels)
+    }
+
+    #[test]
+    fn exr_round_trip() {
+        let (width, height) = (5, 37);
+        let red: Vec<f32> = (0..width * height).map(|i| i as f32 * 0.25).collect();
+        let green: Vec<f32> = (0..width * height).map(|i| (i % 7) as f32 - 3.5).collect();
+        let input = [
+            ExrChannel {
+                name: "R",
+                values: &red,
+            },
+            ExrChannel {
+                name: "G",
+                values: &green,
+            },
+        ];
+
+        for &pixel_type in &[ExrPixelType::Half, ExrPixelType::Float] {
+            for &compression in &[ExrCompression::None, ExrCompression::Zip] {
+                let mut file = vec![];
+                write_exr(
+                    &mut file,
+                    width as u32,
+                    height as u32,
+                    &input,
+                    pixel_type,
+                    compression,
+                )
+                .unwrap();
+                let (names, channels) = read_exr(&file, width, height);
+                assert_eq!(names, vec!["G", "R"]);
+
+                for (values, samples) in [&green, &red].iter().zip(&channels) {
+                    let expected: Vec<u8> = values
+                        .iter()
+                        .flat_map(|&v| match pixel_type {
+                            ExrPixelType::Half => f32_to_half(v).to_le_bytes().to_vec(),
+                            ExrPixelType::Float => v.to_le_bytes().to_vec(),
+                        })
+                        .collect();
+                    assert_eq!(samples, &expected);
+                }
+            }
+        }
+    }
+}
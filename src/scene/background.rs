@@ -0,0 +1,90 @@
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Radiance arriving along rays that leave the scene without hitting anything.
+pub trait Background: Send + Sync {
+    fn color(&self, ray: &Ray) -> Vec3;
+
+    /// Picks a direction in proportion to the light arriving from it, returning `(direction, pdf)`
+    /// with the density per unit solid angle. `None` when the background has no sampling strategy.
+    fn sample(&self) -> Option<(Vec3, f32)> {
+        None
+    }
+
+    /// Density with which `sample` would return `direction`.
+    fn pdf(&self, _direction: &Vec3) -> f32 {
+        0.0
+    }
+}
+
+/// The same color in every direction, for neutral product backdrops.
+#[derive(Copy, Clone, Debug)]
+pub struct SolidBackground {
+    pub color: Vec3,
+}
+
+impl SolidBackground {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        SolidBackground {
+            color: Vec3::new(r, g, b),
+        }
+    }
+}
+
+impl Background for SolidBackground {
+    fn color(&self, _ray: &Ray) -> Vec3 {
+        self.color
+    }
+}
+
+/// Blend from `nadir` (straight down) through their average at the horizon to `zenith` (up).
+#[derive(Copy, Clone, Debug)]
+pub struct GradientBackground {
+    pub nadir: Vec3,
+    pub zenith: Vec3,
+}
+
+impl GradientBackground {
+    pub fn new(nadir: Vec3, zenith: Vec3) -> Self {
+        GradientBackground { nadir, zenith }
+    }
+
+    /// White to light blue.
+    pub fn sky() -> Self {
+        GradientBackground::new(Vec3::new(1.0, 1.0, 1.0), Vec3::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Background for GradientBackground {
+    fn color(&self, ray: &Ray) -> Vec3 {
+        let unit_direction = ray.direction.normalized();
+        let t = 0.5 * (unit_direction.y + 1.0);
+        (1.0 - t) * self.nadir + t * self.zenith
+    }
+}
+
+/// No light from outside the scene, so closed interiors are lit only by their emitters.
+#[derive(Copy, Clone, Debug)]
+pub struct BlackBackground;
+
+impl Background for BlackBackground {
+    fn color(&self, _ray: &Ray) -> Vec3 {
+        Vec3::zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient_endpoints() {
+        let sky = GradientBackground::sky();
+        let up = Ray::new(Vec3::zero(), Vec3::new(0.0, 2.0, 0.0));
+        let down = Ray::new(Vec3::zero(), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(sky.color(&up), Vec3::new(0.5, 0.7, 1.0));
+        assert_eq!(sky.color(&down), sky.nadir);
+        let level = Ray::new(Vec3::zero(), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(sky.color(&level), 0.5 * (sky.nadir + sky.zenith));
+    }
+}
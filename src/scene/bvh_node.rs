@@ -0,0 +1,288 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::hitable_list::HitableList;
+use scene::light::Light;
+use vector_math::aabb::Aabb;
+use vector_math::ray::Ray;
+
+/// Relative cost of visiting a node compared to testing one primitive.
+const TRAVERSAL_COST: f32 = 0.125;
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Bounding volume hierarchy node, split with the surface area heuristic.
+pub struct BvhNode {
+    bounds: Aabb,
+    left: Box<dyn Hitable>,
+    right: Box<dyn Hitable>,
+}
+
+impl BvhNode {
+    /// Builds a hierarchy over `hitables`. Unbounded objects, or ones with infinite or NaN bounds,
+    /// cannot be placed in the tree, so they are returned in a list alongside it.
+    pub fn build(hitables: Vec<Box<dyn Hitable>>) -> Box<dyn Hitable> {
+        let mut bounded = vec![];
+        let mut unbounded = vec![];
+        for hitable in hitables {
+            match hitable.bounding_box() {
+                Some(bounds) if is_finite(&bounds) => bounded.push((bounds, hitable)),
+                _ => unbounded.push(hitable),
+            }
+        }
+
+        if bounded.is_empty() {
+            return Box::new(HitableList { list: unbounded });
+        }
+
+        let tree = build_node(bounded);
+        if unbounded.is_empty() {
+            tree
+        } else {
+            unbounded.push(tree);
+            Box::new(HitableList { list: unbounded })
+        }
+    }
+}
+
+fn build_node(mut items: Vec<(Aabb, Box<dyn Hitable>)>) -> Box<dyn Hitable> {
+    if items.len() == 1 {
+        return items.pop().unwrap().1;
+    }
+
+    let bounds = items.iter().fold(Aabb::empty(), |b, item| b.union(&item.0));
+    let (axis, split, split_cost) = find_split(&items, &bounds);
+
+    let leaf_cost = items.len() as f32;
+    if items.len() <= MAX_LEAF_SIZE && leaf_cost <= split_cost {
+        let list = items.into_iter().map(|item| item.1).collect();
+        return Box::new(HitableList { list });
+    }
+
+    sort_by_centroid(&mut items, axis);
+    let right = items.split_off(split);
+
+    Box::new(BvhNode {
+        bounds,
+        left: build_node(items),
+        right: build_node(right),
+    })
+}
+
+fn is_finite(bounds: &Aabb) -> bool {
+    (0..3).all(|axis| bounds.min[axis].is_finite() && bounds.max[axis].is_finite())
+}
+
+fn sort_by_centroid<T>(items: &mut [(Aabb, T)], axis: usize) {
+    items.sort_by(|a, b| {
+        let ca = a.0.centroid()[axis];
+        let cb = b.0.centroid()[axis];
+        ca.total_cmp(&cb)
+    });
+}
+
+/// Sweeps every axis for the cheapest SAH partition. Returns `(axis, split_index, cost)`, where the
+/// cost is in units of primitive intersections.
+fn find_split(items: &[(Aabb, Box<dyn Hitable>)], bounds: &Aabb) -> (usize, usize, f32) {
+    let count = items.len();
+    let parent_area = bounds.surface_area();
+
+    // Flat or point-like sets give the heuristic nothing to work with; fall back to a median split.
+    if parent_area <= 0.0 {
+        return (0, count / 2, TRAVERSAL_COST + count as f32);
+    }
+
+    let mut best = (0, count / 2, f32::MAX);
+
+    for axis in 0..3 {
+        // Sorted from the original order each time so ties match the final sort in `build_node`.
+        let mut boxes: Vec<(Aabb, ())> = items.iter().map(|item| (item.0, ())).collect();
+        sort_by_centroid(&mut boxes, axis);
+
+        // right_areas[i] is the area of the boxes i..count.
+        let mut right_areas = vec![0.0; count];
+        let mut right_bounds = Aabb::empty();
+        for i in (1..count).rev() {
+            right_bounds = right_bounds.union(&boxes[i].0);
+            right_areas[i] = right_bounds.surface_area();
+        }
+
+        let mut left_bounds = Aabb::empty();
+        for split in 1..count {
+            left_bounds = left_bounds.union(&boxes[split - 1].0);
+            let left_cost = left_bounds.surface_area() * split as f32;
+            let right_cost = right_areas[split] * (count - split) as f32;
+            let cost = TRAVERSAL_COST + (left_cost + right_cost) / parent_area;
+
+            if cost < best.2 {
+                best = (axis, split, cost);
+            }
+        }
+    }
+
+    best
+}
+
+impl Hitable for BvhNode {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        if !self.bounds.hit(ray, t_min, t_max) {
+            return None;
+        }
+
+        let left = self.left.hit_test(ray, t_min, t_max);
+        let closest = left.as_ref().map_or(t_max, |hit| hit.t);
+        let right = self.right.hit_test(ray, t_min, closest);
+        right.or(left)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        if !self.bounds.hit(ray, t_min, t_max) {
+            return 1.0;
+        }
+
+        let left = self.left.transmittance(ray, t_min, t_max);
+        if left == 0.0 {
+            return 0.0;
+        }
+        left * self.right.transmittance(ray, t_min, t_max)
+    }
+
+    fn collect_lights<'a>(&'a self, lights: &mut Vec<&'a dyn Light>) {
+        self.left.collect_lights(lights);
+        self.right.collect_lights(lights);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use scene::lambertian::Lambertian;
+    use scene::sphere::Sphere;
+    use std::sync::Arc;
+    use vector_math::vec3::Vec3;
+
+    fn random_spheres(rng: &mut StdRng, count: usize) -> Vec<Box<dyn Hitable>> {
+        (0..count)
+            .map(|_| {
+                let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+                let sphere = Sphere::new(
+                    20.0 * rng.gen::<f32>() - 10.0,
+                    20.0 * rng.gen::<f32>() - 10.0,
+                    20.0 * rng.gen::<f32>() - 10.0,
+                    0.1 + rng.gen::<f32>(),
+                    material,
+                );
+                Box::new(sphere) as Box<dyn Hitable>
+            })
+            .collect()
+    }
+
+    fn random_ray(rng: &mut StdRng) -> Ray {
+        let origin = Vec3::new(
+            30.0 * rng.gen::<f32>() - 15.0,
+            30.0 * rng.gen::<f32>() - 15.0,
+            30.0 * rng.gen::<f32>() - 15.0,
+        );
+        let target = Vec3::new(
+            10.0 * rng.gen::<f32>() - 5.0,
+            10.0 * rng.gen::<f32>() - 5.0,
+            10.0 * rng.gen::<f32>() - 5.0,
+        );
+        Ray::new(origin, (target - origin).normalized())
+    }
+
+    #[test]
+    fn bvh_matches_linear_list() {
+        for &count in &[1, 2, 3, 7, 64, 500] {
+            let list = HitableList {
+                list: random_spheres(&mut StdRng::seed_from_u64(count as u64), count),
+            };
+            let bvh = BvhNode::build(random_spheres(
+                &mut StdRng::seed_from_u64(count as u64),
+                count,
+            ));
+
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut hits = 0;
+            for _ in 0..2000 {
+                let ray = random_ray(&mut rng);
+                let expected = list.hit_test(&ray, 0.001, f32::MAX).map(|hit| hit.t);
+                assert_eq!(
+                    bvh.hit_test(&ray, 0.001, f32::MAX).map(|hit| hit.t),
+                    expected
+                );
+                if expected.is_some() {
+                    hits += 1;
+                }
+            }
+            assert!(hits > 0);
+        }
+    }
+
+    #[test]
+    fn bvh_respects_t_range() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let list = HitableList {
+            list: random_spheres(&mut StdRng::seed_from_u64(3), 100),
+        };
+        let bvh = BvhNode::build(random_spheres(&mut StdRng::seed_from_u64(3), 100));
+
+        for _ in 0..1000 {
+            let ray = random_ray(&mut rng);
+            let t_min = 10.0 * rng.gen::<f32>();
+            let t_max = t_min + 10.0 * rng.gen::<f32>();
+            let expected = list.hit_test(&ray, t_min, t_max).map(|hit| hit.t);
+            assert_eq!(bvh.hit_test(&ray, t_min, t_max).map(|hit| hit.t), expected);
+            // Spheres are opaque, so shadow rays see exactly the same occlusion.
+            let visible = if expected.is_some() { 0.0 } else { 1.0 };
+            assert_eq!(list.transmittance(&ray, t_min, t_max), visible);
+            assert_eq!(bvh.transmittance(&ray, t_min, t_max), visible);
+        }
+    }
+
+    #[test]
+    fn bvh_keeps_non_finite_bounds_out_of_the_tree() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut spheres = random_spheres(&mut rng, 20);
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        spheres.push(Box::new(Sphere::new(
+            f32::NAN,
+            0.0,
+            0.0,
+            1.0,
+            material.clone(),
+        )));
+        spheres.push(Box::new(Sphere::new(
+            0.0,
+            0.0,
+            0.0,
+            f32::INFINITY,
+            material,
+        )));
+        let bvh = BvhNode::build(spheres);
+
+        let list = HitableList {
+            list: random_spheres(&mut StdRng::seed_from_u64(5), 20),
+        };
+        for _ in 0..200 {
+            let ray = random_ray(&mut rng);
+            let expected = list.hit_test(&ray, 0.001, f32::MAX).map(|hit| hit.t);
+            assert_eq!(
+                bvh.hit_test(&ray, 0.001, f32::MAX).map(|hit| hit.t),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn bvh_bounds_contain_all_objects() {
+        let spheres = random_spheres(&mut StdRng::seed_from_u64(11), 50);
+        let expected = spheres
+            .iter()
+            .fold(Aabb::empty(), |b, s| b.union(&s.bounding_box().unwrap()));
+        let bvh = BvhNode::build(spheres);
+        assert_eq!(bvh.bounding_box(), Some(expected));
+    }
+}
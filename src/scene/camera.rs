@@ -1,4 +1,4 @@
-use vector_math::random_methods::random_in_unit_disk;
+use vector_math::random_methods::{random, random_in_unit_disk};
 use vector_math::ray::Ray;
 use vector_math::vec3::Vec3;
 
@@ -10,11 +10,22 @@ pub struct Camera {
     lens_radius: f32,
     up: Vec3,
     right: Vec3,
+    /// Rays are fired at uniformly random times in `[shutter_open, shutter_close]`.
+    pub shutter_open: f32,
+    pub shutter_close: f32,
 }
 
 impl Camera {
     /// `fov` is vertical field-of-view.
-    pub fn new(origin: Vec3, look_at: Vec3, up: Vec3, fov: f32, aspect: f32, aperture: f32, focus_dist: f32) -> Self {
+    pub fn new(
+        origin: Vec3,
+        look_at: Vec3,
+        up: Vec3,
+        fov: f32,
+        aspect: f32,
+        aperture: f32,
+        focus_dist: f32,
+    ) -> Self {
         let theta = fov * std::f32::consts::PI / 180.0;
         let half_height = (theta / 2.0).tan();
         let half_width = aspect * half_height;
@@ -25,21 +36,38 @@ impl Camera {
 
         Camera {
             origin,
-            lower_left_corner: origin - (half_width * right + half_height * up_orthonormal + forward) * focus_dist,
+            lower_left_corner: origin
+                - (half_width * right + half_height * up_orthonormal + forward) * focus_dist,
             horizontal: 2.0 * half_width * focus_dist * right,
             vertical: 2.0 * half_height * focus_dist * up_orthonormal,
             lens_radius: aperture / 2.0,
             right,
             up: up_orthonormal,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         }
     }
 
+    /// Keeps the shutter open from `open` to `close`, blurring objects that move in that interval.
+    pub fn with_shutter(mut self, open: f32, close: f32) -> Self {
+        self.shutter_open = open;
+        self.shutter_close = close;
+        self
+    }
+
     pub fn get_ray(&self, s: f32, t: f32) -> Ray {
         let rd = self.lens_radius * random_in_unit_disk();
         let offset = self.right * rd.x + self.up * rd.y;
-        Ray::new(
+        // Instantaneous cameras skip the random draw, keeping still renders' samples unchanged.
+        let time = if self.shutter_close > self.shutter_open {
+            self.shutter_open + random::<f32>() * (self.shutter_close - self.shutter_open)
+        } else {
+            self.shutter_open
+        };
+        Ray::at_time(
             self.origin + offset,
             self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
+            time,
         )
     }
 }
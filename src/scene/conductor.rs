@@ -0,0 +1,329 @@
+use scene::hitable::HitRecord;
+use scene::light_ray::LightRay;
+use scene::material::Material;
+use scene::surface_interface::reflect;
+use std::f32::consts::PI;
+use vector_math::random_methods::random;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Fraction of unpolarized light a conductor with complex index of refraction `eta + i k` reflects
+/// at incidence angle `acos(cos_theta)`.
+pub fn fresnel_conductor(cos_theta: f32, eta: f32, k: f32) -> f32 {
+    let cos2 = cos_theta * cos_theta;
+    let sin2 = 1.0 - cos2;
+    let t0 = eta * eta - k * k - sin2;
+    let a2_plus_b2 = f32::sqrt(t0 * t0 + 4.0 * eta * eta * k * k);
+    let a = f32::sqrt((0.5 * (a2_plus_b2 + t0)).max(0.0));
+
+    let t1 = a2_plus_b2 + cos2;
+    let t2 = 2.0 * cos_theta * a;
+    let perpendicular = (t1 - t2) / (t1 + t2);
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let parallel = perpendicular * (t3 - t4) / (t3 + t4);
+    0.5 * (perpendicular + parallel)
+}
+
+/// Metal with a GGX (Trowbridge-Reitz) microfacet surface. Reflectance follows from the complex
+/// index of refraction, one `eta` and `k` per RGB channel; roughness may differ along and across
+/// the tangent, which circles the y axis so that anisotropic surfaces look brushed around it.
+#[derive(Clone, Debug)]
+pub struct Conductor {
+    pub eta: Vec3,
+    pub k: Vec3,
+    /// Perceptual roughness along the tangent; the GGX width is its square.
+    pub roughness_u: f32,
+    /// Perceptual roughness along the bitangent.
+    pub roughness_v: f32,
+}
+
+impl Conductor {
+    pub fn new(eta: Vec3, k: Vec3, roughness: f32) -> Self {
+        Conductor::anisotropic(eta, k, roughness, roughness)
+    }
+
+    pub fn anisotropic(eta: Vec3, k: Vec3, roughness_u: f32, roughness_v: f32) -> Self {
+        Conductor {
+            eta,
+            k,
+            roughness_u,
+            roughness_v,
+        }
+    }
+
+    pub fn gold(roughness: f32) -> Self {
+        Conductor::new(
+            Vec3::new(0.143, 0.374, 1.442),
+            Vec3::new(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: f32) -> Self {
+        Conductor::new(
+            Vec3::new(0.200, 0.924, 1.102),
+            Vec3::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    pub fn aluminium(roughness: f32) -> Self {
+        Conductor::new(
+            Vec3::new(1.657, 0.880, 0.521),
+            Vec3::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+
+    /// The metal called `name`: `gold`, `copper` or `aluminium` (or `aluminum`).
+    pub fn from_name(name: &str, roughness: f32) -> Option<Self> {
+        match name {
+            "gold" => Some(Conductor::gold(roughness)),
+            "copper" => Some(Conductor::copper(roughness)),
+            "aluminium" | "aluminum" => Some(Conductor::aluminium(roughness)),
+            _ => None,
+        }
+    }
+
+    pub fn fresnel(&self, cos_theta: f32) -> Vec3 {
+        Vec3::new(
+            fresnel_conductor(cos_theta, self.eta.x, self.k.x),
+            fresnel_conductor(cos_theta, self.eta.y, self.k.y),
+            fresnel_conductor(cos_theta, self.eta.z, self.k.z),
+        )
+    }
+
+    /// GGX widths along the tangent and bitangent, kept off zero where the density would overflow.
+    fn alpha(&self) -> (f32, f32) {
+        (
+            (self.roughness_u * self.roughness_u).max(1e-4),
+            (self.roughness_v * self.roughness_v).max(1e-4),
+        )
+    }
+
+    /// Tangent, bitangent and normal at the hit.
+    fn frame(hit: &HitRecord) -> (Vec3, Vec3, Vec3) {
+        let normal = hit.shading_normal;
+        let around = Vec3::new(0.0, 1.0, 0.0).cross(normal);
+        if around.squared_magnitude() < 1e-8 {
+            let (tangent, bitangent) = normal.orthonormal_basis();
+            return (tangent, bitangent, normal);
+        }
+        let tangent = around.normalized();
+        (tangent, normal.cross(tangent), normal)
+    }
+
+    fn to_local(frame: &(Vec3, Vec3, Vec3), v: &Vec3) -> Vec3 {
+        Vec3::new(v.dot(frame.0), v.dot(frame.1), v.dot(frame.2))
+    }
+
+    /// Density of microfacet normals `h`, in the local frame.
+    fn distribution(&self, h: &Vec3) -> f32 {
+        let (ax, ay) = self.alpha();
+        let e = (h.x / ax).powi(2) + (h.y / ay).powi(2) + h.z * h.z;
+        1.0 / (PI * ax * ay * e * e)
+    }
+
+    /// Smith's `Λ`, from which the masking of one direction follows as `1 / (1 + Λ)`.
+    fn lambda(&self, w: &Vec3) -> f32 {
+        let (ax, ay) = self.alpha();
+        let tan2 = ((ax * w.x).powi(2) + (ay * w.y).powi(2)) / (w.z * w.z);
+        0.5 * (f32::sqrt(1.0 + tan2) - 1.0)
+    }
+
+    /// Samples a microfacet normal visible from `wo` (Heitz, "Sampling the GGX Distribution of
+    /// Visible Normals"), in the local frame.
+    fn sample_visible_normal(&self, wo: &Vec3) -> Vec3 {
+        let (ax, ay) = self.alpha();
+        // Stretch to the hemisphere configuration, where visible normals are a projected disk.
+        let v = Vec3::new(ax * wo.x, ay * wo.y, wo.z).normalized();
+        let length_squared = v.x * v.x + v.y * v.y;
+        let t1 = if length_squared > 0.0 {
+            Vec3::new(-v.y, v.x, 0.0) / length_squared.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = v.cross(t1);
+
+        let r = random::<f32>().sqrt();
+        let phi = 2.0 * PI * random::<f32>();
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + v.z);
+        let p2 = (1.0 - s) * f32::sqrt(1.0 - p1 * p1) + s * r * phi.sin();
+        let n = p1 * t1 + p2 * t2 + f32::sqrt((1.0 - p1 * p1 - p2 * p2).max(0.0)) * v;
+        Vec3::new(ax * n.x, ay * n.y, n.z.max(0.0)).normalized()
+    }
+
+    /// Local view and light directions for `eval` and `pdf`, or `None` below the surface.
+    fn local_directions(incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> Option<(Vec3, Vec3)> {
+        let frame = Conductor::frame(hit);
+        let wo = Conductor::to_local(&frame, &-incident.direction.normalized());
+        let wi = Conductor::to_local(&frame, scattered);
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return None;
+        }
+        Some((wo, wi))
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay> {
+        let view = -incident.ray.direction.normalized();
+        if self.is_specular() {
+            let reflected = reflect(&-view, &hit.shading_normal);
+            let outgoing_ray = Ray::at_time(hit.point, reflected, incident.ray.time);
+            let fresnel = self.fresnel(view.dot(hit.shading_normal).max(0.0));
+            return Some(LightRay::new(outgoing_ray, incident.color * fresnel));
+        }
+
+        let frame = Conductor::frame(hit);
+        let wo = Conductor::to_local(&frame, &view);
+        if wo.z <= 0.0 {
+            return None;
+        }
+        let h = self.sample_visible_normal(&wo);
+        let wi = reflect(&-wo, &h);
+        if wi.z <= 0.0 {
+            return None;
+        }
+
+        // With visible normals sampled exactly, `eval * cos / pdf` reduces to F G2 / G1.
+        let masking = 1.0 + self.lambda(&wo);
+        let shadowing = 1.0 + self.lambda(&wo) + self.lambda(&wi);
+        let weight = self.fresnel(wo.dot(h)) * (masking / shadowing);
+        let direction = wi.x * frame.0 + wi.y * frame.1 + wi.z * frame.2;
+        let outgoing_ray = Ray::at_time(hit.point, direction, incident.ray.time);
+        Some(LightRay::new(outgoing_ray, incident.color * weight))
+    }
+
+    fn eval(&self, incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> Vec3 {
+        let (wo, wi) = match Conductor::local_directions(incident, scattered, hit) {
+            Some(directions) => directions,
+            None => return Vec3::zero(),
+        };
+        let h = (wo + wi).normalized();
+        let shadowing = 1.0 / (1.0 + self.lambda(&wo) + self.lambda(&wi));
+        self.fresnel(wo.dot(h)) * (self.distribution(&h) * shadowing / (4.0 * wo.z * wi.z))
+    }
+
+    fn pdf(&self, incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> f32 {
+        let (wo, wi) = match Conductor::local_directions(incident, scattered, hit) {
+            Some(directions) => directions,
+            None => return 0.0,
+        };
+        // The density of visible normals, G1(wo) max(0, wo·h) D(h) / wo.z, changed from half
+        // vectors to reflected directions by the Jacobian 1 / (4 wo·h).
+        let h = (wo + wi).normalized();
+        let masking = 1.0 / (1.0 + self.lambda(&wo));
+        masking * self.distribution(&h) / (4.0 * wo.z)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.roughness_u <= 0.0 && self.roughness_v <= 0.0
+    }
+
+    fn albedo(&self, _hit: &HitRecord) -> Vec3 {
+        self.fresnel(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vector_math::random_methods::{random_unit_vector, seed_thread_rng};
+
+    #[test]
+    fn fresnel_conductor_limits() {
+        let (eta, k) = (0.2, 3.9);
+        let normal = ((eta - 1.0) * (eta - 1.0) + k * k) / ((eta + 1.0) * (eta + 1.0) + k * k);
+        assert!((fresnel_conductor(1.0, eta, k) - normal).abs() < 1e-5);
+        assert!((fresnel_conductor(0.0, eta, k) - 1.0).abs() < 1e-5);
+        assert!(fresnel_conductor(0.05, eta, k) > normal);
+
+        // Gold reflects red far better than blue.
+        let gold = Conductor::gold(0.0).fresnel(1.0);
+        assert!(gold.x > 0.9 && gold.z < 0.4);
+        assert!(Conductor::from_name("aluminum", 0.0).is_some());
+        assert!(Conductor::from_name("tin", 0.0).is_none());
+    }
+
+    fn upward_hit<'a>(ray: &Ray, material: &'a Conductor) -> HitRecord<'a> {
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        HitRecord::new(ray, 1.0, normal, normal, (0.0, 0.0), material)
+    }
+
+    #[test]
+    fn sampling_matches_pdf_and_eval() {
+        seed_thread_rng(8);
+        let (eta, k) = (Vec3::new(0.2, 0.9, 1.1), Vec3::new(3.9, 2.4, 2.1));
+        for &(roughness_u, roughness_v) in &[(0.5, 0.5), (0.8, 0.8), (0.4, 0.9)] {
+            let metal = Conductor::anisotropic(eta, k, roughness_u, roughness_v);
+            let incident = Ray::new(
+                Vec3::new(-1.0, 1.0, 0.3),
+                Vec3::new(1.0, -0.6, -0.3).normalized(),
+            );
+            let hit = upward_hit(&incident, &metal);
+            let light_ray = LightRay::new(incident, Vec3::new(1.0, 1.0, 1.0));
+
+            // Uniform sphere sampling of the density and the reflected energy ...
+            let n = 200_000;
+            let mut integral = 0.0;
+            let mut energy = Vec3::zero();
+            for _ in 0..n {
+                let direction = random_unit_vector();
+                integral += metal.pdf(&incident, &direction, &hit);
+                let cos = direction.dot(hit.shading_normal).max(0.0);
+                energy += metal.eval(&incident, &direction, &hit) * cos;
+            }
+            integral *= 4.0 * PI / n as f32;
+            energy *= 4.0 * PI / n as f32;
+
+            // ... against what `scatter` produces.
+            let n = 50_000;
+            let mut kept = 0;
+            let mut weight = Vec3::zero();
+            for _ in 0..n {
+                if let Some(next) = metal.scatter(&light_ray, &hit) {
+                    kept += 1;
+                    weight += next.color;
+                }
+            }
+            let weight = weight / n as f32;
+            assert!(
+                (integral - kept as f32 / n as f32).abs() < 0.03,
+                "{} vs {}",
+                integral,
+                kept
+            );
+            assert!(
+                (energy - weight).magnitude() < 0.03,
+                "{:?} vs {:?}",
+                energy,
+                weight
+            );
+            assert!(weight.x <= 1.0 && weight.y <= 1.0 && weight.z <= 1.0);
+        }
+    }
+
+    #[test]
+    fn anisotropy_stretches_the_highlight_along_the_rougher_direction() {
+        let metal =
+            Conductor::anisotropic(Vec3::new(1.6, 0.9, 0.5), Vec3::new(9.2, 6.3, 4.8), 0.1, 0.5);
+        let incident = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let hit = upward_hit(&incident, &metal);
+        let (tangent, _, _) = Conductor::frame(&hit);
+        let tilted = |v: Vec3| (Vec3::new(0.0, 1.0, 0.0) + 0.3 * v).normalized();
+        let along = metal.pdf(&incident, &tilted(tangent), &hit);
+        let across = metal.pdf(&incident, &tilted(hit.shading_normal.cross(tangent)), &hit);
+        assert!(across > 10.0 * along, "{} vs {}", across, along);
+
+        let mirror = Conductor::aluminium(0.0);
+        assert!(mirror.is_specular());
+        let next = mirror
+            .scatter(&LightRay::new(incident, Vec3::new(1.0, 1.0, 1.0)), &hit)
+            .unwrap();
+        assert_eq!(next.ray.direction, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(next.color, mirror.fresnel(1.0));
+    }
+}
@@ -0,0 +1,164 @@
+use scene::cylinder::{axial_uv, intersect_cap, Part};
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::frame::AxisFrame;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Cone with a base of `radius` at `base`, narrowing to a point at `apex`, optionally closed by a
+/// flat base cap.
+pub struct Cone {
+    frame: AxisFrame,
+    pub radius: f32,
+    pub height: f32,
+    pub capped: bool,
+    pub material: Arc<dyn Material>,
+}
+
+impl Cone {
+    pub fn new(
+        base: Vec3,
+        apex: Vec3,
+        radius: f32,
+        capped: bool,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Cone {
+            frame: AxisFrame::new(base, apex - base),
+            radius,
+            height: (apex - base).magnitude(),
+            capped,
+            material,
+        }
+    }
+
+    /// The side is `x² + z² = k² (h - y)²` with `k = radius / height`, for `0 <= y <= h`.
+    fn intersect_side(&self, local: &Ray, t_min: f32, t_max: f32) -> Option<f32> {
+        let (o, d) = (local.origin, local.direction);
+        let k2 = (self.radius / self.height).powi(2);
+        let h = self.height - o.y;
+        let a = d.x * d.x + d.z * d.z - k2 * d.y * d.y;
+        let b = o.x * d.x + o.z * d.z + k2 * h * d.y;
+        let c = o.x * o.x + o.z * o.z - k2 * h * h;
+
+        let in_range = |t: f32| {
+            let y = o.y + t * d.y;
+            t > t_min && t < t_max && y >= 0.0 && y <= self.height
+        };
+        if a.abs() < 1e-12 {
+            // Parallel to the side: a single crossing.
+            return Some(-c / (2.0 * b)).filter(|&t| in_range(t));
+        }
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (t0, t1) = ((-b - root) / a, (-b + root) / a);
+        // The quadric is a double cone, so the roots are not ordered by the sign of `a` alone.
+        let (t0, t1) = (t0.min(t1), t0.max(t1));
+        [t0, t1].iter().cloned().find(|&t| in_range(t))
+    }
+}
+
+impl Hitable for Cone {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let local = self.frame.to_local_ray(ray);
+        let mut nearest = self
+            .intersect_side(&local, t_min, t_max)
+            .map(|t| (t, Part::Side));
+        if self.capped {
+            let t_max = nearest.map_or(t_max, |(t, _)| t);
+            if let Some(t) = intersect_cap(&local, 0.0, self.radius, t_min, t_max) {
+                nearest = Some((t, Part::Bottom));
+            }
+        }
+
+        let (t, part) = nearest?;
+        let p = local.point_at(t);
+        let local_normal = match part {
+            // The gradient of the implicit surface, which vanishes at the apex.
+            Part::Side => {
+                let k2 = (self.radius / self.height).powi(2);
+                let gradient = Vec3::new(p.x, k2 * (self.height - p.y), p.z);
+                if gradient.squared_magnitude() > 0.0 {
+                    gradient.normalized()
+                } else {
+                    Vec3::new(0.0, 1.0, 0.0)
+                }
+            }
+            _ => Vec3::new(0.0, -1.0, 0.0),
+        };
+        let normal = self.frame.to_world_vector(&local_normal);
+        let uv = axial_uv(&p, part, self.radius, self.height);
+        Some(HitRecord::new(
+            ray,
+            t,
+            normal,
+            normal,
+            uv,
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let apex = self.frame.origin + self.height * self.frame.axis;
+        Some(self.frame.circle_bounds(0.0, self.radius).grow(apex))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+
+    #[test]
+    fn cone_side_and_base() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let cone = Cone::new(
+            Vec3::zero(),
+            Vec3::new(0.0, 2.0, 0.0),
+            1.0,
+            true,
+            material.clone(),
+        );
+        let open = Cone::new(Vec3::zero(), Vec3::new(0.0, 2.0, 0.0), 1.0, false, material);
+
+        // Halfway up the radius is 0.5, and the normal leans up by atan(1/2).
+        let side = Ray::new(Vec3::new(3.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let hit = cone.hit_test(&side, 0.0, f32::MAX).unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-5);
+        assert!((hit.geometric_normal - Vec3::new(2.0, 1.0, 0.0).normalized()).magnitude() < 1e-5);
+        assert!(hit.front_face);
+        assert!((hit.uv.1 - 0.5).abs() < 1e-5);
+
+        let up = Ray::new(Vec3::new(0.3, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let hit = cone.hit_test(&up, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 1.0);
+        assert_eq!(hit.geometric_normal, Vec3::new(0.0, -1.0, 0.0));
+        let hit = open.hit_test(&up, 0.0, f32::MAX).unwrap();
+        assert!((hit.point.y - 1.4).abs() < 1e-5);
+        assert!(!hit.front_face);
+
+        let apex = Ray::new(Vec3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(
+            cone.hit_test(&apex, 0.0, f32::MAX)
+                .unwrap()
+                .geometric_normal,
+            Vec3::new(0.0, 1.0, 0.0)
+        );
+
+        // The mirror-image nappe above the apex is not part of the cone.
+        let above = Ray::new(Vec3::new(3.0, 3.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(cone.hit_test(&above, 0.0, f32::MAX).is_none());
+        assert_eq!(
+            cone.bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(-1.0, 0.0, -1.0),
+                Vec3::new(1.0, 2.0, 1.0)
+            ))
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::random_methods::random;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Fog or smoke of uniform `density` filling a closed, convex `boundary`. Rays travel an
+/// exponentially distributed distance through it and then scatter off `phase_function`, a
+/// volumetric material such as `Isotropic`. Shadow rays are dimmed by the exact transmittance.
+pub struct ConstantMedium {
+    pub boundary: Box<dyn Hitable>,
+    /// Extinction coefficient: the chance of scattering per unit distance.
+    pub density: f32,
+    pub phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    pub fn new(
+        boundary: Box<dyn Hitable>,
+        density: f32,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        ConstantMedium {
+            boundary,
+            density,
+            phase_function,
+        }
+    }
+
+    /// The part of `(t_min, t_max)` inside the boundary.
+    fn segment(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        // Crossings along the whole line, so rays starting inside still know where they are.
+        let entry = self.boundary.hit_test(ray, -f32::MAX, f32::MAX)?;
+        let exit = self.boundary.hit_test(ray, entry.t + 1e-4, f32::MAX)?;
+        let start = entry.t.max(t_min);
+        let end = exit.t.min(t_max);
+        if start >= end {
+            return None;
+        }
+        Some((start, end))
+    }
+}
+
+impl Hitable for ConstantMedium {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let (start, end) = self.segment(ray, t_min, t_max)?;
+        let speed = ray.direction.magnitude();
+        let distance = -(1.0 - random::<f32>()).ln() / self.density;
+        if distance >= (end - start) * speed {
+            return None;
+        }
+
+        // Scattering points have no surface; the normal is arbitrary.
+        let t = start + distance / speed;
+        let normal = Vec3::new(1.0, 0.0, 0.0);
+        let mut hit = HitRecord::new(
+            ray,
+            t,
+            normal,
+            normal,
+            (0.0, 0.0),
+            self.phase_function.as_ref(),
+        );
+        hit.front_face = true;
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        match self.segment(ray, t_min, t_max) {
+            Some((start, end)) => {
+                f32::exp(-self.density * (end - start) * ray.direction.magnitude())
+            }
+            None => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::phase_function::Isotropic;
+    use scene::sphere::Sphere;
+    use vector_math::random_methods::seed_thread_rng;
+
+    #[test]
+    fn transmittance_falls_off_exponentially() {
+        let phase = Arc::new(Isotropic::new(1.0, 1.0, 1.0));
+        let boundary = Box::new(Sphere::new(0.0, 0.0, 0.0, 1.0, phase.clone()));
+        let fog = ConstantMedium::new(boundary, 0.5, phase);
+
+        // Through the center the ray spends 2 units inside, and 1 unit when starting there.
+        seed_thread_rng(9);
+        let n = 20_000;
+        let through = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        let inside = Ray::new(Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        let passed = |ray: &Ray, t_max: f32| {
+            (0..n)
+                .filter(|_| fog.hit_test(ray, 0.001, t_max).is_none())
+                .count() as f32
+                / n as f32
+        };
+        assert!((passed(&through, f32::MAX) - f32::exp(-1.0)).abs() < 0.01);
+        assert!((passed(&inside, f32::MAX) - f32::exp(-0.5)).abs() < 0.01);
+
+        for _ in 0..100 {
+            if let Some(hit) = fog.hit_test(&through, 0.001, f32::MAX) {
+                assert!(hit.point.magnitude() <= 1.0 + 1e-5);
+                assert!(hit.material.is_volumetric());
+            }
+        }
+        // A short segment that stops before the boundary is less likely to scatter.
+        assert!((passed(&through, 2.25) - f32::exp(-0.25)).abs() < 0.01);
+        assert!((fog.transmittance(&through, 0.001, f32::MAX) - f32::exp(-1.0)).abs() < 1e-3);
+        assert!((fog.transmittance(&inside, 0.001, f32::MAX) - f32::exp(-0.5)).abs() < 1e-3);
+    }
+}
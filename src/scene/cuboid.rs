@@ -0,0 +1,143 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Axis-aligned box between `min` and `max`. Each face has UVs running from 0 to 1 along the next
+/// two axes in x, y, z order.
+pub struct Cuboid {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub material: Arc<dyn Material>,
+}
+
+impl Cuboid {
+    pub fn new(a: Vec3, b: Vec3, material: Arc<dyn Material>) -> Self {
+        Cuboid {
+            min: a.min(b),
+            max: a.max(b),
+            material,
+        }
+    }
+
+    fn hit_record(&self, ray: &Ray, t: f32, axis: usize) -> HitRecord<'_> {
+        let point = ray.point_at(t);
+        let mut normal = [0.0; 3];
+        normal[axis] = if point[axis] - self.min[axis] < self.max[axis] - point[axis] {
+            -1.0
+        } else {
+            1.0
+        };
+        let outward_normal = Vec3::new(normal[0], normal[1], normal[2]);
+
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let coordinate =
+            |i: usize| ((point[i] - self.min[i]) / (self.max[i] - self.min[i])).clamp(0.0, 1.0);
+        let uv = (coordinate(u), coordinate(v));
+        HitRecord::new(
+            ray,
+            t,
+            outward_normal,
+            outward_normal,
+            uv,
+            self.material.as_ref(),
+        )
+    }
+}
+
+impl Hitable for Cuboid {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        // Slab test that also tracks which axis the ray enters and leaves through.
+        let (mut near, mut near_axis) = (f32::MIN, 0);
+        let (mut far, mut far_axis) = (f32::MAX, 0);
+        for axis in 0..3 {
+            let inv_d = 1.0 / ray.direction[axis];
+            let mut t0 = (self.min[axis] - ray.origin[axis]) * inv_d;
+            let mut t1 = (self.max[axis] - ray.origin[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // Comparisons with NaN fail, skipping the 0 * inf of rays lying in a face plane.
+            if t0 > near {
+                near = t0;
+                near_axis = axis;
+            }
+            if t1 < far {
+                far = t1;
+                far_axis = axis;
+            }
+        }
+        if near > far {
+            return None;
+        }
+
+        if near > t_min && near < t_max {
+            Some(self.hit_record(ray, near, near_axis))
+        } else if far > t_min && far < t_max {
+            Some(self.hit_record(ray, far, far_axis))
+        } else {
+            None
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.min, self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+
+    #[test]
+    fn cuboid_normals_point_out_of_each_face() {
+        let cuboid = Cuboid::new(
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(-1.0, 0.0, -2.0),
+            Arc::new(Lambertian::new(0.5, 0.5, 0.5)),
+        );
+        let cases = [
+            (
+                Vec3::new(5.0, 0.5, 0.0),
+                Vec3::new(-1.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                4.0,
+            ),
+            (
+                Vec3::new(0.0, -3.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(0.0, -1.0, 0.0),
+                3.0,
+            ),
+            (
+                Vec3::new(0.5, 0.5, -4.0),
+                Vec3::new(0.0, 0.0, 2.0),
+                Vec3::new(0.0, 0.0, -1.0),
+                1.0,
+            ),
+        ];
+        for &(origin, direction, normal, t) in &cases {
+            let hit = cuboid
+                .hit_test(&Ray::new(origin, direction), 0.0, f32::MAX)
+                .unwrap();
+            assert_eq!(hit.t, t);
+            assert_eq!(hit.geometric_normal, normal);
+            assert!(hit.front_face);
+        }
+
+        // From inside, the exit face is hit with its outward normal flipped toward the ray.
+        let inside = Ray::new(Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let hit = cuboid.hit_test(&inside, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 0.5);
+        assert_eq!(hit.geometric_normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(!hit.front_face);
+        // UVs on the top face run along z, then x.
+        assert_eq!(hit.uv, (2.0 / 3.0, 0.5));
+
+        let miss = Ray::new(Vec3::new(5.0, 2.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(cuboid.hit_test(&miss, 0.0, f32::MAX).is_none());
+    }
+}
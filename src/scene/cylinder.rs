@@ -0,0 +1,188 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::frame::AxisFrame;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Where a local-space ray crosses the disk of `radius` around the y axis at height `y`.
+pub fn intersect_cap(local: &Ray, y: f32, radius: f32, t_min: f32, t_max: f32) -> Option<f32> {
+    let t = (y - local.origin.y) / local.direction.y;
+    if !(t > t_min && t < t_max) {
+        return None;
+    }
+    let p = local.point_at(t);
+    if p.x * p.x + p.z * p.z > radius * radius {
+        return None;
+    }
+    Some(t)
+}
+
+/// Angle of a local point around the y axis as a fraction of a turn.
+pub fn turn_fraction(p: &Vec3) -> f32 {
+    f32::atan2(p.z, p.x).rem_euclid(2.0 * PI) / (2.0 * PI)
+}
+
+/// Which part of a cylinder or cone a ray hit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Part {
+    Side,
+    Bottom,
+    Top,
+}
+
+/// UVs for a hit at local point `p` on a shape of `height` whose caps have `radius`: around the
+/// axis and up it on the side, and around and out from the center on the caps.
+pub fn axial_uv(p: &Vec3, part: Part, radius: f32, height: f32) -> (f32, f32) {
+    match part {
+        Part::Side => (turn_fraction(p), (p.y / height).clamp(0.0, 1.0)),
+        Part::Bottom | Part::Top => (turn_fraction(p), f32::sqrt(p.x * p.x + p.z * p.z) / radius),
+    }
+}
+
+/// Cylinder of `radius` from `base` to `top`, optionally closed by flat caps.
+pub struct Cylinder {
+    frame: AxisFrame,
+    pub radius: f32,
+    pub height: f32,
+    pub capped: bool,
+    pub material: Arc<dyn Material>,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Vec3,
+        top: Vec3,
+        radius: f32,
+        capped: bool,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Cylinder {
+            frame: AxisFrame::new(base, top - base),
+            radius,
+            height: (top - base).magnitude(),
+            capped,
+            material,
+        }
+    }
+
+    fn intersect_side(&self, local: &Ray, t_min: f32, t_max: f32) -> Option<f32> {
+        let (o, d) = (local.origin, local.direction);
+        let a = d.x * d.x + d.z * d.z;
+        let b = o.x * d.x + o.z * d.z;
+        let c = o.x * o.x + o.z * o.z - self.radius * self.radius;
+        let discriminant = b * b - a * c;
+        if a == 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [(-b - root) / a, (-b + root) / a]
+            .iter()
+            .cloned()
+            .find(|&t| {
+                let y = o.y + t * d.y;
+                t > t_min && t < t_max && y >= 0.0 && y <= self.height
+            })
+    }
+}
+
+impl Hitable for Cylinder {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let local = self.frame.to_local_ray(ray);
+        let mut nearest = self
+            .intersect_side(&local, t_min, t_max)
+            .map(|t| (t, Part::Side));
+        if self.capped {
+            let t_max = nearest.map_or(t_max, |(t, _)| t);
+            if let Some(t) = intersect_cap(&local, 0.0, self.radius, t_min, t_max) {
+                nearest = Some((t, Part::Bottom));
+            }
+            let t_max = nearest.map_or(t_max, |(t, _)| t);
+            if let Some(t) = intersect_cap(&local, self.height, self.radius, t_min, t_max) {
+                nearest = Some((t, Part::Top));
+            }
+        }
+
+        let (t, part) = nearest?;
+        let p = local.point_at(t);
+        let local_normal = match part {
+            Part::Side => Vec3::new(p.x, 0.0, p.z) / self.radius,
+            Part::Bottom => Vec3::new(0.0, -1.0, 0.0),
+            Part::Top => Vec3::new(0.0, 1.0, 0.0),
+        };
+        let normal = self.frame.to_world_vector(&local_normal);
+        let uv = axial_uv(&p, part, self.radius, self.height);
+        Some(HitRecord::new(
+            ray,
+            t,
+            normal,
+            normal,
+            uv,
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bottom = self.frame.circle_bounds(0.0, self.radius);
+        Some(bottom.union(&self.frame.circle_bounds(self.height, self.radius)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).magnitude() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn cylinder_side_and_caps() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let base = Vec3::new(0.0, 1.0, 0.0);
+        let capped = Cylinder::new(base, Vec3::new(0.0, 3.0, 0.0), 0.5, true, material.clone());
+        let open = Cylinder::new(base, Vec3::new(0.0, 3.0, 0.0), 0.5, false, material);
+
+        let side = Ray::new(Vec3::new(2.0, 2.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let hit = capped.hit_test(&side, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 1.5);
+        assert_near(hit.geometric_normal, Vec3::new(1.0, 0.0, 0.0));
+        assert!(hit.front_face);
+        assert!((hit.uv.1 - 0.5).abs() < 1e-6);
+
+        let down = Ray::new(Vec3::new(0.2, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let hit = capped.hit_test(&down, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 2.0);
+        assert_near(hit.geometric_normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(open.hit_test(&down, 0.0, f32::MAX).is_none());
+
+        // Through the open end, the ray meets the inside of the wall.
+        let slanted = Ray::new(Vec3::new(0.0, 4.0, 0.0), Vec3::new(0.25, -1.0, 0.0));
+        let hit = open.hit_test(&slanted, 0.0, f32::MAX).unwrap();
+        assert!((hit.point.y - 2.0).abs() < 1e-5);
+        assert!(!hit.front_face);
+
+        assert_eq!(
+            capped.bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(-0.5, 1.0, -0.5),
+                Vec3::new(0.5, 3.0, 0.5)
+            ))
+        );
+    }
+
+    #[test]
+    fn tilted_cylinder_normals_are_perpendicular_to_its_axis() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let cylinder = Cylinder::new(Vec3::zero(), Vec3::new(2.0, 2.0, 0.0), 0.5, true, material);
+        let axis = Vec3::new(1.0, 1.0, 0.0).normalized();
+        let ray = Ray::new(Vec3::new(1.0, 1.0, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = cylinder.hit_test(&ray, 0.0, f32::MAX).unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-5);
+        assert!(hit.geometric_normal.dot(axis).abs() < 1e-5);
+        assert_near(hit.geometric_normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+}
@@ -1,7 +1,8 @@
-use rand::prelude::*;
+use scene::hitable::HitRecord;
 use scene::light_ray::LightRay;
 use scene::material::Material;
 use scene::surface_interface::{reflect, refract, schlick};
+use vector_math::random_methods::random;
 use vector_math::ray::Ray;
 use vector_math::vec3::Vec3;
 
@@ -17,47 +18,44 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(
-        &self,
-        incident: &LightRay,
-        hit_point: &Vec3,
-        surface_normal: &Vec3,
-    ) -> Option<LightRay> {
+    fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay> {
         let color = Vec3::new(1.0, 1.0, 1.0);
-        let cos = incident.ray.direction.dot(*surface_normal) / incident.ray.direction.magnitude();
+        let normal = hit.shading_normal;
+        let cos = -incident.ray.direction.dot(normal) / incident.ray.direction.magnitude();
 
-        let outward_normal: Vec3;
         let ni_over_nt: f32;
         let cosine: f32;
 
-        if incident.ray.direction.dot(*surface_normal) > 0.0 {
-            outward_normal = -(*surface_normal);
+        if hit.front_face {
+            ni_over_nt = 1.0 / self.refractive_index;
+            cosine = cos;
+        } else {
             ni_over_nt = self.refractive_index;
             cosine = self.refractive_index * cos;
-        } else {
-            outward_normal = *surface_normal;
-            ni_over_nt = 1.0 / self.refractive_index;
-            cosine = -cos;
         }
 
-        let refracted = refract(&incident.ray.direction, &outward_normal, ni_over_nt);
+        let refracted = refract(&incident.ray.direction, &normal, ni_over_nt);
+        let time = incident.ray.time;
 
         match refracted {
             Some(refracted) => {
-                let mut rng = thread_rng(); // todo: consider the cost of this call, perhaps we need a shared ref.
-                if rng.gen::<f32>() < schlick(cosine, self.refractive_index) {
+                if random::<f32>() < schlick(cosine, self.refractive_index) {
                     let ray =
-                        Ray::new(*hit_point, reflect(&incident.ray.direction, surface_normal));
+                        Ray::at_time(hit.point, reflect(&incident.ray.direction, &normal), time);
                     Some(LightRay::new(ray, incident.color * color))
                 } else {
-                    let ray = Ray::new(*hit_point, refracted);
+                    let ray = Ray::at_time(hit.point, refracted, time);
                     Some(LightRay::new(ray, incident.color * color))
                 }
             }
             None => {
-                let ray = Ray::new(*hit_point, reflect(&incident.ray.direction, surface_normal));
+                let ray = Ray::at_time(hit.point, reflect(&incident.ray.direction, &normal), time);
                 Some(LightRay::new(ray, incident.color * color))
             }
         }
     }
+
+    fn albedo(&self, _hit: &HitRecord) -> Vec3 {
+        Vec3::new(1.0, 1.0, 1.0)
+    }
 }
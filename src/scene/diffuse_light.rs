@@ -0,0 +1,36 @@
+use scene::hitable::HitRecord;
+use scene::light_ray::LightRay;
+use scene::material::Material;
+use scene::texture::{ConstantTexture, Texture};
+use std::sync::Arc;
+use vector_math::vec3::Vec3;
+
+/// Emissive surface radiating `emit` uniformly from both sides and absorbing all light it receives.
+#[derive(Clone)]
+pub struct DiffuseLight {
+    pub emit: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        DiffuseLight::textured(Arc::new(ConstantTexture::new(r, g, b)))
+    }
+
+    pub fn textured(emit: Arc<dyn Texture>) -> Self {
+        DiffuseLight { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _incident: &LightRay, _hit: &HitRecord) -> Option<LightRay> {
+        None
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        self.emit.value(hit.uv, &hit.point)
+    }
+
+    fn emits_light(&self) -> bool {
+        true
+    }
+}
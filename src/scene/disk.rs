@@ -0,0 +1,137 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::light::{sample_surface, surface_pdf, Light, LightSample};
+use scene::material::Material;
+use scene::plane::plane_intersection;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::random_methods::random;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Flat disk facing `normal`. UVs are the angle around the center, as a fraction of a turn, and the
+/// distance from it as a fraction of the radius.
+pub struct Disk {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub radius: f32,
+    pub material: Arc<dyn Material>,
+}
+
+impl Disk {
+    pub fn new(center: Vec3, normal: Vec3, radius: f32, material: Arc<dyn Material>) -> Self {
+        Disk {
+            center,
+            normal: normal.normalized(),
+            radius,
+            material,
+        }
+    }
+
+    pub fn area(&self) -> f32 {
+        PI * self.radius * self.radius
+    }
+}
+
+impl Hitable for Disk {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let t = plane_intersection(&self.center, &self.normal, ray, t_min, t_max)?;
+        let offset = ray.point_at(t) - self.center;
+        let distance_squared = offset.squared_magnitude();
+        if distance_squared > self.radius * self.radius {
+            return None;
+        }
+
+        let (tangent, bitangent) = self.normal.orthonormal_basis();
+        let angle = f32::atan2(offset.dot(bitangent), offset.dot(tangent)).rem_euclid(2.0 * PI);
+        let uv = (angle / (2.0 * PI), distance_squared.sqrt() / self.radius);
+        let mut hit = HitRecord::new(ray, t, self.normal, self.normal, uv, self.material.as_ref());
+        if self.material.emits_light() {
+            hit.light = Some(self);
+        }
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Along each axis the rim reaches radius times the sine of that axis' angle to the normal.
+        let n = self.normal;
+        let extent = |c: f32| self.radius * f32::sqrt((1.0 - c * c).max(0.0));
+        let e = Vec3::new(extent(n.x), extent(n.y), extent(n.z));
+        Some(Aabb::new(self.center - e, self.center + e))
+    }
+
+    fn collect_lights<'a>(&'a self, lights: &mut Vec<&'a dyn Light>) {
+        if self.material.emits_light() {
+            lights.push(self);
+        }
+    }
+}
+
+/// Samples points uniformly over the disk's area.
+impl Light for Disk {
+    fn sample(&self, origin: &Vec3) -> Option<LightSample> {
+        let (tangent, bitangent) = self.normal.orthonormal_basis();
+        let r = self.radius * f32::sqrt(random::<f32>());
+        let phi = 2.0 * PI * random::<f32>();
+        let point = self.center + r * (phi.cos() * tangent + phi.sin() * bitangent);
+        sample_surface(self, self.area(), origin, point)
+    }
+
+    fn pdf(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        surface_pdf(self, self.area(), origin, direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::diffuse_light::DiffuseLight;
+
+    #[test]
+    fn disk_hits_within_its_radius() {
+        let emitter = Arc::new(DiffuseLight::new(1.0, 1.0, 1.0));
+        let disk = Disk::new(
+            Vec3::new(0.0, 2.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            1.0,
+            emitter,
+        );
+        let toward = |x: f32, y: f32| Ray::new(Vec3::new(x, y, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = disk.hit_test(&toward(0.0, 2.5), 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 3.0);
+        assert_eq!(hit.geometric_normal, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(hit.uv.1, 0.5);
+        assert!(hit.light.is_some());
+        assert!(disk.hit_test(&toward(0.8, 2.8), 0.0, f32::MAX).is_none());
+        assert_eq!(
+            disk.bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(-1.0, 1.0, 0.0),
+                Vec3::new(1.0, 3.0, 0.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn disk_light_pdf_matches_its_solid_angle() {
+        use vector_math::random_methods::seed_thread_rng;
+
+        // Seen head-on from distance h, a disk of radius r subtends 2π(1 - h/√(h² + r²)).
+        let emitter = Arc::new(DiffuseLight::new(1.0, 1.0, 1.0));
+        let disk = Disk::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            emitter,
+        );
+        let origin = Vec3::new(0.0, 2.0, 0.0);
+        let solid_angle = 2.0 * PI * (1.0 - 2.0 / f32::sqrt(5.0));
+        seed_thread_rng(11);
+        let n = 100_000;
+        let mean_inverse_pdf: f32 = (0..n)
+            .map(|_| 1.0 / disk.sample(&origin).unwrap().pdf)
+            .sum::<f32>()
+            / n as f32;
+        assert!((mean_inverse_pdf / solid_angle - 1.0).abs() < 0.02);
+    }
+}
@@ -0,0 +1,349 @@
+use image::hdr::HDRDecoder;
+use scene::background::Background;
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use vector_math::distribution::Distribution2D;
+use vector_math::random_methods::random;
+use vector_math::ray::Ray;
+use vector_math::vec3::{luminance, Vec3};
+
+/// Equirectangular (latitude/longitude) environment light. The top row of the image is straight up
+/// (+y) and u runs around the horizon starting from +x toward +z.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>,
+    pub intensity: f32,
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    /// `pixels` are row-major from the top-left corner.
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3>) -> Self {
+        assert!(width > 0 && height > 0, "environment map is empty");
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "environment map pixel count does not match its size"
+        );
+
+        // Rows near the poles cover less solid angle, so weight them by sin(theta).
+        let mut weights = Vec::with_capacity(pixels.len());
+        for row in 0..height {
+            let sin_theta = f32::sin(PI * (row as f32 + 0.5) / height as f32);
+            for pixel in &pixels[row * width..(row + 1) * width] {
+                weights.push(luminance(*pixel).max(0.0) * sin_theta);
+            }
+        }
+
+        EnvironmentMap {
+            width,
+            height,
+            distribution: Distribution2D::new(&weights, width, height),
+            pixels,
+            intensity: 1.0,
+        }
+    }
+
+    /// Loads a Radiance `.hdr` or `.pfm` image, chosen by extension.
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        let (width, height, pixels) = match extension.as_deref() {
+            Some("hdr") | Some("pic") => read_hdr(BufReader::new(File::open(path)?))?,
+            Some("pfm") => read_pfm(BufReader::new(File::open(path)?))?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{}: environment maps must be .hdr or .pfm", path.display()),
+                ))
+            }
+        };
+
+        Ok(EnvironmentMap::new(width, height, pixels))
+    }
+
+    fn texel(&self, u: f32, v: f32) -> Vec3 {
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+        self.pixels[y * self.width + x]
+    }
+}
+
+/// Equirectangular coordinates of a direction, with v = 0 straight up.
+pub fn direction_to_uv(direction: &Vec3) -> (f32, f32) {
+    let d = direction.normalized();
+    let mut phi = f32::atan2(d.z, d.x);
+    if phi < 0.0 {
+        phi += 2.0 * PI;
+    }
+    let theta = f32::acos(d.y.clamp(-1.0, 1.0));
+    (phi / (2.0 * PI), theta / PI)
+}
+
+pub fn uv_to_direction(u: f32, v: f32) -> Vec3 {
+    let phi = 2.0 * PI * u;
+    let theta = PI * v;
+    Vec3::new(
+        theta.sin() * phi.cos(),
+        theta.cos(),
+        theta.sin() * phi.sin(),
+    )
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, ray: &Ray) -> Vec3 {
+        let (u, v) = direction_to_uv(&ray.direction);
+        self.intensity * self.texel(u, v)
+    }
+
+    fn sample(&self) -> Option<(Vec3, f32)> {
+        let ((u, v), pdf_uv) = self.distribution.sample(random::<f32>(), random::<f32>());
+        let sin_theta = f32::sin(PI * v);
+        if pdf_uv == 0.0 || sin_theta == 0.0 {
+            return None;
+        }
+
+        // The map spans 2 pi by pi radians, and solid angle is sin(theta) times dtheta dphi.
+        Some((uv_to_direction(u, v), pdf_uv / (2.0 * PI * PI * sin_theta)))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f32 {
+        let (u, v) = direction_to_uv(direction);
+        let sin_theta = f32::sin(PI * v);
+        if sin_theta == 0.0 {
+            return 0.0;
+        }
+        self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+fn invalid_data<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn read_hdr<R: BufRead>(reader: R) -> Result<(usize, usize, Vec<Vec3>), io::Error> {
+    let decoder = HDRDecoder::new(reader).map_err(|err| invalid_data(format!("{:?}", err)))?;
+    let metadata = decoder.metadata();
+    if metadata.width == 0 || metadata.height == 0 {
+        return Err(invalid_data("empty HDR image"));
+    }
+    let pixels = decoder
+        .read_image_hdr()
+        .map_err(|err| invalid_data(format!("{:?}", err)))?;
+
+    let pixels = pixels
+        .iter()
+        .map(|p| Vec3::new(p.data[0], p.data[1], p.data[2]))
+        .collect();
+    Ok((metadata.width as usize, metadata.height as usize, pixels))
+}
+
+/// Reads a Portable Float Map: a `PF` (RGB) or `Pf` (grey) header, the size, and a scale whose sign
+/// gives the byte order, followed by rows stored bottom to top.
+pub fn read_pfm<R: BufRead>(mut reader: R) -> Result<(usize, usize, Vec<Vec3>), io::Error> {
+    let mut read_token = || -> Result<String, io::Error> {
+        let mut token = String::new();
+        let mut byte = [0u8];
+        loop {
+            reader.read_exact(&mut byte)?;
+            let c = byte[0] as char;
+            if c.is_ascii_whitespace() {
+                if !token.is_empty() {
+                    return Ok(token);
+                }
+            } else {
+                token.push(c);
+            }
+        }
+    };
+
+    let channels = match read_token()?.as_str() {
+        "PF" => 3,
+        "Pf" => 1,
+        other => return Err(invalid_data(format!("not a PFM file (header '{}')", other))),
+    };
+    let width: usize = read_token()?
+        .parse()
+        .map_err(|_| invalid_data("invalid PFM width"))?;
+    let height: usize = read_token()?
+        .parse()
+        .map_err(|_| invalid_data("invalid PFM height"))?;
+    let scale: f32 = read_token()?
+        .parse()
+        .map_err(|_| invalid_data("invalid PFM scale"))?;
+    if width == 0 || height == 0 {
+        return Err(invalid_data("empty PFM image"));
+    }
+    let little_endian = scale < 0.0;
+
+    let len = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels * 4))
+        .ok_or_else(|| invalid_data("PFM size is too large"))?;
+    // Grown as the data arrives, so a corrupt size cannot ask for more memory than the file holds.
+    let mut data = vec![];
+    reader.take(len as u64).read_to_end(&mut data)?;
+    if data.len() != len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "truncated PFM data",
+        ));
+    }
+
+    let floats: Vec<f32> = data
+        .chunks(4)
+        .map(|b| {
+            let bytes = [b[0], b[1], b[2], b[3]];
+            if little_endian {
+                f32::from_le_bytes(bytes)
+            } else {
+                f32::from_be_bytes(bytes)
+            }
+        })
+        .collect();
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in (0..height).rev() {
+        for x in 0..width {
+            let i = (row * width + x) * channels;
+            pixels.push(if channels == 3 {
+                Vec3::new(floats[i], floats[i + 1], floats[i + 2])
+            } else {
+                Vec3::new(floats[i], floats[i], floats[i])
+            });
+        }
+    }
+
+    Ok((width, height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vector_math::random_methods::seed_thread_rng;
+
+    #[test]
+    fn uv_direction_round_trip() {
+        for &(u, v) in &[(0.1, 0.2), (0.5, 0.5), (0.9, 0.7), (0.25, 0.99)] {
+            let (u2, v2) = direction_to_uv(&uv_to_direction(u, v));
+            assert!((u - u2).abs() < 1e-5 && (v - v2).abs() < 1e-5);
+        }
+        assert_eq!(direction_to_uv(&Vec3::new(0.0, 1.0, 0.0)).1, 0.0);
+    }
+
+    #[test]
+    fn sampling_favours_bright_texels() {
+        // A dim map with one bright "sun" texel.
+        let (width, height) = (16, 8);
+        let mut pixels = vec![Vec3::new(0.01, 0.01, 0.01); width * height];
+        let sun = 3 * width + 5;
+        pixels[sun] = Vec3::new(1000.0, 1000.0, 1000.0);
+        let map = EnvironmentMap::new(width, height, pixels);
+
+        seed_thread_rng(1);
+        let mut sun_hits = 0;
+        for _ in 0..1000 {
+            let (direction, pdf) = map.sample().unwrap();
+            assert!((map.pdf(&direction) - pdf).abs() <= 1e-3 * pdf);
+
+            let (u, v) = direction_to_uv(&direction);
+            let texel = (v * height as f32) as usize * width + (u * width as f32) as usize;
+            if texel == sun {
+                sun_hits += 1;
+            }
+        }
+        assert!(sun_hits > 950);
+    }
+
+    #[test]
+    fn pdf_integrates_to_one_over_sphere() {
+        let pixels = (0..32).map(|i| Vec3::new(i as f32, 1.0, 0.5)).collect();
+        let map = EnvironmentMap::new(8, 4, pixels);
+
+        let n = 200;
+        let mut total = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let (u, v) = ((i as f32 + 0.5) / n as f32, (j as f32 + 0.5) / n as f32);
+                let solid_angle = 2.0 * PI * PI * f32::sin(PI * v) / (n * n) as f32;
+                total += map.pdf(&uv_to_direction(u, v)) * solid_angle;
+            }
+        }
+        assert!((total - 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn reads_pfm() {
+        // 2x1 little-endian colour map; rows are stored bottom-up.
+        let mut data = b"PF\n2 1\n-1.0\n".to_vec();
+        for value in &[1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        let (width, height, pixels) = read_pfm(&data[..]).unwrap();
+        assert_eq!((width, height), (2, 1));
+        assert_eq!(
+            pixels,
+            vec![Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0)]
+        );
+
+        assert!(read_pfm(&b"P6\n1 1\n255\n"[..]).is_err());
+        assert!(read_pfm(&data[..data.len() - 1]).is_err());
+        let huge = format!("PF\n{} {}\n-1.0\n", usize::MAX / 2, 3);
+        assert_eq!(
+            read_pfm(huge.as_bytes()).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        let large = format!("PF\n{} {}\n-1.0\n", 1 << 20, 1 << 10);
+        assert_eq!(
+            read_pfm(large.as_bytes()).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        let empty = read_pfm(&b"PF\n0 0\n-1\n"[..]).unwrap_err();
+        assert_eq!(
+            (empty.kind(), empty.to_string()),
+            (io::ErrorKind::InvalidData, "empty PFM image".to_string())
+        );
+        assert!(read_pfm(&b"Pf\n4 0\n-1\n"[..]).is_err());
+    }
+
+    #[test]
+    fn loads_radiance_hdr() {
+        use image::hdr::HDREncoder;
+        use image::Rgb;
+
+        let path = std::env::temp_dir().join("environment_map_loads_radiance_hdr.hdr");
+        let data = [
+            Rgb {
+                data: [0.5, 1.0, 2.0],
+            },
+            Rgb {
+                data: [8.0, 0.25, 0.0],
+            },
+        ];
+        HDREncoder::new(File::create(&path).unwrap())
+            .encode(&data, 2, 1)
+            .unwrap();
+
+        let map = EnvironmentMap::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((map.width, map.height), (2, 1));
+        assert_eq!(
+            map.pixels,
+            vec![Vec3::new(0.5, 1.0, 2.0), Vec3::new(8.0, 0.25, 0.0)]
+        );
+
+        assert!(EnvironmentMap::load(Path::new("sky.png")).is_err());
+        let empty =
+            read_hdr(&b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 0 +X 0\n"[..]).unwrap_err();
+        assert_eq!(
+            (empty.kind(), empty.to_string()),
+            (io::ErrorKind::InvalidData, "empty HDR image".to_string())
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use scene::voxel_grid::VoxelGrid;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::random_methods::random;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Smoke, clouds or fire whose density varies through `bounds`, following a voxel grid scaled by
+/// `density`. Scattering distances come from delta tracking and shadow rays are dimmed by ratio
+/// tracking, both of which stay unbiased however the density varies.
+pub struct GridMedium {
+    grid: VoxelGrid,
+    pub bounds: Aabb,
+    /// Extinction coefficient for a grid value of 1.
+    pub density: f32,
+    pub phase_function: Arc<dyn Material>,
+}
+
+impl GridMedium {
+    pub fn new(
+        grid: VoxelGrid,
+        bounds: Aabb,
+        density: f32,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        GridMedium {
+            grid,
+            bounds,
+            density,
+            phase_function,
+        }
+    }
+
+    /// Extinction coefficient at a point inside the bounds.
+    pub fn density_at(&self, point: &Vec3) -> f32 {
+        let size = self.bounds.max - self.bounds.min;
+        let p = *point - self.bounds.min;
+        self.density
+            * self
+                .grid
+                .sample(&Vec3::new(p.x / size.x, p.y / size.y, p.z / size.z))
+    }
+
+    /// Upper bound on the density: the rate of tentative collisions both tracking methods sample.
+    fn majorant(&self) -> f32 {
+        self.density * self.grid.max()
+    }
+
+    /// Ray parameter of the next tentative collision after `t`.
+    fn step(&self, ray: &Ray, t: f32) -> f32 {
+        t - (1.0 - random::<f32>()).ln() / (self.majorant() * ray.direction.magnitude())
+    }
+}
+
+impl Hitable for GridMedium {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        if self.majorant() <= 0.0 {
+            return None;
+        }
+        let (start, end) = self.bounds.clip(ray, t_min, t_max)?;
+
+        // Delta tracking: each tentative collision is real with probability density / majorant.
+        let mut t = start;
+        loop {
+            t = self.step(ray, t);
+            if t >= end {
+                return None;
+            }
+            if random::<f32>() * self.majorant() < self.density_at(&ray.point_at(t)) {
+                break;
+            }
+        }
+
+        // Scattering points have no surface; the normal is arbitrary.
+        let normal = Vec3::new(1.0, 0.0, 0.0);
+        let mut hit = HitRecord::new(
+            ray,
+            t,
+            normal,
+            normal,
+            (0.0, 0.0),
+            self.phase_function.as_ref(),
+        );
+        hit.front_face = true;
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+
+    /// Ratio tracking: the product of the chances of passing each tentative collision.
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        if self.majorant() <= 0.0 {
+            return 1.0;
+        }
+        let (start, end) = match self.bounds.clip(ray, t_min, t_max) {
+            Some(segment) => segment,
+            None => return 1.0,
+        };
+
+        let mut transmittance = 1.0;
+        let mut t = self.step(ray, start);
+        while t < end {
+            transmittance *= 1.0 - self.density_at(&ray.point_at(t)) / self.majorant();
+            t = self.step(ray, t);
+        }
+        transmittance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::phase_function::Isotropic;
+    use vector_math::random_methods::seed_thread_rng;
+
+    #[test]
+    fn tracking_matches_the_optical_depth() {
+        // Density rising linearly from 0 to 2 along x through a 2-unit box, so the optical depth
+        // across it is 2, and 0.5 across its first half.
+        let grid = VoxelGrid::from_fn([64, 1, 1], |p| p.x);
+        let bounds = Aabb::new(Vec3::new(0.0, -1.0, -1.0), Vec3::new(2.0, 1.0, 1.0));
+        let medium = GridMedium::new(grid, bounds, 2.0, Arc::new(Isotropic::new(1.0, 1.0, 1.0)));
+        assert!((medium.density_at(&Vec3::new(1.0, 0.0, 0.0)) - 1.0).abs() < 1e-5);
+
+        seed_thread_rng(4);
+        let n = 20_000;
+        let ray = Ray::new(Vec3::new(-3.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+        let passed = |t_max: f32| {
+            (0..n)
+                .filter(|_| medium.hit_test(&ray, 0.0, t_max).is_none())
+                .count() as f32
+        };
+        assert!((passed(f32::MAX) / n as f32 - f32::exp(-2.0)).abs() < 0.01);
+        assert!((passed(2.0) / n as f32 - f32::exp(-0.5)).abs() < 0.01);
+
+        let mean = |t_max: f32| {
+            (0..n)
+                .map(|_| medium.transmittance(&ray, 0.0, t_max))
+                .sum::<f32>()
+                / n as f32
+        };
+        assert!((mean(f32::MAX) - f32::exp(-2.0)).abs() < 0.01);
+        assert!((mean(2.0) - f32::exp(-0.5)).abs() < 0.01);
+
+        for _ in 0..100 {
+            if let Some(hit) = medium.hit_test(&ray, 0.0, f32::MAX) {
+                assert!(hit.point.x >= 0.0 && hit.point.x <= 2.0);
+            }
+        }
+        let miss = Ray::new(Vec3::new(-3.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(medium.transmittance(&miss, 0.0, f32::MAX), 1.0);
+    }
+}
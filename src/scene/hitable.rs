@@ -1,7 +1,73 @@
-use scene::light_ray::LightRay;
+use scene::light::Light;
+use scene::material::Material;
+use vector_math::aabb::Aabb;
 use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
 
-pub trait Hitable {
-    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<f32>;
-    fn scatter(&self, incident: &LightRay) -> Option<LightRay>;
+/// Everything known about a ray/surface intersection. Both normals face against the incoming ray;
+/// `front_face` records whether that is the outward side of the surface.
+pub struct HitRecord<'a> {
+    pub t: f32,
+    pub point: Vec3,
+    pub geometric_normal: Vec3,
+    pub shading_normal: Vec3,
+    pub front_face: bool,
+    pub uv: (f32, f32),
+    pub material: &'a dyn Material,
+    /// Identifies the scene object for ID passes; 0 unless set by an `ObjectId` wrapper.
+    pub object_id: u32,
+    /// The light that was hit, when the surface is an emitter that `collect_lights` reports.
+    pub light: Option<&'a dyn Light>,
+}
+
+impl<'a> HitRecord<'a> {
+    /// Builds a record from outward-facing unit normals, flipping them toward the ray as needed.
+    pub fn new(
+        ray: &Ray,
+        t: f32,
+        outward_normal: Vec3,
+        outward_shading_normal: Vec3,
+        uv: (f32, f32),
+        material: &'a dyn Material,
+    ) -> Self {
+        let front_face = ray.direction.dot(outward_normal) < 0.0;
+        let (geometric_normal, shading_normal) = if front_face {
+            (outward_normal, outward_shading_normal)
+        } else {
+            (-outward_normal, -outward_shading_normal)
+        };
+
+        HitRecord {
+            t,
+            point: ray.point_at(t),
+            geometric_normal,
+            shading_normal,
+            front_face,
+            uv,
+            material,
+            object_id: 0,
+            light: None,
+        }
+    }
+}
+
+pub trait Hitable: Send + Sync {
+    /// The closest intersection in `(t_min, t_max)`.
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>>;
+
+    /// `None` for unbounded geometry, which acceleration structures test separately.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Fraction of light that crosses `(t_min, t_max)` along the ray without being blocked or
+    /// scattered, for shadow rays. Surfaces are opaque; media return an unbiased estimate.
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        if self.hit_test(ray, t_min, t_max).is_some() {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Adds the emitters in this object that support direct light sampling.
+    fn collect_lights<'a>(&'a self, _lights: &mut Vec<&'a dyn Light>) {}
 }
@@ -1,28 +1,49 @@
-use scene::hitable::Hitable;
-use scene::light_ray::LightRay;
+use scene::hitable::{HitRecord, Hitable};
+use scene::light::Light;
+use vector_math::aabb::Aabb;
+use vector_math::ray::Ray;
 
 pub struct HitableList {
-    pub list: Vec<Box<Hitable>>,
+    pub list: Vec<Box<dyn Hitable>>,
 }
 
-impl HitableList {
-    pub fn scatter(&self, path: &LightRay, t_min: f32, t_max: f32) -> Option<LightRay> {
+impl Hitable for HitableList {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
         let mut closest_so_far = t_max;
-        let mut hit_result: Option<&Box<Hitable>> = None;
+        let mut hit_result: Option<HitRecord> = None;
 
         for item in &self.list {
-            match item.hit_test(&path.ray, t_min, closest_so_far) {
-                Some(t) => {
-                    closest_so_far = t;
-                    hit_result = Some(item);
-                }
-                None => {}
+            if let Some(hit) = item.hit_test(ray, t_min, closest_so_far) {
+                closest_so_far = hit.t;
+                hit_result = Some(hit);
             }
         }
 
-        match hit_result {
-            Some(object) => object.scatter(path),
-            None => None,
+        hit_result
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut bounds = Aabb::empty();
+        for item in &self.list {
+            bounds = bounds.union(&item.bounding_box()?);
+        }
+        Some(bounds)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        let mut transmittance = 1.0;
+        for item in &self.list {
+            transmittance *= item.transmittance(ray, t_min, t_max);
+            if transmittance == 0.0 {
+                break;
+            }
+        }
+        transmittance
+    }
+
+    fn collect_lights<'a>(&'a self, lights: &mut Vec<&'a dyn Light>) {
+        for item in &self.list {
+            item.collect_lights(lights);
         }
     }
 }
@@ -0,0 +1,133 @@
+use scene::hitable::{HitRecord, Hitable};
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::matrix4::Matrix4;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Places a shared object in the scene through an affine transform, so one mesh can appear many
+/// times without copying it. Emitters inside an instance are only found by scattered rays, not by
+/// light sampling.
+pub struct Instance {
+    pub object: Arc<dyn Hitable>,
+    to_world: Matrix4,
+    to_object: Matrix4,
+    /// The inverse transpose, which keeps normals perpendicular under non-uniform scaling.
+    normal_to_world: Matrix4,
+}
+
+impl Instance {
+    /// Panics if `to_world` is not invertible.
+    pub fn new(object: Arc<dyn Hitable>, to_world: Matrix4) -> Self {
+        let to_object = to_world
+            .inverse()
+            .expect("instance transform is not invertible");
+        Instance {
+            object,
+            to_world,
+            to_object,
+            normal_to_world: to_object.transpose(),
+        }
+    }
+
+    fn transform_normal(&self, normal: &Vec3) -> Vec3 {
+        self.normal_to_world.transform_vector(normal).normalized()
+    }
+
+    /// The direction is not renormalized, so parameters along the ray are the same in both spaces.
+    fn to_local_ray(&self, ray: &Ray) -> Ray {
+        Ray::at_time(
+            self.to_object.transform_point(&ray.origin),
+            self.to_object.transform_vector(&ray.direction),
+            ray.time,
+        )
+    }
+}
+
+impl Hitable for Instance {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let mut hit = self
+            .object
+            .hit_test(&self.to_local_ray(ray), t_min, t_max)?;
+        hit.point = ray.point_at(hit.t);
+        hit.geometric_normal = self.transform_normal(&hit.geometric_normal);
+        hit.shading_normal = self.transform_normal(&hit.shading_normal);
+        hit.light = None;
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let local = self.object.bounding_box()?;
+        let corners = (0..8).map(|i| {
+            let pick = |bit: usize, axis: usize| {
+                if i & bit == 0 {
+                    local.min[axis]
+                } else {
+                    local.max[axis]
+                }
+            };
+            Vec3::new(pick(1, 0), pick(2, 1), pick(4, 2))
+        });
+        Some(corners.fold(Aabb::empty(), |bounds, corner| {
+            bounds.grow(self.to_world.transform_point(&corner))
+        }))
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        self.object
+            .transmittance(&self.to_local_ray(ray), t_min, t_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+    use scene::sphere::Sphere;
+
+    #[test]
+    fn instances_share_and_transform_an_object() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let sphere: Arc<dyn Hitable> = Arc::new(Sphere::new(0.0, 0.0, 0.0, 1.0, material));
+        let moved = Instance::new(
+            sphere.clone(),
+            Matrix4::translation(Vec3::new(5.0, 0.0, 0.0)),
+        );
+        let squashed = Instance::new(sphere, Matrix4::scaling(Vec3::new(2.0, 1.0, 1.0)));
+
+        let ray = Ray::new(Vec3::new(5.0, 0.0, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = moved.hit_test(&ray, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 2.0);
+        assert_eq!(hit.point, Vec3::new(5.0, 0.0, 1.0));
+        assert!(squashed.hit_test(&ray, 0.0, f32::MAX).is_none());
+
+        // On the ellipsoid x²/4 + y² + z² = 1 at 45° in the xz plane, the normal leans toward z.
+        let point = Vec3::new(f32::sqrt(2.0), 0.0, f32::sqrt(0.5));
+        let ray = Ray::new(point + Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = squashed.hit_test(&ray, 0.0, f32::MAX).unwrap();
+        assert!((hit.point - point).magnitude() < 1e-5);
+        let expected = Vec3::new(point.x / 4.0, 0.0, point.z).normalized();
+        assert!((hit.geometric_normal - expected).magnitude() < 1e-5);
+        assert!(hit.front_face);
+
+        assert_eq!(
+            squashed.bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(-2.0, -1.0, -1.0),
+                Vec3::new(2.0, 1.0, 1.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn rotated_bounding_box_contains_the_object() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let sphere: Arc<dyn Hitable> = Arc::new(Sphere::new(1.0, 0.0, 0.0, 0.5, material));
+        let instance = Instance::new(sphere, Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), 90.0));
+        let bounds = instance.bounding_box().unwrap();
+        assert!((bounds.centroid() - Vec3::new(0.0, 1.0, 0.0)).magnitude() < 1e-5);
+
+        let ray = Ray::new(Vec3::new(0.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!((instance.hit_test(&ray, 0.0, f32::MAX).unwrap().t - 4.5).abs() < 1e-5);
+    }
+}
@@ -1,32 +1,61 @@
+use scene::hitable::HitRecord;
 use scene::light_ray::LightRay;
 use scene::material::Material;
-use vector_math::random_methods::random_in_unit_sphere;
+use scene::texture::{ConstantTexture, Texture};
+use std::f32::consts::PI;
+use std::sync::Arc;
+use vector_math::random_methods::random_unit_vector;
 use vector_math::ray::Ray;
 use vector_math::vec3::Vec3;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone)]
 pub struct Lambertian {
-    pub albedo: Vec3,
+    pub albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(r: f32, g: f32, b: f32) -> Self {
-        Lambertian {
-            albedo: Vec3::new(r, g, b),
-        }
+        Lambertian::textured(Arc::new(ConstantTexture::new(r, g, b)))
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>) -> Self {
+        Lambertian { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(
-        &self,
-        incident: &LightRay,
-        hit_point: &Vec3,
-        surface_normal: &Vec3,
-    ) -> Option<LightRay> {
-        let exitance_direction = *surface_normal + random_in_unit_sphere();
-        let exitance_ray = Ray::new(*hit_point, exitance_direction.normalized());
-        let color = incident.color * self.albedo;
+    fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay> {
+        // A uniform point on the unit sphere offset by the normal is a cosine-weighted direction.
+        let exitance_direction = hit.shading_normal + random_unit_vector();
+        if exitance_direction.squared_magnitude() < 1e-12 {
+            return None;
+        }
+        let exitance_ray = Ray::at_time(
+            hit.point,
+            exitance_direction.normalized(),
+            incident.ray.time,
+        );
+        let color = incident.color * self.albedo(hit);
         Some(LightRay::new(exitance_ray, color))
     }
+
+    fn eval(&self, _incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> Vec3 {
+        if scattered.dot(hit.shading_normal) > 0.0 {
+            self.albedo(hit) / PI
+        } else {
+            Vec3::zero()
+        }
+    }
+
+    fn pdf(&self, _incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> f32 {
+        scattered.dot(hit.shading_normal).max(0.0) / PI
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn albedo(&self, hit: &HitRecord) -> Vec3 {
+        self.albedo.value(hit.uv, &hit.point)
+    }
 }
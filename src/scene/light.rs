@@ -0,0 +1,69 @@
+use scene::hitable::Hitable;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// A direction toward a light, chosen by `Light::sample`.
+#[derive(Copy, Clone, Debug)]
+pub struct LightSample {
+    /// Unit direction from the shaded point toward the light.
+    pub direction: Vec3,
+    /// Distance along `direction` to the sampled point on the light.
+    pub distance: f32,
+    /// Radiance emitted back along `-direction`.
+    pub radiance: Vec3,
+    /// Density per unit solid angle with which `direction` was chosen.
+    pub pdf: f32,
+}
+
+/// An emitter that can be sampled directly, so paths find it with shadow rays, not by chance.
+pub trait Light: Send + Sync {
+    /// Picks a direction from `origin` toward the light. `None` when the light cannot be sampled
+    /// from there, in which case it must be found by scattered rays.
+    fn sample(&self, origin: &Vec3) -> Option<LightSample>;
+
+    /// Density with which `sample` would return `direction` from `origin`.
+    fn pdf(&self, origin: &Vec3, direction: &Vec3) -> f32;
+}
+
+/// Light sample toward `point`, drawn uniformly from the `area` of an emitting `surface`.
+pub fn sample_surface(
+    surface: &dyn Hitable,
+    area: f32,
+    origin: &Vec3,
+    point: Vec3,
+) -> Option<LightSample> {
+    let offset = point - *origin;
+    if offset.squared_magnitude() == 0.0 {
+        return None;
+    }
+    let direction = offset.normalized();
+    let hit = surface.hit_test(&Ray::new(*origin, direction), 0.0, f32::MAX)?;
+    let pdf = solid_angle_pdf(area, hit.t, direction.dot(hit.geometric_normal));
+    if pdf <= 0.0 {
+        return None;
+    }
+    Some(LightSample {
+        direction,
+        distance: hit.t,
+        radiance: hit.material.emitted(&hit),
+        pdf,
+    })
+}
+
+/// Density with which `sample_surface` picks the unit `direction` from `origin`.
+pub fn surface_pdf(surface: &dyn Hitable, area: f32, origin: &Vec3, direction: &Vec3) -> f32 {
+    match surface.hit_test(&Ray::new(*origin, *direction), 0.0, f32::MAX) {
+        Some(hit) => solid_angle_pdf(area, hit.t, direction.dot(hit.geometric_normal)),
+        None => 0.0,
+    }
+}
+
+/// Converts a uniform density over `area` to one per unit solid angle, for a point `distance` away
+/// whose surface is at `cos` to the line of sight.
+fn solid_angle_pdf(area: f32, distance: f32, cos: f32) -> f32 {
+    let cos = cos.abs();
+    if cos <= 0.0 || area <= 0.0 {
+        return 0.0;
+    }
+    distance * distance / (cos * area)
+}
@@ -1,11 +1,51 @@
+use scene::hitable::HitRecord;
 use scene::light_ray::LightRay;
+use vector_math::ray::Ray;
 use vector_math::vec3::Vec3;
 
-pub trait Material {
-    fn scatter(
-        &self,
-        incident: &LightRay,
-        hit_point: &Vec3,
-        surface_normal: &Vec3,
-    ) -> Option<LightRay>;
+pub trait Material: Send + Sync {
+    fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay>;
+
+    /// BSDF value for light arriving from unit direction `scattered` and leaving back along
+    /// `incident`, without the cosine factor.
+    fn eval(&self, _incident: &Ray, _scattered: &Vec3, _hit: &HitRecord) -> Vec3 {
+        Vec3::zero()
+    }
+
+    /// Density per unit solid angle with which `scatter` picks the unit direction `scattered`.
+    fn pdf(&self, _incident: &Ray, _scattered: &Vec3, _hit: &HitRecord) -> f32 {
+        0.0
+    }
+
+    /// Specular materials scatter into directions that a light sample will never match, so `eval`
+    /// and `pdf` are zero and lights are only reached through `scatter`.
+    fn is_specular(&self) -> bool {
+        true
+    }
+
+    /// Volumetric materials scatter inside a medium, where there is no surface for light to arrive
+    /// at an angle to, so the integrator leaves out the cosine factor.
+    fn is_volumetric(&self) -> bool {
+        false
+    }
+
+    /// Radiance leaving the surface on its own, independent of incoming light.
+    fn emitted(&self, _hit: &HitRecord) -> Vec3 {
+        Vec3::zero()
+    }
+
+    /// Whether `emitted` can be non-zero, which makes objects with this material light sources.
+    fn emits_light(&self) -> bool {
+        false
+    }
+
+    /// Base surface color, reported in albedo passes for denoising.
+    fn albedo(&self, _hit: &HitRecord) -> Vec3 {
+        Vec3::zero()
+    }
+
+    /// Identifies the material for material ID passes; 0 unless wrapped in a `MaterialId`.
+    fn id(&self) -> u32 {
+        0
+    }
 }
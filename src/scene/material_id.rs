@@ -0,0 +1,69 @@
+use scene::hitable::HitRecord;
+use scene::light_ray::LightRay;
+use scene::material::Material;
+use std::sync::Arc;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Gives the wrapped material an `id` for material ID passes, behaving exactly like it otherwise.
+pub struct MaterialId {
+    pub id: u32,
+    pub material: Arc<dyn Material>,
+}
+
+impl MaterialId {
+    pub fn new(id: u32, material: Arc<dyn Material>) -> Self {
+        MaterialId { id, material }
+    }
+}
+
+impl Material for MaterialId {
+    fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay> {
+        self.material.scatter(incident, hit)
+    }
+
+    fn eval(&self, incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> Vec3 {
+        self.material.eval(incident, scattered, hit)
+    }
+
+    fn pdf(&self, incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> f32 {
+        self.material.pdf(incident, scattered, hit)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.material.is_specular()
+    }
+
+    fn is_volumetric(&self) -> bool {
+        self.material.is_volumetric()
+    }
+
+    fn emitted(&self, hit: &HitRecord) -> Vec3 {
+        self.material.emitted(hit)
+    }
+
+    fn emits_light(&self) -> bool {
+        self.material.emits_light()
+    }
+
+    fn albedo(&self, hit: &HitRecord) -> Vec3 {
+        self.material.albedo(hit)
+    }
+
+    fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+/// Hands out material IDs in definition order, counting from 1 so that 0 means no material.
+#[derive(Debug, Default)]
+pub struct MaterialIds {
+    count: u32,
+}
+
+impl MaterialIds {
+    pub fn assign(&mut self, material: Arc<dyn Material>) -> Arc<dyn Material> {
+        self.count += 1;
+        Arc::new(MaterialId::new(self.count, material))
+    }
+}
@@ -1,43 +1,130 @@
+use scene::hitable::HitRecord;
 use scene::light_ray::LightRay;
 use scene::material::Material;
 use scene::surface_interface::reflect;
+use scene::texture::{ConstantTexture, Texture};
+use std::f32::consts::PI;
+use std::sync::Arc;
 use vector_math::random_methods::random_in_unit_sphere;
 use vector_math::ray::Ray;
 use vector_math::vec3::Vec3;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone)]
 pub struct Metal {
-    pub albedo: Vec3,
+    pub albedo: Arc<dyn Texture>,
     pub roughness: f32,
 }
 
 impl Metal {
     pub fn new(r: f32, g: f32, b: f32, roughness: f32) -> Self {
-        Metal {
-            albedo: Vec3::new(r, g, b),
-            roughness,
-        }
+        Metal::textured(Arc::new(ConstantTexture::new(r, g, b)), roughness)
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>, roughness: f32) -> Self {
+        Metal { albedo, roughness }
+    }
+
+    fn reflected(incident: &Ray, hit: &HitRecord) -> Vec3 {
+        reflect(&incident.direction.normalized(), &hit.shading_normal)
     }
 }
 
 impl Material for Metal {
-    fn scatter(
-        &self,
-        incident: &LightRay,
-        hit_point: &Vec3,
-        surface_normal: &Vec3,
-    ) -> Option<LightRay> {
-        let reflected = reflect(&incident.ray.direction, surface_normal); // direction is assumed to be normalized
-
-        if reflected.dot(*surface_normal) > 0.0 {
-            let outgoing_ray = Ray::new(
-                *hit_point,
-                reflected + self.roughness * random_in_unit_sphere(),
-            );
-            let outgoing = LightRay::new(outgoing_ray, incident.color * self.albedo);
-            Some(outgoing)
-        } else {
-            None
+    fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay> {
+        let fuzzed =
+            Metal::reflected(&incident.ray, hit) + self.roughness * random_in_unit_sphere();
+        if fuzzed.dot(hit.shading_normal) <= 0.0 || fuzzed.squared_magnitude() < 1e-12 {
+            return None;
+        }
+
+        let outgoing_ray = Ray::at_time(hit.point, fuzzed.normalized(), incident.ray.time);
+        Some(LightRay::new(
+            outgoing_ray,
+            incident.color * self.albedo(hit),
+        ))
+    }
+
+    /// Chosen so that `eval * cos / pdf` is the albedo, the weight `scatter` gives its samples.
+    fn eval(&self, incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> Vec3 {
+        let cos = scattered.dot(hit.shading_normal);
+        if cos <= 0.0 {
+            return Vec3::zero();
+        }
+        self.albedo(hit) * (self.pdf(incident, scattered, hit) / cos)
+    }
+
+    /// `scatter` offsets the mirror direction `r` by a point in a ball of radius `roughness`, so a
+    /// direction's density is the ball's volume along that ray, integrated in spherical shells.
+    fn pdf(&self, incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> f32 {
+        if self.roughness <= 0.0 || scattered.dot(hit.shading_normal) <= 0.0 {
+            return 0.0;
+        }
+
+        let cos = scattered.dot(Metal::reflected(incident, hit));
+        let discriminant = self.roughness * self.roughness - (1.0 - cos * cos);
+        if discriminant < 0.0 {
+            return 0.0;
+        }
+
+        // The ray enters the ball at distance t0 and leaves it at t1.
+        let t1 = cos + discriminant.sqrt();
+        let t0 = (cos - discriminant.sqrt()).max(0.0);
+        if t1 <= 0.0 {
+            return 0.0;
         }
+        (t1 * t1 * t1 - t0 * t0 * t0) / (4.0 * PI * self.roughness.powi(3))
+    }
+
+    fn is_specular(&self) -> bool {
+        self.roughness <= 0.0
+    }
+
+    fn albedo(&self, hit: &HitRecord) -> Vec3 {
+        self.albedo.value(hit.uv, &hit.point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::hitable::Hitable;
+    use scene::sphere::Sphere;
+    use std::sync::Arc;
+    use vector_math::random_methods::{random_unit_vector, seed_thread_rng};
+
+    #[test]
+    fn pdf_matches_scatter_distribution() {
+        let metal = Metal::new(0.8, 0.8, 0.8, 0.4);
+        let floor = Sphere::new(0.0, -1000.0, 0.0, 1000.0, Arc::new(metal.clone()));
+        let incident = Ray::new(
+            Vec3::new(-1.0, 0.3, 0.0),
+            Vec3::new(1.0, -0.3, 0.0).normalized(),
+        );
+        let hit = floor.hit_test(&incident, 0.001, f32::MAX).unwrap();
+
+        // At grazing incidence part of the lobe falls below the surface. Integrating the density
+        // over all directions gives the fraction of samples that `scatter` keeps.
+        seed_thread_rng(3);
+        let n = 100_000;
+        let integral: f32 = (0..n)
+            .map(|_| metal.pdf(&incident, &random_unit_vector(), &hit))
+            .sum::<f32>()
+            * 4.0
+            * PI;
+        let light_ray = LightRay::new(incident, Vec3::new(1.0, 1.0, 1.0));
+        let kept = (0..n)
+            .filter(|_| metal.scatter(&light_ray, &hit).is_some())
+            .count();
+        assert!(kept < n * 95 / 100);
+        assert!((integral / n as f32 - kept as f32 / n as f32).abs() < 0.02);
+
+        // Directions near the mirror direction are denser than those near the edge of the lobe.
+        let mirror = Vec3::new(1.0, 0.3, 0.0).normalized();
+        let edge = Vec3::new(1.0, 0.7, 0.0).normalized();
+        assert!(metal.pdf(&incident, &mirror, &hit) > metal.pdf(&incident, &edge, &hit));
+        assert_eq!(
+            metal.pdf(&incident, &Vec3::new(-1.0, 1.0, 0.0).normalized(), &hit),
+            0.0
+        );
     }
 }
@@ -1,10 +1,36 @@
+pub mod background;
+pub mod bvh_node;
 pub mod camera;
+pub mod conductor;
+pub mod cone;
+pub mod constant_medium;
+pub mod cuboid;
+pub mod cylinder;
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod disk;
+pub mod environment_map;
+pub mod grid_medium;
 pub mod hitable;
 pub mod hitable_list;
+pub mod instance;
 pub mod lambertian;
+pub mod light;
 pub mod light_ray;
 pub mod material;
+pub mod material_id;
 pub mod metal;
+pub mod moving_sphere;
+pub mod noise_texture;
+pub mod obj_loader;
+pub mod object_id;
+pub mod phase_function;
+pub mod plane;
+pub mod rect;
 pub mod sphere;
 pub mod surface_interface;
+pub mod texture;
+pub mod torus;
+pub mod triangle;
+pub mod triangle_mesh;
+pub mod voxel_grid;
@@ -0,0 +1,137 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::*;
+use scene::sphere::{sphere_intersection, sphere_uv};
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Sphere whose center moves through `keyframes` of `(time, center)`, linearly between them and
+/// holding still before the first and after the last. Emissive moving spheres are only found by
+/// scattered rays, not by light sampling.
+pub struct MovingSphere {
+    keyframes: Vec<(f32, Vec3)>,
+    pub radius: f32,
+    pub material: Arc<dyn Material>,
+}
+
+impl MovingSphere {
+    /// Moves at constant velocity from `center0` at `time0` to `center1` at `time1`.
+    pub fn new(
+        center0: Vec3,
+        time0: f32,
+        center1: Vec3,
+        time1: f32,
+        radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        MovingSphere::keyframed(vec![(time0, center0), (time1, center1)], radius, material)
+    }
+
+    /// Keyframe times must be strictly increasing.
+    pub fn keyframed(
+        keyframes: Vec<(f32, Vec3)>,
+        radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(!keyframes.is_empty(), "moving sphere has no keyframes");
+        assert!(
+            keyframes.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "moving sphere keyframe times are not increasing"
+        );
+        MovingSphere {
+            keyframes,
+            radius,
+            material,
+        }
+    }
+
+    pub fn center(&self, time: f32) -> Vec3 {
+        let next = self.keyframes.iter().position(|&(t, _)| t > time);
+        match next {
+            Some(0) => self.keyframes[0].1,
+            Some(i) => {
+                let (t0, c0) = self.keyframes[i - 1];
+                let (t1, c1) = self.keyframes[i];
+                let s = (time - t0) / (t1 - t0);
+                (1.0 - s) * c0 + s * c1
+            }
+            None => self.keyframes[self.keyframes.len() - 1].1,
+        }
+    }
+}
+
+impl Hitable for MovingSphere {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let center = self.center(ray.time);
+        let t = sphere_intersection(&center, self.radius, ray, t_min, t_max)?;
+        let outward_normal = (ray.point_at(t) - center) / self.radius;
+        Some(HitRecord::new(
+            ray,
+            t,
+            outward_normal,
+            outward_normal,
+            sphere_uv(&outward_normal),
+            self.material.as_ref(),
+        ))
+    }
+
+    /// Motion between keyframes is linear, so boxes around the keyframes enclose the whole path.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius.abs() * Vec3::new(1.0, 1.0, 1.0);
+        let bounds = self
+            .keyframes
+            .iter()
+            .fold(Aabb::empty(), |bounds, &(_, center)| {
+                bounds.union(&Aabb::new(center - r, center + r))
+            });
+        Some(bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+
+    #[test]
+    fn center_follows_keyframes() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let keyframes = vec![
+            (0.0, Vec3::zero()),
+            (1.0, Vec3::new(2.0, 0.0, 0.0)),
+            (3.0, Vec3::new(2.0, 4.0, 0.0)),
+        ];
+        let sphere = MovingSphere::keyframed(keyframes, 0.5, material);
+        assert_eq!(sphere.center(-1.0), Vec3::zero());
+        assert_eq!(sphere.center(0.5), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(sphere.center(2.0), Vec3::new(2.0, 2.0, 0.0));
+        assert_eq!(sphere.center(5.0), Vec3::new(2.0, 4.0, 0.0));
+        assert_eq!(
+            sphere.bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(-0.5, -0.5, -0.5),
+                Vec3::new(2.5, 4.5, 0.5)
+            ))
+        );
+    }
+
+    #[test]
+    fn hits_depend_on_ray_time() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let sphere = MovingSphere::new(
+            Vec3::zero(),
+            0.0,
+            Vec3::new(0.0, 2.0, 0.0),
+            1.0,
+            0.5,
+            material,
+        );
+        let at =
+            |time: f32| Ray::at_time(Vec3::new(0.0, 2.0, 3.0), Vec3::new(0.0, 0.0, -1.0), time);
+        assert!(sphere.hit_test(&at(0.0), 0.0, f32::MAX).is_none());
+        let hit = sphere.hit_test(&at(1.0), 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 2.5);
+        assert_eq!(hit.geometric_normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+}
@@ -0,0 +1,167 @@
+use scene::texture::Texture;
+use std::f32::consts::PI;
+use vector_math::noise::Perlin;
+use vector_math::vec3::Vec3;
+
+fn mix(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    (1.0 - t) * a + t * b
+}
+
+/// Veined stone: sine bands along x, bent by turbulence.
+pub struct MarbleTexture {
+    pub noise: Perlin,
+    pub base: Vec3,
+    pub vein: Vec3,
+    /// Frequency of the pattern in world space.
+    pub scale: f32,
+    /// How far turbulence bends the bands.
+    pub distortion: f32,
+    pub octaves: u32,
+}
+
+impl MarbleTexture {
+    pub fn new(seed: u64) -> Self {
+        MarbleTexture {
+            noise: Perlin::new(seed),
+            base: Vec3::new(0.9, 0.9, 0.88),
+            vein: Vec3::new(0.2, 0.2, 0.25),
+            scale: 1.0,
+            distortion: 10.0,
+            octaves: 7,
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _uv: (f32, f32), point: &Vec3) -> Vec3 {
+        let p = self.scale * *point;
+        let band =
+            0.5 * (1.0 + f32::sin(p.x + self.distortion * self.noise.turbulence(&p, self.octaves)));
+        mix(self.vein, self.base, band)
+    }
+}
+
+/// Growth rings around the y axis, wobbled by fBm.
+pub struct WoodTexture {
+    pub noise: Perlin,
+    pub light: Vec3,
+    pub dark: Vec3,
+    /// Rings per world unit.
+    pub scale: f32,
+    /// How far the rings wander, in ring widths.
+    pub distortion: f32,
+    pub octaves: u32,
+}
+
+impl WoodTexture {
+    pub fn new(seed: u64) -> Self {
+        WoodTexture {
+            noise: Perlin::new(seed),
+            light: Vec3::new(0.75, 0.55, 0.35),
+            dark: Vec3::new(0.45, 0.28, 0.14),
+            scale: 4.0,
+            distortion: 0.6,
+            octaves: 4,
+        }
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _uv: (f32, f32), point: &Vec3) -> Vec3 {
+        let p = self.scale * *point;
+        let radius = f32::sqrt(p.x * p.x + p.z * p.z);
+        let rings = radius + self.distortion * self.noise.fbm(&p, self.octaves, 2.0, 0.5);
+        let ring = 0.5 * (1.0 - f32::cos(2.0 * PI * rings.fract()));
+        mix(self.light, self.dark, ring * ring)
+    }
+}
+
+/// Soft fBm clouds over a sky color.
+pub struct CloudsTexture {
+    pub noise: Perlin,
+    pub sky: Vec3,
+    pub cloud: Vec3,
+    pub scale: f32,
+    /// Fraction of the surface covered by cloud, from 0 to 1.
+    pub coverage: f32,
+    pub octaves: u32,
+}
+
+impl CloudsTexture {
+    pub fn new(seed: u64) -> Self {
+        CloudsTexture {
+            noise: Perlin::new(seed),
+            sky: Vec3::new(0.3, 0.5, 0.9),
+            cloud: Vec3::new(1.0, 1.0, 1.0),
+            scale: 1.0,
+            coverage: 0.5,
+            octaves: 6,
+        }
+    }
+}
+
+impl Texture for CloudsTexture {
+    fn value(&self, _uv: (f32, f32), point: &Vec3) -> Vec3 {
+        let density = 0.5
+            + 0.5
+                * self
+                    .noise
+                    .fbm(&(self.scale * *point), self.octaves, 2.0, 0.5);
+        let threshold = 1.0 - self.coverage.clamp(0.0, 1.0);
+        let cloud = if threshold < 1.0 {
+            ((density - threshold) / (1.0 - threshold)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        mix(self.sky, self.cloud, cloud)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn within(color: Vec3, a: Vec3, b: Vec3) -> bool {
+        let (low, high) = (a.min(b), a.max(b));
+        let eps = 1e-5;
+        (0..3).all(|i| color[i] >= low[i] - eps && color[i] <= high[i] + eps)
+    }
+
+    #[test]
+    fn patterns_stay_between_their_colors() {
+        let marble = MarbleTexture::new(3);
+        let wood = WoodTexture::new(3);
+        let clouds = CloudsTexture::new(3);
+        let mut marble_values = vec![];
+        for i in 0..200 {
+            let p = Vec3::new(i as f32 * 0.37, (i as f32 * 0.11).sin(), i as f32 * -0.05);
+            let value = marble.value((0.0, 0.0), &p);
+            assert!(within(value, marble.base, marble.vein));
+            assert!(within(wood.value((0.0, 0.0), &p), wood.light, wood.dark));
+            assert!(within(
+                clouds.value((0.0, 0.0), &p),
+                clouds.sky,
+                clouds.cloud
+            ));
+            marble_values.push(value.x);
+        }
+        // The pattern actually varies.
+        let (low, high) = marble_values
+            .iter()
+            .fold((1.0f32, 0.0f32), |(l, h), &v| (l.min(v), h.max(v)));
+        assert!(high - low > 0.3);
+    }
+
+    #[test]
+    fn same_seed_gives_same_pattern() {
+        let p = Vec3::new(1.3, -0.4, 2.2);
+        assert_eq!(
+            MarbleTexture::new(9).value((0.0, 0.0), &p),
+            MarbleTexture::new(9).value((0.0, 0.0), &p)
+        );
+
+        let mut clear = CloudsTexture::new(9);
+        clear.coverage = 0.0;
+        assert_eq!(clear.value((0.0, 0.0), &p), clear.sky);
+    }
+}
@@ -0,0 +1,778 @@
+use scene::dielectric::Dielectric;
+use scene::diffuse_light::DiffuseLight;
+use scene::lambertian::Lambertian;
+use scene::material::Material;
+use scene::material_id::MaterialIds;
+use scene::metal::Metal;
+use scene::triangle_mesh::TriangleMesh;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use vector_math::vec3::Vec3;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(PathBuf, io::Error),
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            ObjError::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+        }
+    }
+}
+
+impl Error for ObjError {}
+
+struct LineContext<'a> {
+    file: &'a str,
+    line: usize,
+}
+
+impl<'a> LineContext<'a> {
+    fn error<S: Into<String>>(&self, message: S) -> ObjError {
+        ObjError::Parse {
+            file: self.file.to_string(),
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn floats(
+        &self,
+        keyword: &str,
+        args: &[&str],
+        min: usize,
+        max: usize,
+    ) -> Result<Vec<f32>, ObjError> {
+        if args.len() < min || args.len() > max {
+            let expected = if min == max {
+                format!("{}", min)
+            } else {
+                format!("{} to {}", min, max)
+            };
+            return Err(self.error(format!(
+                "'{}' expects {} numbers, found {}",
+                keyword,
+                expected,
+                args.len()
+            )));
+        }
+
+        args.iter()
+            .map(|arg| {
+                arg.parse::<f32>()
+                    .map_err(|_| self.error(format!("'{}' has invalid number '{}'", keyword, arg)))
+            })
+            .collect()
+    }
+
+    fn vec3(&self, keyword: &str, args: &[&str]) -> Result<Vec3, ObjError> {
+        let v = self.floats(keyword, args, 3, 3)?;
+        Ok(Vec3::new(v[0], v[1], v[2]))
+    }
+}
+
+/// Lines of `source` with comments stripped, numbered from 1, skipping blank lines.
+fn statements(source: &str) -> impl Iterator<Item = (usize, Vec<&str>)> {
+    source.lines().enumerate().filter_map(|(index, line)| {
+        let line = line.split('#').next().unwrap_or("");
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            None
+        } else {
+            Some((index + 1, tokens))
+        }
+    })
+}
+
+/// Which renderer material an MTL entry maps onto.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MtlKind {
+    Diffuse,
+    Metal,
+    Dielectric,
+    Emissive,
+}
+
+/// The subset of an MTL material definition the renderer understands.
+#[derive(Clone, Debug)]
+pub struct MtlMaterial {
+    /// `Kd`
+    pub diffuse: Vec3,
+    /// `Ks`
+    pub specular: Vec3,
+    /// `Ke`
+    pub emission: Vec3,
+    /// `Ns`, the Phong exponent.
+    pub shininess: f32,
+    /// `Ni`
+    pub refractive_index: f32,
+    /// `d`, or `1 - Tr`.
+    pub dissolve: f32,
+    pub illum: u32,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        MtlMaterial {
+            diffuse: Vec3::new(0.8, 0.8, 0.8),
+            specular: Vec3::zero(),
+            emission: Vec3::zero(),
+            shininess: 0.0,
+            refractive_index: 1.5,
+            dissolve: 1.0,
+            illum: 2,
+        }
+    }
+}
+
+impl MtlMaterial {
+    pub fn kind(&self) -> MtlKind {
+        let max = |v: Vec3| v.x.max(v.y).max(v.z);
+
+        if max(self.emission) > 0.0 {
+            MtlKind::Emissive
+        } else if self.dissolve < 1.0 || [4, 6, 7, 9].contains(&self.illum) {
+            MtlKind::Dielectric
+        } else if self.illum == 3
+            || self.illum == 5
+            || (max(self.diffuse) == 0.0 && max(self.specular) > 0.0)
+        {
+            MtlKind::Metal
+        } else {
+            MtlKind::Diffuse
+        }
+    }
+
+    pub fn to_material(&self) -> Arc<dyn Material> {
+        match self.kind() {
+            MtlKind::Diffuse => Arc::new(Lambertian::new(
+                self.diffuse.x,
+                self.diffuse.y,
+                self.diffuse.z,
+            )),
+            MtlKind::Metal => {
+                // Map the Phong exponent to a roughness: Ns = 0 is fully rough, large Ns a mirror.
+                let roughness = f32::sqrt(2.0 / (self.shininess.max(0.0) + 2.0));
+                Arc::new(Metal::new(
+                    self.specular.x,
+                    self.specular.y,
+                    self.specular.z,
+                    roughness,
+                ))
+            }
+            MtlKind::Dielectric => Arc::new(Dielectric::new(self.refractive_index)),
+            MtlKind::Emissive => Arc::new(DiffuseLight::new(
+                self.emission.x,
+                self.emission.y,
+                self.emission.z,
+            )),
+        }
+    }
+}
+
+pub fn parse_mtl(source: &str, file: &str) -> Result<HashMap<String, MtlMaterial>, ObjError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlMaterial)> = None;
+
+    for (line, tokens) in statements(source) {
+        let ctx = LineContext { file, line };
+        let keyword = tokens[0];
+        let args = &tokens[1..];
+
+        if keyword == "newmtl" {
+            if args.len() != 1 {
+                return Err(ctx.error("'newmtl' expects a single name"));
+            }
+            if let Some((name, material)) = current.take() {
+                materials.insert(name, material);
+            }
+            current = Some((args[0].to_string(), MtlMaterial::default()));
+            continue;
+        }
+
+        let material = match current {
+            Some((_, ref mut material)) => material,
+            None => return Err(ctx.error(format!("'{}' before any 'newmtl'", keyword))),
+        };
+
+        match keyword {
+            "Kd" => material.diffuse = ctx.vec3(keyword, args)?,
+            "Ks" => material.specular = ctx.vec3(keyword, args)?,
+            "Ke" => material.emission = ctx.vec3(keyword, args)?,
+            "Ns" => material.shininess = ctx.floats(keyword, args, 1, 1)?[0],
+            "Ni" => material.refractive_index = ctx.floats(keyword, args, 1, 1)?[0],
+            "d" => material.dissolve = ctx.floats(keyword, args, 1, 1)?[0],
+            "Tr" => material.dissolve = 1.0 - ctx.floats(keyword, args, 1, 1)?[0],
+            "illum" => {
+                material.illum = match args {
+                    [value] => value
+                        .parse()
+                        .map_err(|_| ctx.error(format!("'illum' has invalid model '{}'", value)))?,
+                    _ => return Err(ctx.error("'illum' expects a single model number")),
+                }
+            }
+            // Ambient colour, texture maps and the remaining statements have no equivalent here.
+            _ => {}
+        }
+    }
+
+    if let Some((name, material)) = current {
+        materials.insert(name, material);
+    }
+
+    Ok(materials)
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Triangles for one group/material run, de-indexed into a single index buffer.
+struct MeshBuilder {
+    material: Arc<dyn Material>,
+    vertex_map: HashMap<Corner, u32>,
+    corners: Vec<Corner>,
+    indices: Vec<[u32; 3]>,
+}
+
+impl MeshBuilder {
+    fn new(material: Arc<dyn Material>) -> Self {
+        MeshBuilder {
+            material,
+            vertex_map: HashMap::new(),
+            corners: vec![],
+            indices: vec![],
+        }
+    }
+
+    fn vertex(&mut self, corner: Corner) -> u32 {
+        let corners = &mut self.corners;
+        *self.vertex_map.entry(corner).or_insert_with(|| {
+            corners.push(corner);
+            (corners.len() - 1) as u32
+        })
+    }
+
+    fn build(self, positions: &[Vec3], normals: &[Vec3], uvs: &[(f32, f32)]) -> TriangleMesh {
+        let mesh_positions: Vec<Vec3> =
+            self.corners.iter().map(|c| positions[c.position]).collect();
+
+        let has_normals = self.corners.iter().any(|c| c.normal.is_some());
+        let mesh_normals = if has_normals {
+            // Vertices without a usable normal get the area-weighted average of their faces.
+            let mut generated = vec![Vec3::zero(); self.corners.len()];
+            for &[i0, i1, i2] in &self.indices {
+                let p0 = mesh_positions[i0 as usize];
+                let face_normal =
+                    (mesh_positions[i1 as usize] - p0).cross(mesh_positions[i2 as usize] - p0);
+                for &i in &[i0, i1, i2] {
+                    generated[i as usize] += face_normal;
+                }
+            }
+
+            self.corners
+                .iter()
+                .zip(generated)
+                .map(|(c, g)| match c.normal.map(|n| normals[n]) {
+                    Some(n) if n.squared_magnitude() > 0.0 => n,
+                    _ if g.squared_magnitude() > 0.0 => g.normalized(),
+                    _ => Vec3::new(0.0, 1.0, 0.0),
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let has_uvs = self.corners.iter().any(|c| c.uv.is_some());
+        let mesh_uvs = if has_uvs {
+            self.corners
+                .iter()
+                .map(|c| c.uv.map_or((0.0, 0.0), |i| uvs[i]))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        TriangleMesh::new(
+            mesh_positions,
+            mesh_normals,
+            mesh_uvs,
+            self.indices,
+            self.material,
+        )
+    }
+}
+
+/// Resolves a 1-based or negative (relative to the end) OBJ index.
+fn resolve_index(
+    ctx: &LineContext,
+    token: &str,
+    kind: &str,
+    count: usize,
+) -> Result<usize, ObjError> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| ctx.error(format!("invalid {} index '{}'", kind, token)))?;
+
+    let resolved = if index > 0 {
+        index - 1
+    } else if index < 0 {
+        count as i64 + index
+    } else {
+        return Err(ctx.error(format!("{} index cannot be 0", kind)));
+    };
+
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(ctx.error(format!(
+            "{} index {} out of range ({} defined)",
+            kind, index, count
+        )));
+    }
+
+    Ok(resolved as usize)
+}
+
+/// Loads every face in an OBJ file as triangle meshes, one per group and material. Materials come
+/// from the MTL libraries it references, resolved relative to the OBJ file, and are numbered by
+/// `ids`; faces before any `usemtl` get `default_material`. Polygons are fan-triangulated, so they
+/// are expected to be convex.
+pub fn load_obj(
+    path: &Path,
+    default_material: Arc<dyn Material>,
+    ids: &mut MaterialIds,
+) -> Result<Vec<TriangleMesh>, ObjError> {
+    let source = fs::read_to_string(path).map_err(|err| ObjError::Io(path.to_path_buf(), err))?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+    parse_obj(
+        &source,
+        &path.display().to_string(),
+        default_material,
+        ids,
+        |library| {
+            let mtl_path = directory.join(library);
+            let mtl_source =
+                fs::read_to_string(&mtl_path).map_err(|err| ObjError::Io(mtl_path.clone(), err))?;
+            parse_mtl(&mtl_source, &mtl_path.display().to_string())
+        },
+    )
+}
+
+/// Parses OBJ `source`; `load_library` is called with the name from each `mtllib` statement.
+pub fn parse_obj<F>(
+    source: &str,
+    file: &str,
+    default_material: Arc<dyn Material>,
+    ids: &mut MaterialIds,
+    mut load_library: F,
+) -> Result<Vec<TriangleMesh>, ObjError>
+where
+    F: FnMut(&str) -> Result<HashMap<String, MtlMaterial>, ObjError>,
+{
+    let mut positions: Vec<Vec3> = vec![];
+    let mut normals: Vec<Vec3> = vec![];
+    let mut uvs: Vec<(f32, f32)> = vec![];
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+
+    let mut finished: Vec<MeshBuilder> = vec![];
+    let mut current = MeshBuilder::new(default_material);
+
+    for (line, tokens) in statements(source) {
+        let ctx = LineContext { file, line };
+        let keyword = tokens[0];
+        let args = &tokens[1..];
+
+        match keyword {
+            "v" => {
+                // A trailing w or vertex colour is ignored.
+                let v = ctx.floats(keyword, args, 3, 6)?;
+                positions.push(Vec3::new(v[0], v[1], v[2]));
+            }
+            "vn" => {
+                // Zero normals are kept here and replaced by generated ones when the mesh is built.
+                let n = ctx.vec3(keyword, args)?;
+                normals.push(if n.squared_magnitude() > 0.0 {
+                    n.normalized()
+                } else {
+                    n
+                });
+            }
+            "vt" => {
+                let v = ctx.floats(keyword, args, 1, 3)?;
+                uvs.push((v[0], if v.len() > 1 { v[1] } else { 0.0 }));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(ctx.error(format!(
+                        "face needs at least 3 vertices, found {}",
+                        args.len()
+                    )));
+                }
+
+                let mut face = Vec::with_capacity(args.len());
+                for arg in args {
+                    let parts: Vec<&str> = arg.split('/').collect();
+                    if parts.len() > 3 || parts[0].is_empty() {
+                        return Err(ctx.error(format!("malformed face vertex '{}'", arg)));
+                    }
+
+                    let optional = |part: Option<&&str>, kind: &str, count: usize| match part {
+                        Some(token) if !token.is_empty() => {
+                            resolve_index(&ctx, token, kind, count).map(Some)
+                        }
+                        _ => Ok(None),
+                    };
+
+                    let corner = Corner {
+                        position: resolve_index(&ctx, parts[0], "vertex", positions.len())?,
+                        uv: optional(parts.get(1), "texture coordinate", uvs.len())?,
+                        normal: optional(parts.get(2), "normal", normals.len())?,
+                    };
+                    face.push(current.vertex(corner));
+                }
+
+                for i in 1..face.len() - 1 {
+                    current.indices.push([face[0], face[i], face[i + 1]]);
+                }
+            }
+            "g" | "o" | "usemtl" => {
+                let material = if keyword == "usemtl" {
+                    if args.len() != 1 {
+                        return Err(ctx.error("'usemtl' expects a single name"));
+                    }
+                    match materials.get(args[0]) {
+                        Some(material) => material.clone(),
+                        None => return Err(ctx.error(format!("unknown material '{}'", args[0]))),
+                    }
+                } else {
+                    current.material.clone()
+                };
+
+                let next = MeshBuilder::new(material);
+                let previous = std::mem::replace(&mut current, next);
+                if !previous.indices.is_empty() {
+                    finished.push(previous);
+                }
+            }
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(ctx.error("'mtllib' expects a file name"));
+                }
+                for library in args {
+                    // Sorted so that the IDs do not depend on hash order.
+                    let mut library: Vec<(String, MtlMaterial)> =
+                        load_library(library)?.into_iter().collect();
+                    library.sort_by(|a, b| a.0.cmp(&b.0));
+                    for (name, material) in library {
+                        materials.insert(name, ids.assign(material.to_material()));
+                    }
+                }
+            }
+            // Smoothing groups, lines, points and free-form geometry are not rendered.
+            _ => {}
+        }
+    }
+
+    if !current.indices.is_empty() {
+        finished.push(current);
+    }
+
+    if finished.is_empty() {
+        return Err(ObjError::Parse {
+            file: file.to_string(),
+            line: source.lines().count(),
+            message: "no faces found".to_string(),
+        });
+    }
+
+    Ok(finished
+        .into_iter()
+        .map(|builder| builder.build(&positions, &normals, &uvs))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::hitable::{HitRecord, Hitable};
+    use vector_math::ray::Ray;
+
+    fn grey() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(0.5, 0.5, 0.5))
+    }
+
+    fn no_libraries(name: &str) -> Result<HashMap<String, MtlMaterial>, ObjError> {
+        panic!("unexpected mtllib {}", name)
+    }
+
+    fn parse_error(source: &str) -> (usize, String) {
+        match parse_obj(
+            source,
+            "test.obj",
+            grey(),
+            &mut MaterialIds::default(),
+            no_libraries,
+        ) {
+            Err(ObjError::Parse { line, message, .. }) => (line, message),
+            Err(err) => panic!("unexpected error {}", err),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    const QUAD: &str = "
+        # unit quad in the xy plane
+        v 0 0 0
+        v 1 0 0
+        v 1 1 0
+        v 0 1 0
+        vt 0 0
+        vt 1 0
+        vt 1 1
+        vt 0 1
+        f 1/1 2/2 3/3 4/4
+    ";
+
+    #[test]
+    fn polygon_is_triangulated() {
+        let meshes = parse_obj(
+            QUAD,
+            "quad.obj",
+            grey(),
+            &mut MaterialIds::default(),
+            no_libraries,
+        )
+        .unwrap();
+        assert_eq!(meshes.len(), 1);
+
+        let data = &meshes[0].data;
+        assert_eq!(data.indices, vec![[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(data.uvs.len(), 4);
+        assert!(data.normals.is_empty());
+
+        let ray = Ray::new(Vec3::new(0.2, 0.7, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            meshes[0].hit_test(&ray, 0.0, f32::MAX).map(|hit| hit.t),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn negative_indices_are_relative() {
+        let source = "
+            v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            vn 0 0 2
+            f -3//-1 -2//-1 -1//-1
+        ";
+        let meshes = parse_obj(
+            source,
+            "neg.obj",
+            grey(),
+            &mut MaterialIds::default(),
+            no_libraries,
+        )
+        .unwrap();
+        let data = &meshes[0].data;
+        assert_eq!(
+            data.positions,
+            vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0)
+            ]
+        );
+        assert_eq!(data.normals, vec![Vec3::new(0.0, 0.0, 1.0); 3]);
+    }
+
+    #[test]
+    fn missing_normals_are_generated() {
+        // The second triangle has no normals, so its unshared vertex gets the face normal.
+        let source = "
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vn 0 0 1
+            f 1//1 2//1 3//1
+            f 1 3 4
+        ";
+        let meshes = parse_obj(
+            source,
+            "mixed.obj",
+            grey(),
+            &mut MaterialIds::default(),
+            no_libraries,
+        )
+        .unwrap();
+        let data = &meshes[0].data;
+        assert_eq!(data.positions.len(), 6);
+        assert!(data.normals.iter().all(|n| *n == Vec3::new(0.0, 0.0, 1.0)));
+
+        // So do vertices whose written normal is zero.
+        let zero = source.replace("f 1 3 4", "vn 0 0 0\n f 1//2 3//2 4//2");
+        let meshes = parse_obj(
+            &zero,
+            "zero.obj",
+            grey(),
+            &mut MaterialIds::default(),
+            no_libraries,
+        )
+        .unwrap();
+        assert!(meshes[0]
+            .data
+            .normals
+            .iter()
+            .all(|n| *n == Vec3::new(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn groups_and_materials_split_meshes() {
+        let source = "
+            mtllib scene.mtl
+            v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            g first
+            usemtl gold
+            f 1 2 3
+            f 1 3 2
+            g second
+            f 1 2 3
+            usemtl lamp
+            f 1 2 3
+        ";
+        let mtl = "
+            newmtl gold
+            Kd 0 0 0
+            Ks 1.0 0.8 0.3
+            Ns 200
+            illum 3
+            newmtl lamp
+            Ke 4 4 4
+        ";
+        let mut requested = vec![];
+        let meshes = parse_obj(
+            source,
+            "groups.obj",
+            grey(),
+            &mut MaterialIds::default(),
+            |name| {
+                requested.push(name.to_string());
+                parse_mtl(mtl, name)
+            },
+        )
+        .unwrap();
+
+        assert_eq!(requested, vec!["scene.mtl".to_string()]);
+        let triangle_counts: Vec<usize> = meshes.iter().map(|m| m.data.indices.len()).collect();
+        assert_eq!(triangle_counts, vec![2, 1, 1]);
+        let material_ids: Vec<u32> = meshes.iter().map(|m| m.data.material.id()).collect();
+        assert_eq!(material_ids, vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn mtl_kinds() {
+        let mtl = "
+            newmtl matte
+            Kd 0.2 0.3 0.4
+            newmtl mirror
+            Kd 0 0 0
+            Ks 0.9 0.9 0.9
+            newmtl glass
+            Ni 1.33
+            d 0.1
+            newmtl tinted
+            Tr 0.5
+            newmtl polished
+            illum 3
+            newmtl lamp
+            Kd 1 1 1
+            Ke 10 9 8
+        ";
+        let materials = parse_mtl(mtl, "test.mtl").unwrap();
+        assert_eq!(materials["matte"].kind(), MtlKind::Diffuse);
+        assert_eq!(materials["matte"].diffuse, Vec3::new(0.2, 0.3, 0.4));
+        assert_eq!(materials["mirror"].kind(), MtlKind::Metal);
+        assert_eq!(materials["glass"].kind(), MtlKind::Dielectric);
+        assert_eq!(materials["glass"].refractive_index, 1.33);
+        assert_eq!(materials["tinted"].kind(), MtlKind::Dielectric);
+        assert_eq!(materials["polished"].kind(), MtlKind::Metal);
+        assert_eq!(materials["lamp"].kind(), MtlKind::Emissive);
+
+        // Emissive materials really glow, with the Ke color.
+        let lamp = materials["lamp"].to_material();
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let hit = HitRecord::new(&ray, 1.0, normal, normal, (0.0, 0.0), lamp.as_ref());
+        assert!(lamp.emits_light());
+        assert_eq!(lamp.emitted(&hit), Vec3::new(10.0, 9.0, 8.0));
+        assert!(!materials["matte"].to_material().emits_light());
+    }
+
+    #[test]
+    fn malformed_files_report_lines() {
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 0\n"),
+            (2, "'v' expects 3 to 6 numbers, found 2".to_string())
+        );
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 x 0\n"),
+            (2, "'v' has invalid number 'x'".to_string())
+        );
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n"),
+            (4, "vertex index 4 out of range (3 defined)".to_string())
+        );
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 0\n"),
+            (4, "vertex index cannot be 0".to_string())
+        );
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 0 0\nf 1 2\n"),
+            (3, "face needs at least 3 vertices, found 2".to_string())
+        );
+        assert_eq!(
+            parse_error("v 0 0 0\nusemtl missing\n"),
+            (2, "unknown material 'missing'".to_string())
+        );
+        assert_eq!(parse_error("v 0 0 0\n").1, "no faces found");
+
+        match parse_mtl("Kd 1 1 1\n", "bad.mtl") {
+            Err(err) => assert_eq!(err.to_string(), "bad.mtl:1: 'Kd' before any 'newmtl'"),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn missing_file_is_io_error() {
+        match load_obj(
+            Path::new("does/not/exist.obj"),
+            grey(),
+            &mut MaterialIds::default(),
+        ) {
+            Err(ObjError::Io(path, _)) => assert_eq!(path, PathBuf::from("does/not/exist.obj")),
+            _ => panic!("expected an io error"),
+        }
+    }
+}
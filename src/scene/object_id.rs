@@ -0,0 +1,44 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::light::Light;
+use vector_math::aabb::Aabb;
+use vector_math::ray::Ray;
+
+/// Stamps `id` onto every hit of the wrapped object, for object ID passes.
+pub struct ObjectId {
+    pub id: u32,
+    pub object: Box<dyn Hitable>,
+}
+
+impl Hitable for ObjectId {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let mut hit = self.object.hit_test(ray, t_min, t_max)?;
+        hit.object_id = self.id;
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object.bounding_box()
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        self.object.transmittance(ray, t_min, t_max)
+    }
+
+    fn collect_lights<'a>(&'a self, lights: &mut Vec<&'a dyn Light>) {
+        self.object.collect_lights(lights);
+    }
+}
+
+/// Wraps each object with its position in the list, counting from 1 so that 0 means no object.
+pub fn assign_object_ids(objects: Vec<Box<dyn Hitable>>) -> Vec<Box<dyn Hitable>> {
+    objects
+        .into_iter()
+        .enumerate()
+        .map(|(index, object)| {
+            Box::new(ObjectId {
+                id: index as u32 + 1,
+                object,
+            }) as Box<dyn Hitable>
+        })
+        .collect()
+}
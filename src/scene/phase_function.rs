@@ -0,0 +1,178 @@
+use scene::hitable::HitRecord;
+use scene::light_ray::LightRay;
+use scene::material::Material;
+use scene::texture::{ConstantTexture, Texture};
+use std::f32::consts::PI;
+use std::sync::Arc;
+use vector_math::random_methods::{random, random_unit_vector};
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Henyey–Greenstein phase function for the angle between the incoming and scattered directions of
+/// travel. `g` in `(-1, 1)` is the mean cosine: positive values scatter forward, negative backward.
+pub fn henyey_greenstein(cos_theta: f32, g: f32) -> f32 {
+    let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+}
+
+/// Samples the cosine of the scattering angle with density `henyey_greenstein`.
+fn sample_henyey_greenstein(g: f32) -> f32 {
+    let xi = random::<f32>();
+    if g.abs() < 1e-3 {
+        return 1.0 - 2.0 * xi;
+    }
+    let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+    ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+}
+
+/// Medium that scatters light equally in every direction, keeping the `albedo` fraction of it.
+#[derive(Clone)]
+pub struct Isotropic {
+    pub albedo: Arc<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        Isotropic::textured(Arc::new(ConstantTexture::new(r, g, b)))
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>) -> Self {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay> {
+        let ray = Ray::at_time(hit.point, random_unit_vector(), incident.ray.time);
+        Some(LightRay::new(ray, incident.color * self.albedo(hit)))
+    }
+
+    fn eval(&self, _incident: &Ray, _scattered: &Vec3, hit: &HitRecord) -> Vec3 {
+        self.albedo(hit) / (4.0 * PI)
+    }
+
+    fn pdf(&self, _incident: &Ray, _scattered: &Vec3, _hit: &HitRecord) -> f32 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
+
+    fn albedo(&self, hit: &HitRecord) -> Vec3 {
+        self.albedo.value(hit.uv, &hit.point)
+    }
+}
+
+/// Medium scattering with the Henyey–Greenstein phase function, as for fog and smoke whose
+/// particles favour the forward direction.
+#[derive(Clone)]
+pub struct HenyeyGreenstein {
+    pub albedo: Arc<dyn Texture>,
+    /// The phase function's `g`.
+    pub anisotropy: f32,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(r: f32, g: f32, b: f32, anisotropy: f32) -> Self {
+        HenyeyGreenstein::textured(Arc::new(ConstantTexture::new(r, g, b)), anisotropy)
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>, anisotropy: f32) -> Self {
+        HenyeyGreenstein { albedo, anisotropy }
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, incident: &LightRay, hit: &HitRecord) -> Option<LightRay> {
+        let forward = incident.ray.direction.normalized();
+        let (tangent, bitangent) = forward.orthonormal_basis();
+        let cos_theta = sample_henyey_greenstein(self.anisotropy);
+        let sin_theta = f32::sqrt((1.0 - cos_theta * cos_theta).max(0.0));
+        let phi = 2.0 * PI * random::<f32>();
+        let scattered =
+            sin_theta * (phi.cos() * tangent + phi.sin() * bitangent) + cos_theta * forward;
+        let ray = Ray::at_time(hit.point, scattered, incident.ray.time);
+        // The phase function is sampled exactly, so its ratio to the density is the albedo.
+        Some(LightRay::new(ray, incident.color * self.albedo(hit)))
+    }
+
+    fn eval(&self, incident: &Ray, scattered: &Vec3, hit: &HitRecord) -> Vec3 {
+        self.albedo(hit) * self.pdf(incident, scattered, hit)
+    }
+
+    fn pdf(&self, incident: &Ray, scattered: &Vec3, _hit: &HitRecord) -> f32 {
+        henyey_greenstein(
+            incident.direction.normalized().dot(*scattered),
+            self.anisotropy,
+        )
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
+    }
+
+    fn albedo(&self, hit: &HitRecord) -> Vec3 {
+        self.albedo.value(hit.uv, &hit.point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+    use vector_math::random_methods::seed_thread_rng;
+
+    #[test]
+    fn henyey_greenstein_is_normalized_and_sampled_by_its_density() {
+        for &g in &[-0.7, 0.0, 0.3, 0.9] {
+            // Integrate over cos θ in [-1, 1], times 2π for the azimuth.
+            let n = 20_000;
+            let integral: f32 = (0..n)
+                .map(|i| {
+                    henyey_greenstein(-1.0 + 2.0 * (i as f32 + 0.5) / n as f32, g) * 2.0 / n as f32
+                })
+                .sum::<f32>()
+                * 2.0
+                * PI;
+            assert!((integral - 1.0).abs() < 1e-2, "g = {}: {}", g, integral);
+
+            // The mean cosine of samples is g.
+            seed_thread_rng(5);
+            let mean: f32 = (0..n).map(|_| sample_henyey_greenstein(g)).sum::<f32>() / n as f32;
+            assert!((mean - g).abs() < 0.02, "g = {}: {}", g, mean);
+        }
+    }
+
+    #[test]
+    fn forward_scattering_follows_the_ray() {
+        let medium = HenyeyGreenstein::new(0.5, 0.5, 0.5, 0.8);
+        let surface = Lambertian::new(0.5, 0.5, 0.5);
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -2.0));
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+        let hit = HitRecord::new(&ray, 1.0, normal, normal, (0.0, 0.0), &surface);
+        let path = LightRay::new(ray, Vec3::new(1.0, 1.0, 1.0));
+
+        seed_thread_rng(2);
+        let n = 2000;
+        let scattered_z = |_| medium.scatter(&path, &hit).unwrap().ray.direction.z;
+        let mean_z: f32 = (0..n).map(scattered_z).sum::<f32>() / n as f32;
+        assert!(mean_z < -0.7);
+        let ahead = medium.pdf(&ray, &Vec3::new(0.0, 0.0, -1.0), &hit);
+        let behind = medium.pdf(&ray, &Vec3::new(0.0, 0.0, 1.0), &hit);
+        assert!(ahead > 100.0 * behind);
+        assert_eq!(
+            medium.eval(&ray, &Vec3::new(0.0, 0.0, -1.0), &hit),
+            Vec3::new(0.5, 0.5, 0.5) * ahead
+        );
+        assert!(medium.is_volumetric() && !medium.is_specular());
+    }
+}
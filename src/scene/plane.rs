@@ -0,0 +1,90 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Infinite plane through `point`, facing `normal`. UVs are distances from `point` along two fixed
+/// directions in the plane, so repeating textures tile it.
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub material: Arc<dyn Material>,
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3, material: Arc<dyn Material>) -> Self {
+        Plane {
+            point,
+            normal: normal.normalized(),
+            material,
+        }
+    }
+}
+
+/// Ray parameter in `(t_min, t_max)` where `ray` crosses the plane through `point` with unit
+/// `normal`.
+pub fn plane_intersection(
+    point: &Vec3,
+    normal: &Vec3,
+    ray: &Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Option<f32> {
+    let t = (*point - ray.origin).dot(*normal) / ray.direction.dot(*normal);
+    // Also rejects the NaN of rays lying in the plane.
+    if t > t_min && t < t_max {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+impl Hitable for Plane {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let t = plane_intersection(&self.point, &self.normal, ray, t_min, t_max)?;
+        let (tangent, bitangent) = self.normal.orthonormal_basis();
+        let offset = ray.point_at(t) - self.point;
+        let uv = (offset.dot(tangent), offset.dot(bitangent));
+        Some(HitRecord::new(
+            ray,
+            t,
+            self.normal,
+            self.normal,
+            uv,
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+
+    #[test]
+    fn plane_is_hit_from_either_side() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let plane = Plane::new(
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            material,
+        );
+        let above = Ray::new(Vec3::new(30.0, 1.0, -70.0), Vec3::new(0.0, -1.0, 0.0));
+        let hit = plane.hit_test(&above, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 2.0);
+        assert_eq!(hit.geometric_normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(hit.front_face);
+
+        let below = Ray::new(Vec3::new(0.0, -3.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        assert!(!plane.hit_test(&below, 0.0, f32::MAX).unwrap().front_face);
+        let parallel = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(plane.hit_test(&parallel, 0.0, f32::MAX).is_none());
+        assert!(plane.bounding_box().is_none());
+    }
+}
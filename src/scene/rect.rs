@@ -0,0 +1,183 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::light::{sample_surface, surface_pdf, Light, LightSample};
+use scene::material::Material;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::random_methods::random;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// The plane an `AxisRect` lies in.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RectPlane {
+    Xy,
+    Yz,
+    Xz,
+}
+
+impl RectPlane {
+    /// Indices of the `(u, v, normal)` axes.
+    fn axes(self) -> (usize, usize, usize) {
+        match self {
+            RectPlane::Xy => (0, 1, 2),
+            RectPlane::Yz => (1, 2, 0),
+            RectPlane::Xz => (0, 2, 1),
+        }
+    }
+}
+
+/// Rectangle at `k` along the plane's normal axis, spanning `[a0, a1]` and `[b0, b1]` along its
+/// first and second axes. The outward normal points along the positive normal axis, and UVs run
+/// from 0 at `a0`/`b0` to 1 at `a1`/`b1`.
+pub struct AxisRect {
+    pub plane: RectPlane,
+    pub a0: f32,
+    pub a1: f32,
+    pub b0: f32,
+    pub b1: f32,
+    pub k: f32,
+    pub material: Arc<dyn Material>,
+}
+
+impl AxisRect {
+    pub fn new(
+        plane: RectPlane,
+        a0: f32,
+        a1: f32,
+        b0: f32,
+        b1: f32,
+        k: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        AxisRect {
+            plane,
+            a0: a0.min(a1),
+            a1: a0.max(a1),
+            b0: b0.min(b1),
+            b1: b0.max(b1),
+            k,
+            material,
+        }
+    }
+
+    /// Places `(a, b, normal)` coordinates on their axes.
+    fn point(&self, a: f32, b: f32, n: f32) -> Vec3 {
+        let (ua, vb, na) = self.plane.axes();
+        let mut p = [0.0; 3];
+        p[ua] = a;
+        p[vb] = b;
+        p[na] = n;
+        Vec3::new(p[0], p[1], p[2])
+    }
+
+    pub fn area(&self) -> f32 {
+        (self.a1 - self.a0) * (self.b1 - self.b0)
+    }
+}
+
+impl Hitable for AxisRect {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let (ua, vb, na) = self.plane.axes();
+        let t = (self.k - ray.origin[na]) / ray.direction[na];
+        // Also rejects the NaN of rays lying in the plane.
+        if !(t > t_min && t < t_max) {
+            return None;
+        }
+        let a = ray.origin[ua] + t * ray.direction[ua];
+        let b = ray.origin[vb] + t * ray.direction[vb];
+        if a < self.a0 || a > self.a1 || b < self.b0 || b > self.b1 {
+            return None;
+        }
+
+        let normal = self.point(0.0, 0.0, 1.0);
+        let uv = (
+            (a - self.a0) / (self.a1 - self.a0),
+            (b - self.b0) / (self.b1 - self.b0),
+        );
+        let mut hit = HitRecord::new(ray, t, normal, normal, uv, self.material.as_ref());
+        if self.material.emits_light() {
+            hit.light = Some(self);
+        }
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(
+            Aabb::empty()
+                .grow(self.point(self.a0, self.b0, self.k))
+                .grow(self.point(self.a1, self.b1, self.k)),
+        )
+    }
+
+    fn collect_lights<'a>(&'a self, lights: &mut Vec<&'a dyn Light>) {
+        if self.material.emits_light() {
+            lights.push(self);
+        }
+    }
+}
+
+/// Samples points uniformly over the rectangle's area.
+impl Light for AxisRect {
+    fn sample(&self, origin: &Vec3) -> Option<LightSample> {
+        let a = self.a0 + random::<f32>() * (self.a1 - self.a0);
+        let b = self.b0 + random::<f32>() * (self.b1 - self.b0);
+        sample_surface(self, self.area(), origin, self.point(a, b, self.k))
+    }
+
+    fn pdf(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        surface_pdf(self, self.area(), origin, direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::diffuse_light::DiffuseLight;
+    use scene::lambertian::Lambertian;
+
+    #[test]
+    fn rect_hits_inside_its_bounds() {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        let floor = AxisRect::new(RectPlane::Xz, -1.0, 1.0, 0.0, 4.0, 0.5, material);
+        let down = Ray::new(Vec3::new(0.5, 2.0, 1.0), Vec3::new(0.0, -1.0, 0.0));
+        let hit = floor.hit_test(&down, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 1.5);
+        assert_eq!(hit.geometric_normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(hit.front_face);
+        assert_eq!(hit.uv, (0.75, 0.25));
+
+        let outside = Ray::new(Vec3::new(1.5, 2.0, 1.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(floor.hit_test(&outside, 0.0, f32::MAX).is_none());
+        let parallel = Ray::new(Vec3::new(0.0, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(floor.hit_test(&parallel, 0.0, f32::MAX).is_none());
+        assert_eq!(
+            floor.bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(-1.0, 0.5, 0.0),
+                Vec3::new(1.0, 0.5, 4.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn rect_light_samples_integrate_to_one() {
+        use vector_math::random_methods::{random_unit_vector, seed_thread_rng};
+
+        let emitter = Arc::new(DiffuseLight::new(3.0, 3.0, 3.0));
+        let light = AxisRect::new(RectPlane::Xy, -1.0, 1.0, -0.5, 0.5, -2.0, emitter);
+        let origin = Vec3::new(0.3, 0.2, 0.0);
+        seed_thread_rng(3);
+        for _ in 0..100 {
+            let sample = light.sample(&origin).unwrap();
+            assert_eq!(sample.radiance, Vec3::new(3.0, 3.0, 3.0));
+            assert!((sample.pdf - light.pdf(&origin, &sample.direction)).abs() < 1e-3 * sample.pdf);
+            assert!(((origin + sample.distance * sample.direction).z + 2.0).abs() < 1e-5);
+        }
+
+        let n = 200_000;
+        let total: f32 = (0..n)
+            .map(|_| light.pdf(&origin, &random_unit_vector()))
+            .sum();
+        assert!((total * 4.0 * std::f32::consts::PI / n as f32 - 1.0).abs() < 0.05);
+    }
+}
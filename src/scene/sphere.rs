@@ -1,59 +1,212 @@
-use scene::hitable::Hitable;
-use scene::light_ray::LightRay;
+use scene::hitable::{HitRecord, Hitable};
+use scene::light::{Light, LightSample};
 use scene::material::*;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::random_methods::random;
 use vector_math::ray::Ray;
 use vector_math::vec3::Vec3;
 
 pub struct Sphere {
     pub center: Vec3,
     pub radius: f32,
-    pub material: Box<Material>,
+    pub material: Arc<dyn Material>,
 }
 
 impl Sphere {
-    pub fn new(x: f32, y: f32, z: f32, radius: f32, material: Box<Material>) -> Self {
+    pub fn new(x: f32, y: f32, z: f32, radius: f32, material: Arc<dyn Material>) -> Self {
         Sphere {
             center: Vec3::new(x, y, z),
             radius,
             material,
         }
     }
+
+    fn hit_record(&self, ray: &Ray, t: f32) -> HitRecord<'_> {
+        // Dividing by the signed radius turns negative-radius spheres inside out, for hollow glass.
+        let outward_normal = (ray.point_at(t) - self.center) / self.radius;
+        let mut hit = HitRecord::new(
+            ray,
+            t,
+            outward_normal,
+            outward_normal,
+            sphere_uv(&outward_normal),
+            self.material.as_ref(),
+        );
+        if self.material.emits_light() {
+            hit.light = Some(self);
+        }
+        hit
+    }
+
+    /// `1 - cos` of the half-angle of the cone the sphere subtends from `origin`; `None` inside.
+    fn cone_extent(&self, origin: &Vec3) -> Option<f32> {
+        let distance_squared = (self.center - *origin).squared_magnitude();
+        let sin_squared = self.radius * self.radius / distance_squared;
+        if sin_squared >= 1.0 {
+            return None;
+        }
+        // Written to avoid cancellation for small, distant spheres.
+        Some(sin_squared / (1.0 + f32::sqrt(1.0 - sin_squared)))
+    }
+}
+
+/// Longitude/latitude coordinates of a point on the unit sphere, with v = 0 at the south pole.
+pub fn sphere_uv(p: &Vec3) -> (f32, f32) {
+    let phi = f32::atan2(-p.z, p.x) + PI;
+    let theta = f32::acos((-p.y).clamp(-1.0, 1.0));
+    (phi / (2.0 * PI), theta / PI)
+}
+
+/// Ray parameter of the nearest intersection with a sphere in `(t_min, t_max)`.
+pub fn sphere_intersection(
+    center: &Vec3,
+    radius: f32,
+    ray: &Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Option<f32> {
+    let oc = ray.origin - *center; // vector from sphere center to ray origin
+
+    let a = ray.direction.dot(ray.direction);
+    let b = oc.dot(ray.direction);
+    let c = oc.dot(oc) - radius * radius;
+    let discriminant = b * b - a * c;
+
+    if discriminant > 0.0 {
+        let temp = (-b - f32::sqrt(discriminant)) / a;
+        if temp < t_max && temp > t_min {
+            return Some(temp);
+        }
+
+        let temp = (-b + f32::sqrt(discriminant)) / a;
+        if temp < t_max && temp > t_min {
+            return Some(temp);
+        }
+    }
+
+    None
 }
 
 impl Hitable for Sphere {
-    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<f32> {
-        let oc = ray.origin - self.center; // vector from sphere center to ray origin
-
-        let a = ray.direction.dot(ray.direction);
-        let b = oc.dot(ray.direction);
-        let c = oc.dot(oc) - self.radius * self.radius;
-        let discriminant = b * b - a * c;
-
-        if discriminant > 0.0 {
-            let temp = (-b - f32::sqrt(b * b - a * c)) / a;
-            if temp < t_max && temp > t_min {
-                return Some(temp);
-            }
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let t = sphere_intersection(&self.center, self.radius, ray, t_min, t_max)?;
+        Some(self.hit_record(ray, t))
+    }
 
-            let temp = (-b + f32::sqrt(b * b - a * c)) / a;
-            if temp < t_max && temp > t_min {
-                return Some(temp);
-            }
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius.abs() * Vec3::new(1.0, 1.0, 1.0);
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
+
+    fn collect_lights<'a>(&'a self, lights: &mut Vec<&'a dyn Light>) {
+        if self.material.emits_light() {
+            lights.push(self);
         }
+    }
+}
+
+/// Samples directions uniformly within the cone the sphere subtends, so every sample hits it.
+impl Light for Sphere {
+    fn sample(&self, origin: &Vec3) -> Option<LightSample> {
+        let extent = self.cone_extent(origin)?;
+        let axis = (self.center - *origin).normalized();
+        let (tangent, bitangent) = axis.orthonormal_basis();
 
-        None
+        let cos_theta = 1.0 - random::<f32>() * extent;
+        let sin_theta = f32::sqrt((1.0 - cos_theta * cos_theta).max(0.0));
+        let phi = 2.0 * PI * random::<f32>();
+        let direction =
+            sin_theta * (phi.cos() * tangent + phi.sin() * bitangent) + cos_theta * axis;
+
+        // Directions at the rim of the cone can graze past the sphere by rounding error.
+        let ray = Ray::new(*origin, direction);
+        let hit = self.hit_test(&ray, 0.0, f32::MAX)?;
+        Some(LightSample {
+            direction,
+            distance: hit.t,
+            radiance: hit.material.emitted(&hit),
+            pdf: 1.0 / (2.0 * PI * extent),
+        })
     }
 
-    fn scatter(&self, incident: &LightRay) -> Option<LightRay> {
-        match self.hit_test(&incident.ray, 0.0, std::f32::MAX) {
-            Some(t) => {
-                let hit_point = incident.ray.point_at(t);
-                let incoming_ray = Ray::new(hit_point, incident.ray.direction); // assuming direction is normalized
-                let incoming_light = LightRay::new(incoming_ray, incident.color);
-                let normal = (hit_point - self.center).normalized();
-                self.material.scatter(&incoming_light, &hit_point, &normal)
+    fn pdf(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        match self.cone_extent(origin) {
+            Some(extent)
+                if self
+                    .hit_test(&Ray::new(*origin, *direction), 0.0, f32::MAX)
+                    .is_some() =>
+            {
+                1.0 / (2.0 * PI * extent)
             }
-            None => None,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+
+    #[test]
+    fn sphere_hit_record_faces_ray() {
+        let sphere = Sphere::new(0.0, 0.0, 0.0, 1.0, Arc::new(Lambertian::new(0.5, 0.5, 0.5)));
+
+        let outside = Ray::new(Vec3::new(0.0, 0.0, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = sphere.hit_test(&outside, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 2.0);
+        assert_eq!(hit.point, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(hit.geometric_normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(hit.front_face);
+
+        let inside = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = sphere.hit_test(&inside, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 1.0);
+        assert_eq!(hit.shading_normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn sphere_uv_poles_and_longitude() {
+        assert_eq!(sphere_uv(&Vec3::new(0.0, -1.0, 0.0)).1, 0.0);
+        assert_eq!(sphere_uv(&Vec3::new(0.0, 1.0, 0.0)).1, 1.0);
+        assert_eq!(sphere_uv(&Vec3::new(1.0, 0.0, 0.0)), (0.5, 0.5));
+        assert_eq!(sphere_uv(&Vec3::new(0.0, 0.0, -1.0)).0, 0.75);
+    }
+
+    #[test]
+    fn light_samples_cover_the_subtended_cone() {
+        use scene::diffuse_light::DiffuseLight;
+        use vector_math::random_methods::{random_unit_vector, seed_thread_rng};
+
+        let light = Sphere::new(
+            0.0,
+            3.0,
+            0.0,
+            1.0,
+            Arc::new(DiffuseLight::new(2.0, 2.0, 2.0)),
+        );
+        let origin = Vec3::new(0.5, 0.0, 0.0);
+        seed_thread_rng(7);
+        for _ in 0..100 {
+            let sample = light.sample(&origin).unwrap();
+            assert!((sample.direction.magnitude() - 1.0).abs() < 1e-5);
+            assert_eq!(sample.radiance, Vec3::new(2.0, 2.0, 2.0));
+            assert_eq!(sample.pdf, light.pdf(&origin, &sample.direction));
+            let on_surface = origin + sample.distance * sample.direction;
+            assert!(((on_surface - light.center).magnitude() - 1.0).abs() < 1e-4);
         }
+
+        // The density is uniform over the cone, so it integrates to one over all directions.
+        let n = 100_000;
+        let total: f32 = (0..n)
+            .map(|_| light.pdf(&origin, &random_unit_vector()))
+            .sum();
+        assert!((total * 4.0 * PI / n as f32 - 1.0).abs() < 0.05);
+
+        assert!(light.sample(&Vec3::new(0.0, 3.5, 0.0)).is_none());
+        assert_eq!(light.pdf(&origin, &Vec3::new(0.0, -1.0, 0.0)), 0.0);
     }
 }
@@ -0,0 +1,239 @@
+use image;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use tone_mapping::srgb_decode;
+use vector_math::vec3::Vec3;
+
+/// A color that varies over a surface, looked up by the hit's surface coordinates and position.
+pub trait Texture: Send + Sync {
+    fn value(&self, uv: (f32, f32), point: &Vec3) -> Vec3;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ConstantTexture {
+    pub color: Vec3,
+}
+
+impl ConstantTexture {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        ConstantTexture {
+            color: Vec3::new(r, g, b),
+        }
+    }
+}
+
+impl Texture for ConstantTexture {
+    fn value(&self, _uv: (f32, f32), _point: &Vec3) -> Vec3 {
+        self.color
+    }
+}
+
+/// Solid checkerboard of `size`-wide cubes in world space, so it needs no surface coordinates.
+pub struct CheckerTexture {
+    pub even: Arc<dyn Texture>,
+    pub odd: Arc<dyn Texture>,
+    pub size: f32,
+}
+
+impl CheckerTexture {
+    pub fn new(even: Arc<dyn Texture>, odd: Arc<dyn Texture>, size: f32) -> Self {
+        CheckerTexture { even, odd, size }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, uv: (f32, f32), point: &Vec3) -> Vec3 {
+        let cell = |x: f32| (x / self.size).floor() as i64;
+        if (cell(point.x) + cell(point.y) + cell(point.z)).rem_euclid(2) == 0 {
+            self.even.value(uv, point)
+        } else {
+            self.odd.value(uv, point)
+        }
+    }
+}
+
+/// How texture coordinates outside `[0, 1]` are brought back onto the image.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WrapMode {
+    Repeat,
+    /// Repeats with every other copy flipped, so that edges match.
+    Mirror,
+    /// Extends the edge texels.
+    Clamp,
+}
+
+impl WrapMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "repeat" => Some(WrapMode::Repeat),
+            "mirror" => Some(WrapMode::Mirror),
+            "clamp" => Some(WrapMode::Clamp),
+            _ => None,
+        }
+    }
+
+    /// Maps a texel index onto `0..size`.
+    fn apply(self, index: i64, size: usize) -> usize {
+        let size = size as i64;
+        let index = match self {
+            WrapMode::Repeat => index.rem_euclid(size),
+            WrapMode::Mirror => {
+                let period = index.rem_euclid(2 * size);
+                if period < size {
+                    period
+                } else {
+                    2 * size - 1 - period
+                }
+            }
+            WrapMode::Clamp => index.clamp(0, size - 1),
+        };
+        index as usize
+    }
+}
+
+/// Bilinearly filtered image mapped over the surface's UV coordinates, v = 0 at the bottom row.
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    /// Linear colors, row-major from the top-left corner.
+    pixels: Vec<Vec3>,
+    pub wrap: WrapMode,
+}
+
+impl ImageTexture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3>, wrap: WrapMode) -> Self {
+        assert!(width > 0 && height > 0, "texture image is empty");
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "texture pixel count does not match its size"
+        );
+        ImageTexture {
+            width,
+            height,
+            pixels,
+            wrap,
+        }
+    }
+
+    /// Loads an 8-bit image in any format the `image` crate reads, such as PNG or JPEG, decoding
+    /// its sRGB values to linear color.
+    pub fn load(path: &Path, wrap: WrapMode) -> Result<Self, io::Error> {
+        let image = image::open(path)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?
+            .to_rgb();
+        let (width, height) = image.dimensions();
+        let decode = |c: u8| srgb_decode(c as f32 / 255.0);
+        let pixels = image
+            .pixels()
+            .map(|p| Vec3::new(decode(p.data[0]), decode(p.data[1]), decode(p.data[2])))
+            .collect();
+        Ok(ImageTexture::new(
+            width as usize,
+            height as usize,
+            pixels,
+            wrap,
+        ))
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Vec3 {
+        let x = self.wrap.apply(x, self.width);
+        let y = self.wrap.apply(y, self.height);
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, uv: (f32, f32), _point: &Vec3) -> Vec3 {
+        // Texel centers sit at half-integer coordinates.
+        let x = uv.0 * self.width as f32 - 0.5;
+        let y = (1.0 - uv.1) * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = (1.0 - fx) * self.texel(x0, y0) + fx * self.texel(x0 + 1, y0);
+        let bottom = (1.0 - fx) * self.texel(x0, y0 + 1) + fx * self.texel(x0 + 1, y0 + 1);
+        (1.0 - fy) * top + fy * bottom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checker_alternates_in_three_dimensions() {
+        let checker = CheckerTexture::new(
+            Arc::new(ConstantTexture::new(1.0, 1.0, 1.0)),
+            Arc::new(ConstantTexture::new(0.0, 0.0, 0.0)),
+            0.5,
+        );
+        let at = |x: f32, y: f32, z: f32| checker.value((0.0, 0.0), &Vec3::new(x, y, z)).x;
+        assert_eq!(at(0.25, 0.25, 0.25), 1.0);
+        assert_eq!(at(0.75, 0.25, 0.25), 0.0);
+        assert_eq!(at(0.75, 0.75, 0.25), 1.0);
+        assert_eq!(at(0.25, 0.25, -0.25), 0.0);
+    }
+
+    #[test]
+    fn wrap_modes() {
+        assert_eq!(WrapMode::Repeat.apply(-1, 4), 3);
+        assert_eq!(WrapMode::Repeat.apply(9, 4), 1);
+        assert_eq!(WrapMode::Mirror.apply(-1, 4), 0);
+        assert_eq!(WrapMode::Mirror.apply(5, 4), 2);
+        assert_eq!(WrapMode::Clamp.apply(-3, 4), 0);
+        assert_eq!(WrapMode::Clamp.apply(7, 4), 3);
+    }
+
+    #[test]
+    fn image_is_filtered_bilinearly() {
+        // Black and white texels side by side, top row first.
+        let black = Vec3::zero();
+        let white = Vec3::new(1.0, 1.0, 1.0);
+        let texture = ImageTexture::new(2, 1, vec![black, white], WrapMode::Clamp);
+        let at = |u: f32| texture.value((u, 0.5), &Vec3::zero()).x;
+        assert_eq!(at(0.25), 0.0);
+        assert_eq!(at(0.5), 0.5);
+        assert_eq!(at(0.75), 1.0);
+        assert_eq!(at(1.5), 1.0);
+
+        // Repeating blends the right edge with the left one.
+        let repeating = ImageTexture::new(2, 1, vec![black, white], WrapMode::Repeat);
+        assert_eq!(repeating.value((1.0, 0.5), &Vec3::zero()).x, 0.5);
+    }
+
+    #[test]
+    fn loads_png_as_linear_color() {
+        use image::{ImageBuffer, Rgb};
+
+        let path = std::env::temp_dir().join("texture_loads_png.png");
+        let mut image = ImageBuffer::new(1, 2);
+        image.put_pixel(
+            0,
+            0,
+            Rgb {
+                data: [255u8, 0, 0],
+            },
+        );
+        image.put_pixel(
+            0,
+            1,
+            Rgb {
+                data: [0u8, 0, 188],
+            },
+        );
+        image.save(&path).unwrap();
+        let texture = ImageTexture::load(&path, WrapMode::Clamp).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // v = 1 is the top row.
+        assert_eq!(
+            texture.value((0.5, 1.0), &Vec3::zero()),
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+        assert!((texture.value((0.5, 0.0), &Vec3::zero()).z - 0.5).abs() < 0.01);
+        assert!(ImageTexture::load(Path::new("missing.png"), WrapMode::Repeat).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use scene::cylinder::turn_fraction;
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::frame::AxisFrame;
+use vector_math::polynomial::solve_quartic;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Ring around `axis` through `center`: a tube of `minor_radius` swept along a circle of
+/// `major_radius`. UVs run around the axis and then around the tube.
+pub struct Torus {
+    frame: AxisFrame,
+    pub major_radius: f32,
+    pub minor_radius: f32,
+    pub material: Arc<dyn Material>,
+}
+
+impl Torus {
+    pub fn new(
+        center: Vec3,
+        axis: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Torus {
+            frame: AxisFrame::new(center, axis),
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+
+    /// Closest root in `(t_min, t_max)` of `(|p|² + R² - r²)² = 4R² (x² + z²)` along a local ray.
+    fn intersect(&self, local: &Ray, t_min: f32, t_max: f32) -> Option<f32> {
+        // Start from the bounding sphere, keeping the coefficients small for distant rays.
+        let outer = self.major_radius + self.minor_radius;
+        let (o, d) = (local.origin, local.direction);
+        let a = d.dot(d);
+        let b = o.dot(d);
+        let discriminant = b * b - a * (o.dot(o) - outer * outer);
+        if discriminant <= 0.0 {
+            return None;
+        }
+        let start = ((-b - discriminant.sqrt()) / a).max(0.0);
+        let end = (-b + discriminant.sqrt()) / a;
+        if end <= t_min || start >= t_max {
+            return None;
+        }
+
+        let o = local.point_at(start);
+        let (ox, oy, oz) = (f64::from(o.x), f64::from(o.y), f64::from(o.z));
+        let (dx, dy, dz) = (f64::from(d.x), f64::from(d.y), f64::from(d.z));
+        let (big_r2, r2) = (
+            f64::from(self.major_radius).powi(2),
+            f64::from(self.minor_radius).powi(2),
+        );
+
+        let dd = dx * dx + dy * dy + dz * dz;
+        let od = ox * dx + oy * dy + oz * dz;
+        let e = ox * ox + oy * oy + oz * oz - big_r2 - r2;
+        let c3 = 4.0 * dd * od;
+        let c2 = 2.0 * dd * e + 4.0 * od * od + 4.0 * big_r2 * dy * dy;
+        let c1 = 4.0 * od * e + 8.0 * big_r2 * oy * dy;
+        let c0 = e * e - 4.0 * big_r2 * (r2 - oy * oy);
+
+        solve_quartic(
+            c3 / (dd * dd),
+            c2 / (dd * dd),
+            c1 / (dd * dd),
+            c0 / (dd * dd),
+        )
+        .into_iter()
+        .map(|t| start + t as f32)
+        .find(|&t| t > t_min && t < t_max)
+    }
+}
+
+impl Hitable for Torus {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let local = self.frame.to_local_ray(ray);
+        let t = self.intersect(&local, t_min, t_max)?;
+        let p = local.point_at(t);
+
+        // The normal points from the nearest point on the ring through the center of the tube.
+        let radial = Vec3::new(p.x, 0.0, p.z);
+        let ring = if radial.squared_magnitude() > 0.0 {
+            self.major_radius * radial.normalized()
+        } else {
+            Vec3::new(self.major_radius, 0.0, 0.0)
+        };
+        let local_normal = (p - ring).normalized();
+        let normal = self.frame.to_world_vector(&local_normal);
+
+        let tube_angle =
+            f32::atan2(local_normal.y, local_normal.dot(ring.normalized())).rem_euclid(2.0 * PI);
+        let uv = (turn_fraction(&p), tube_angle / (2.0 * PI));
+        Some(HitRecord::new(
+            ray,
+            t,
+            normal,
+            normal,
+            uv,
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.minor_radius * Vec3::new(1.0, 1.0, 1.0);
+        let ring = self.frame.circle_bounds(0.0, self.major_radius);
+        Some(Aabb::new(ring.min - r, ring.max + r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+
+    fn torus() -> Torus {
+        let material = Arc::new(Lambertian::new(0.5, 0.5, 0.5));
+        Torus::new(Vec3::zero(), Vec3::new(0.0, 1.0, 0.0), 2.0, 0.5, material)
+    }
+
+    #[test]
+    fn torus_is_hit_on_the_tube() {
+        let torus = torus();
+        // Along the x axis the ray crosses the tube at x = 2.5, 1.5, -1.5 and -2.5.
+        let ray = Ray::new(Vec3::new(10.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let hit = torus.hit_test(&ray, 0.0, f32::MAX).unwrap();
+        assert!((hit.t - 7.5).abs() < 1e-4);
+        assert!((hit.geometric_normal - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-4);
+        assert!(hit.front_face);
+        let hit = torus.hit_test(&ray, 7.6, f32::MAX).unwrap();
+        assert!((hit.t - 8.5).abs() < 1e-4);
+        assert!(!hit.front_face);
+
+        // Straight down through the hole misses, and onto the top of the tube hits.
+        let hole = Ray::new(Vec3::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(torus.hit_test(&hole, 0.0, f32::MAX).is_none());
+        let top = Ray::new(Vec3::new(0.0, 5.0, -2.0), Vec3::new(0.0, -1.0, 0.0));
+        let hit = torus.hit_test(&top, 0.0, f32::MAX).unwrap();
+        assert!((hit.t - 4.5).abs() < 1e-4);
+        assert!((hit.uv.1 - 0.25).abs() < 1e-4);
+
+        assert_eq!(
+            torus.bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(-2.5, -0.5, -2.5),
+                Vec3::new(2.5, 0.5, 2.5)
+            ))
+        );
+    }
+
+    #[test]
+    fn torus_hits_lie_on_its_surface() {
+        let torus = torus();
+        let mut hits = 0;
+        for i in 0..200 {
+            let angle = i as f32 * 0.1;
+            let origin = Vec3::new(
+                8.0 * angle.cos(),
+                3.0 * (angle * 0.7).sin(),
+                8.0 * angle.sin(),
+            );
+            let target = Vec3::new((angle * 1.3).sin() * 2.0, 0.0, (angle * 0.9).cos() * 2.0);
+            let ray = Ray::new(origin, (target - origin).normalized());
+            if let Some(hit) = torus.hit_test(&ray, 0.0, f32::MAX) {
+                hits += 1;
+                let p = hit.point;
+                let ring_distance = f32::sqrt(p.x * p.x + p.z * p.z) - 2.0;
+                assert!((f32::sqrt(ring_distance * ring_distance + p.y * p.y) - 0.5).abs() < 1e-3);
+            }
+        }
+        assert!(hits > 50);
+    }
+}
@@ -0,0 +1,193 @@
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Watertight ray/triangle intersection (Woop, Benthin and Wald 2013). Rays through a shared edge
+/// or vertex hit at least one of the adjacent triangles. Returns `(t, b1, b2)`, where `b1` and `b2`
+/// are the barycentric weights of `p1` and `p2`.
+pub fn intersect_triangle(
+    ray: &Ray,
+    p0: Vec3,
+    p1: Vec3,
+    p2: Vec3,
+    t_min: f32,
+    t_max: f32,
+) -> Option<(f32, f32, f32)> {
+    let d = ray.direction;
+
+    // Permute axes so the largest direction component becomes z.
+    let kz = if d.x.abs() > d.y.abs() {
+        if d.x.abs() > d.z.abs() {
+            0
+        } else {
+            2
+        }
+    } else if d.y.abs() > d.z.abs() {
+        1
+    } else {
+        2
+    };
+    let mut kx = (kz + 1) % 3;
+    let mut ky = (kx + 1) % 3;
+    if d[kz] < 0.0 {
+        std::mem::swap(&mut kx, &mut ky);
+    }
+
+    let sx = d[kx] / d[kz];
+    let sy = d[ky] / d[kz];
+    let sz = 1.0 / d[kz];
+
+    let a = p0 - ray.origin;
+    let b = p1 - ray.origin;
+    let c = p2 - ray.origin;
+
+    // Shear so the ray runs along +z from the origin.
+    let ax = a[kx] - sx * a[kz];
+    let ay = a[ky] - sy * a[kz];
+    let bx = b[kx] - sx * b[kz];
+    let by = b[ky] - sy * b[kz];
+    let cx = c[kx] - sx * c[kz];
+    let cy = c[ky] - sy * c[kz];
+
+    let mut u = cx * by - cy * bx;
+    let mut v = ax * cy - ay * cx;
+    let mut w = bx * ay - by * ax;
+
+    // Edge functions that land exactly on zero are resolved in double precision.
+    if u == 0.0 || v == 0.0 || w == 0.0 {
+        u = (f64::from(cx) * f64::from(by) - f64::from(cy) * f64::from(bx)) as f32;
+        v = (f64::from(ax) * f64::from(cy) - f64::from(ay) * f64::from(cx)) as f32;
+        w = (f64::from(bx) * f64::from(ay) - f64::from(by) * f64::from(ax)) as f32;
+    }
+
+    if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
+        return None;
+    }
+
+    let det = u + v + w;
+    if det == 0.0 {
+        return None;
+    }
+
+    let az = sz * a[kz];
+    let bz = sz * b[kz];
+    let cz = sz * c[kz];
+    let t = (u * az + v * bz + w * cz) / det;
+
+    if t > t_min && t < t_max {
+        Some((t, v / det, w / det))
+    } else {
+        None
+    }
+}
+
+/// Triangle with counter-clockwise winding facing the geometric normal.
+pub struct Triangle {
+    pub vertices: [Vec3; 3],
+    pub material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    pub fn new(v0: Vec3, v1: Vec3, v2: Vec3, material: Arc<dyn Material>) -> Self {
+        Triangle {
+            vertices: [v0, v1, v2],
+            material,
+        }
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        (v1 - v0).cross(v2 - v0).normalized()
+    }
+}
+
+impl Hitable for Triangle {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let [v0, v1, v2] = self.vertices;
+        let (t, b1, b2) = intersect_triangle(ray, v0, v1, v2, t_min, t_max)?;
+        let normal = self.normal();
+        Some(HitRecord::new(
+            ray,
+            t,
+            normal,
+            normal,
+            (b1, b2),
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [v0, v1, v2] = self.vertices;
+        Some(Aabb::empty().grow(v0).grow(v1).grow(v2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_triangle() -> (Vec3, Vec3, Vec3) {
+        (
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn triangle_hit_barycentrics() {
+        let (p0, p1, p2) = unit_triangle();
+        let ray = Ray::new(Vec3::new(0.25, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let (t, b1, b2) = intersect_triangle(&ray, p0, p1, p2, 0.0, f32::MAX).unwrap();
+        assert_eq!(t, 2.0);
+        assert_eq!(b1, 0.25);
+        assert_eq!(b2, 0.5);
+
+        // Either side of the triangle is hit.
+        let below = Ray::new(Vec3::new(0.25, 0.25, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(intersect_triangle(&below, p0, p1, p2, 0.0, f32::MAX).is_some());
+    }
+
+    #[test]
+    fn triangle_miss_and_range() {
+        let (p0, p1, p2) = unit_triangle();
+        let outside = Ray::new(Vec3::new(0.75, 0.75, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(intersect_triangle(&outside, p0, p1, p2, 0.0, f32::MAX).is_none());
+
+        let ray = Ray::new(Vec3::new(0.25, 0.25, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(intersect_triangle(&ray, p0, p1, p2, 0.0, 1.5).is_none());
+        assert!(intersect_triangle(&ray, p0, p1, p2, 2.5, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn shared_edge_is_watertight() {
+        // Two triangles sharing the unit square's diagonal; rays along it must not leak.
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(1.0, 0.0, 0.0);
+        let c = Vec3::new(1.0, 1.0, 0.0);
+        let d = Vec3::new(0.0, 1.0, 0.0);
+
+        for i in 1..100 {
+            let s = i as f32 / 100.0;
+            for &direction in &[
+                Vec3::new(0.0, 0.0, -1.0),
+                Vec3::new(0.3, -0.2, -1.0),
+                Vec3::new(-0.7, 0.4, -1.0),
+            ] {
+                // Aim each ray at a point on the shared edge.
+                let target = Vec3::new(s, s, 0.0);
+                let ray = Ray::new(target - direction, direction);
+                let first = intersect_triangle(&ray, a, b, c, 0.0, f32::MAX);
+                let second = intersect_triangle(&ray, a, c, d, 0.0, f32::MAX);
+                assert!(
+                    first.is_some() || second.is_some(),
+                    "ray leaked at s = {}",
+                    s
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use scene::bvh_node::BvhNode;
+use scene::hitable::{HitRecord, Hitable};
+use scene::material::Material;
+use scene::triangle::intersect_triangle;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::ray::Ray;
+use vector_math::vec3::Vec3;
+
+/// Vertex and index buffers shared by every triangle of a mesh.
+pub struct MeshData {
+    pub positions: Vec<Vec3>,
+    /// Per-vertex shading normals, or empty to use face normals.
+    pub normals: Vec<Vec3>,
+    /// Per-vertex texture coordinates, or empty.
+    pub uvs: Vec<(f32, f32)>,
+    /// Counter-clockwise vertex indices of each triangle.
+    pub indices: Vec<[u32; 3]>,
+    pub material: Arc<dyn Material>,
+}
+
+impl MeshData {
+    fn vertices(&self, triangle: usize) -> [Vec3; 3] {
+        let [i0, i1, i2] = self.indices[triangle];
+        [
+            self.positions[i0 as usize],
+            self.positions[i1 as usize],
+            self.positions[i2 as usize],
+        ]
+    }
+
+    /// Interpolated vertex normal at barycentric `(b1, b2)`, falling back to the face normal.
+    pub fn shading_normal(&self, triangle: usize, b1: f32, b2: f32) -> Vec3 {
+        if self.normals.is_empty() {
+            let [v0, v1, v2] = self.vertices(triangle);
+            return (v1 - v0).cross(v2 - v0).normalized();
+        }
+
+        let [i0, i1, i2] = self.indices[triangle];
+        let n0 = self.normals[i0 as usize];
+        let n1 = self.normals[i1 as usize];
+        let n2 = self.normals[i2 as usize];
+        ((1.0 - b1 - b2) * n0 + b1 * n1 + b2 * n2).normalized()
+    }
+
+    /// Interpolated texture coordinate at barycentric `(b1, b2)`. Meshes without UVs use the
+    /// barycentrics themselves.
+    pub fn uv(&self, triangle: usize, b1: f32, b2: f32) -> (f32, f32) {
+        if self.uvs.is_empty() {
+            return (b1, b2);
+        }
+
+        let [i0, i1, i2] = self.indices[triangle];
+        let b0 = 1.0 - b1 - b2;
+        let (u0, v0) = self.uvs[i0 as usize];
+        let (u1, v1) = self.uvs[i1 as usize];
+        let (u2, v2) = self.uvs[i2 as usize];
+        (b0 * u0 + b1 * u1 + b2 * u2, b0 * v0 + b1 * v1 + b2 * v2)
+    }
+}
+
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    index: usize,
+}
+
+impl Hitable for MeshTriangle {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let [v0, v1, v2] = self.mesh.vertices(self.index);
+        let (t, b1, b2) = intersect_triangle(ray, v0, v1, v2, t_min, t_max)?;
+        let geometric_normal = (v1 - v0).cross(v2 - v0).normalized();
+        let shading_normal = self.mesh.shading_normal(self.index, b1, b2);
+        let uv = self.mesh.uv(self.index, b1, b2);
+        Some(HitRecord::new(
+            ray,
+            t,
+            geometric_normal,
+            shading_normal,
+            uv,
+            self.mesh.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [v0, v1, v2] = self.mesh.vertices(self.index);
+        Some(Aabb::empty().grow(v0).grow(v1).grow(v2))
+    }
+}
+
+/// Indexed triangle mesh with its own bounding volume hierarchy.
+pub struct TriangleMesh {
+    pub data: Arc<MeshData>,
+    bvh: Box<dyn Hitable>,
+}
+
+impl TriangleMesh {
+    /// `normals` and `uvs` must be empty or have one entry per position.
+    pub fn new(
+        positions: Vec<Vec3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f32, f32)>,
+        indices: Vec<[u32; 3]>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(!indices.is_empty(), "mesh has no triangles");
+        assert!(
+            normals.is_empty() || normals.len() == positions.len(),
+            "mesh normal count does not match positions"
+        );
+        assert!(
+            uvs.is_empty() || uvs.len() == positions.len(),
+            "mesh uv count does not match positions"
+        );
+        assert!(
+            indices
+                .iter()
+                .flatten()
+                .all(|&i| (i as usize) < positions.len()),
+            "mesh index out of range"
+        );
+
+        let data = Arc::new(MeshData {
+            positions,
+            normals,
+            uvs,
+            indices,
+            material,
+        });
+
+        let triangles = (0..data.indices.len())
+            .map(|index| {
+                Box::new(MeshTriangle {
+                    mesh: data.clone(),
+                    index,
+                }) as Box<dyn Hitable>
+            })
+            .collect();
+
+        TriangleMesh {
+            data,
+            bvh: BvhNode::build(triangles),
+        }
+    }
+}
+
+impl Hitable for TriangleMesh {
+    fn hit_test(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        self.bvh.hit_test(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scene::lambertian::Lambertian;
+
+    fn quad() -> TriangleMesh {
+        let positions = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let normals = vec![
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0).normalized(),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        let uvs = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let indices = vec![[0, 1, 2], [0, 2, 3]];
+        TriangleMesh::new(
+            positions,
+            normals,
+            uvs,
+            indices,
+            Arc::new(Lambertian::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn mesh_hit_test() {
+        let mesh = quad();
+        let hit = Ray::new(Vec3::new(0.3, 0.6, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let miss = Ray::new(Vec3::new(1.3, 0.6, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit_test(&hit, 0.0, f32::MAX).unwrap();
+        assert_eq!(record.t, 1.0);
+        assert!(record.front_face);
+        assert_eq!(record.geometric_normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!((record.uv.0 - 0.3).abs() < 1e-6 && (record.uv.1 - 0.6).abs() < 1e-6);
+        assert!(mesh.hit_test(&miss, 0.0, f32::MAX).is_none());
+
+        let from_behind = Ray::new(Vec3::new(0.3, 0.6, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let record = mesh.hit_test(&from_behind, 0.0, f32::MAX).unwrap();
+        assert!(!record.front_face);
+        assert_eq!(record.geometric_normal, Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            mesh.bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn mesh_interpolates_attributes() {
+        let mesh = quad();
+        assert_eq!(mesh.data.uv(0, 0.5, 0.25), (0.75, 0.25));
+        assert_eq!(
+            mesh.data.shading_normal(1, 0.5, 0.5),
+            Vec3::new(0.0, 0.0, 1.0)
+        );
+
+        let at_vertex = mesh.data.shading_normal(0, 1.0, 0.0);
+        assert!((at_vertex - Vec3::new(1.0, 0.0, 1.0).normalized()).magnitude() < 1e-6);
+    }
+}
@@ -0,0 +1,288 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use vector_math::aabb::Aabb;
+use vector_math::noise::Perlin;
+use vector_math::vec3::Vec3;
+
+/// Densities on a regular 3D grid over the unit cube, with x varying fastest, then y, then z.
+/// Values sit at cell centers and are interpolated trilinearly between them.
+#[derive(Clone, Debug)]
+pub struct VoxelGrid {
+    pub resolution: [usize; 3],
+    data: Vec<f32>,
+    max: f32,
+}
+
+impl VoxelGrid {
+    /// Panics if `data` does not hold one value per cell.
+    pub fn new(resolution: [usize; 3], data: Vec<f32>) -> Self {
+        assert_eq!(
+            data.len(),
+            resolution[0] * resolution[1] * resolution[2],
+            "voxel count mismatch"
+        );
+        let max = data.iter().cloned().fold(0.0, f32::max);
+        VoxelGrid {
+            resolution,
+            data,
+            max,
+        }
+    }
+
+    /// Evaluates `density` at every cell center, given in unit cube coordinates.
+    pub fn from_fn<F: Fn(Vec3) -> f32>(resolution: [usize; 3], density: F) -> Self {
+        let [nx, ny, nz] = resolution;
+        let mut data = Vec::with_capacity(nx * ny * nz);
+        for z in 0..nz {
+            for y in 0..ny {
+                for x in 0..nx {
+                    let center = Vec3::new(
+                        (x as f32 + 0.5) / nx as f32,
+                        (y as f32 + 0.5) / ny as f32,
+                        (z as f32 + 0.5) / nz as f32,
+                    );
+                    data.push(density(center));
+                }
+            }
+        }
+        VoxelGrid::new(resolution, data)
+    }
+
+    /// A billowing cloud: fractal noise, cut off below `1 - coverage` and faded out toward the
+    /// sphere inscribed in the grid so that it never touches the sides.
+    pub fn cloud(
+        resolution: [usize; 3],
+        seed: u64,
+        frequency: f32,
+        octaves: u32,
+        coverage: f32,
+    ) -> Self {
+        let noise = Perlin::new(seed);
+        let threshold = 1.0 - coverage.clamp(0.0, 1.0);
+        VoxelGrid::from_fn(resolution, |p| {
+            let radius = 2.0 * (p - Vec3::new(0.5, 0.5, 0.5)).magnitude();
+            let falloff = (1.0 - radius * radius).max(0.0);
+            let value = (0.5 + 0.5 * noise.fbm(&(frequency * p), octaves, 2.0, 0.5)) * falloff;
+            if threshold < 1.0 {
+                ((value - threshold) / (1.0 - threshold)).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Loads a Mitsuba `.vol` grid of one float32 or uint8 channel, with its stored bounds.
+    pub fn load_vol(path: &Path) -> Result<(Self, Aabb), io::Error> {
+        read_vol(BufReader::new(File::open(path)?))
+    }
+
+    /// Loads headerless 8-bit densities, scaled to `[0, 1]`, as written by most volume datasets.
+    pub fn load_raw(path: &Path, resolution: [usize; 3]) -> Result<Self, io::Error> {
+        let expected = voxel_count(resolution)
+            .ok_or_else(|| invalid_data("raw volume resolution is too large"))?;
+        let mut bytes = vec![];
+        File::open(path)?.read_to_end(&mut bytes)?;
+        if bytes.len() != expected {
+            return Err(invalid_data(format!(
+                "raw volume has {} bytes, but the resolution needs {}",
+                bytes.len(),
+                expected
+            )));
+        }
+        let data = bytes.iter().map(|&b| f32::from(b) / 255.0).collect();
+        Ok(VoxelGrid::new(resolution, data))
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.data[(z * self.resolution[1] + y) * self.resolution[0] + x]
+    }
+
+    /// Interpolated density at `p` in the unit cube, constant beyond the outermost cell centers.
+    pub fn sample(&self, p: &Vec3) -> f32 {
+        // Cell index and interpolation weight along one axis.
+        let split = |axis: usize| {
+            let n = self.resolution[axis];
+            let x = (p[axis] * n as f32 - 0.5).clamp(0.0, (n - 1) as f32);
+            let i = (x as usize).min(n.saturating_sub(2));
+            (i, (i + 1).min(n - 1), x - i as f32)
+        };
+        let (x0, x1, fx) = split(0);
+        let (y0, y1, fy) = split(1);
+        let (z0, z1, fz) = split(2);
+
+        let lerp = |a: f32, b: f32, t: f32| a + t * (b - a);
+        let along_x = |y, z| lerp(self.value(x0, y, z), self.value(x1, y, z), fx);
+        let along_y = |z| lerp(along_x(y0, z), along_x(y1, z), fy);
+        lerp(along_y(z0), along_y(z1), fz)
+    }
+}
+
+fn invalid_data<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn voxel_count(resolution: [usize; 3]) -> Option<usize> {
+    resolution[0]
+        .checked_mul(resolution[1])?
+        .checked_mul(resolution[2])
+}
+
+/// Reads exactly `len` bytes, growing the buffer only as data arrives so that a corrupt header
+/// cannot ask for more memory than the file holds.
+fn read_bytes<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>, io::Error> {
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "truncated .vol data",
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Reads a Mitsuba grid volume: `VOL`, version 3, then little-endian encoding, resolution, channel
+/// count and bounding box, followed by the values with x varying fastest.
+pub fn read_vol<R: Read>(mut reader: R) -> Result<(VoxelGrid, Aabb), io::Error> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic[..3] != b"VOL" || magic[3] != 3 {
+        return Err(invalid_data("not a version 3 .vol file"));
+    }
+
+    let mut header = [0u8; 44];
+    reader.read_exact(&mut header)?;
+    let word = |i: usize| {
+        [
+            header[4 * i],
+            header[4 * i + 1],
+            header[4 * i + 2],
+            header[4 * i + 3],
+        ]
+    };
+    let int = |i: usize| i32::from_le_bytes(word(i));
+    let float = |i: usize| f32::from_le_bytes(word(i));
+
+    let encoding = int(0);
+    let (nx, ny, nz) = (int(1), int(2), int(3));
+    if nx <= 0 || ny <= 0 || nz <= 0 {
+        return Err(invalid_data("invalid .vol resolution"));
+    }
+    if int(4) != 1 {
+        return Err(invalid_data(format!(
+            "{} channels; density grids need 1",
+            int(4)
+        )));
+    }
+    let bounds = Aabb::new(
+        Vec3::new(float(5), float(6), float(7)),
+        Vec3::new(float(8), float(9), float(10)),
+    );
+
+    let resolution = [nx as usize, ny as usize, nz as usize];
+    let count =
+        voxel_count(resolution).ok_or_else(|| invalid_data(".vol resolution is too large"))?;
+    let data: Vec<f32> = match encoding {
+        1 => {
+            let len = count
+                .checked_mul(4)
+                .ok_or_else(|| invalid_data(".vol resolution is too large"))?;
+            read_bytes(&mut reader, len)?
+                .chunks(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect()
+        }
+        3 => read_bytes(&mut reader, count)?
+            .iter()
+            .map(|&b| f32::from(b) / 255.0)
+            .collect(),
+        other => return Err(invalid_data(format!("unsupported .vol encoding {}", other))),
+    };
+    // Tracking needs densities in [0, majorant]; a negative one would brighten shadow rays.
+    if let Some(value) = data.iter().find(|v| !(v.is_finite() && **v >= 0.0)) {
+        return Err(invalid_data(format!(
+            ".vol density {} is not a non-negative number",
+            value
+        )));
+    }
+    Ok((VoxelGrid::new(resolution, data), bounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voxel_grid_interpolates_between_cell_centers() {
+        // Density equal to x, sampled at the centers 0.25 and 0.75.
+        let grid = VoxelGrid::from_fn([2, 1, 1], |p| p.x);
+        assert_eq!(grid.max(), 0.75);
+        assert_eq!(grid.sample(&Vec3::new(0.5, 0.5, 0.5)), 0.5);
+        assert_eq!(grid.sample(&Vec3::new(0.625, 0.1, 0.9)), 0.625);
+        // Constant past the outer centers.
+        assert_eq!(grid.sample(&Vec3::new(0.0, 0.5, 0.5)), 0.25);
+        assert_eq!(grid.sample(&Vec3::new(1.0, 0.5, 0.5)), 0.75);
+
+        let grid = VoxelGrid::new([2, 2, 2], vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(grid.sample(&Vec3::new(0.3, 0.6, 0.5)), 0.5);
+    }
+
+    #[test]
+    fn cloud_fades_out_at_the_edges() {
+        let grid = VoxelGrid::cloud([16, 16, 16], 3, 4.0, 4, 0.8);
+        assert!(grid.max() > 0.0 && grid.max() <= 1.0);
+        assert_eq!(grid.sample(&Vec3::new(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(grid.sample(&Vec3::new(1.0, 0.5, 0.5)), 0.0);
+    }
+
+    #[test]
+    fn reads_vol_files() {
+        let mut bytes = b"VOL\x03".to_vec();
+        for value in &[1, 2, 1, 1, 1] {
+            bytes.extend_from_slice(&i32::to_le_bytes(*value));
+        }
+        for value in &[-1.0f32, 0.0, 0.0, 1.0, 1.0, 2.0, 0.25, 0.5] {
+            bytes.extend_from_slice(&f32::to_le_bytes(*value));
+        }
+        let (grid, bounds) = read_vol(&bytes[..]).unwrap();
+        assert_eq!(grid.resolution, [2, 1, 1]);
+        assert_eq!(grid.max(), 0.5);
+        assert_eq!(
+            bounds,
+            Aabb::new(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 2.0))
+        );
+
+        assert!(read_vol(&bytes[..bytes.len() - 1]).is_err());
+        // A huge resolution fails on the missing data rather than allocating for it.
+        let mut huge = bytes.clone();
+        for axis in 1..4 {
+            huge[4 + 4 * axis..8 + 4 * axis].copy_from_slice(&i32::to_le_bytes(i32::MAX));
+        }
+        assert_eq!(
+            read_vol(&huge[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        huge[12..20].copy_from_slice(&bytes[12..20]);
+        assert_eq!(
+            read_vol(&huge[..]).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        let len = bytes.len();
+        for bad in &[-0.5f32, f32::NAN, f32::INFINITY] {
+            let mut invalid = bytes.clone();
+            invalid[len - 4..].copy_from_slice(&bad.to_le_bytes());
+            assert_eq!(
+                read_vol(&invalid[..]).unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+        bytes[3] = 2;
+        assert!(read_vol(&bytes[..]).is_err());
+    }
+}
@@ -0,0 +1,420 @@
+use super::SceneError;
+
+/// A value on the right-hand side of `key = value`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// The literal text, so integers and floats can each be parsed at full precision.
+    Number(String),
+    String(String),
+    Bool(bool),
+    Array(Vec<Value>),
+    /// An inline `{ key = value, ... }` table.
+    Table(Vec<Entry>),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Bool(_) => "a boolean",
+            Value::Array(_) => "an array",
+            Value::Table(_) => "a table",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub value: Value,
+    pub line: usize,
+}
+
+/// A `[name]` or `[[name]]` section with the entries that follow it. Entries before the first
+/// header belong to a table with an empty name.
+#[derive(Clone, Debug)]
+pub struct Table {
+    pub name: String,
+    pub is_array: bool,
+    pub line: usize,
+    pub entries: Vec<Entry>,
+}
+
+/// Parses the TOML subset described in the module documentation.
+pub fn parse_document(source: &str, file: &str) -> Result<Vec<Table>, SceneError> {
+    let mut tables = vec![Table {
+        name: String::new(),
+        is_array: false,
+        line: 1,
+        entries: vec![],
+    }];
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line = index + 1;
+        let error = |message: String| SceneError::Parse {
+            file: file.to_string(),
+            line,
+            message,
+        };
+
+        let mut cursor = Cursor::new(raw_line, line);
+        cursor.skip_whitespace();
+        if cursor.at_end_of_statement() {
+            continue;
+        }
+
+        if cursor.eat('[') {
+            let is_array = cursor.eat('[');
+            let name = cursor.take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if name.is_empty() {
+                return Err(error("expected a table name".to_string()));
+            }
+            if !cursor.eat(']') || (is_array && !cursor.eat(']')) {
+                return Err(error(format!("unterminated table header '{}'", name)));
+            }
+            cursor.skip_whitespace();
+            if !cursor.at_end_of_statement() {
+                return Err(error("unexpected text after table header".to_string()));
+            }
+
+            let name = name.to_string();
+            if let Some(previous) = tables.iter().find(|t| t.name == name) {
+                if !is_array || !previous.is_array {
+                    return Err(error(format!(
+                        "table '{}' is already defined on line {}",
+                        name, previous.line
+                    )));
+                }
+            }
+
+            tables.push(Table {
+                name,
+                is_array,
+                line,
+                entries: vec![],
+            });
+            continue;
+        }
+
+        let entry = cursor.entry().map_err(error)?;
+        cursor.skip_whitespace();
+        if !cursor.at_end_of_statement() {
+            return Err(error(format!(
+                "unexpected text after value: '{}'",
+                cursor.rest().trim()
+            )));
+        }
+
+        let table = tables.last_mut().unwrap();
+        if let Some(previous) = table.entries.iter().find(|e| e.key == entry.key) {
+            return Err(error(format!(
+                "key '{}' is already set on line {}",
+                entry.key, previous.line
+            )));
+        }
+        table.entries.push(entry);
+    }
+
+    Ok(tables)
+}
+
+struct Cursor<'a> {
+    text: &'a str,
+    position: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str, line: usize) -> Self {
+        Cursor {
+            text,
+            position: 0,
+            line,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn at_end_of_statement(&self) -> bool {
+        matches!(self.peek(), None | Some('#'))
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        let rest = self.rest();
+        let length = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.position += length;
+        &rest[..length]
+    }
+
+    fn entry(&mut self) -> Result<Entry, String> {
+        let key = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if key.is_empty() {
+            return Err(format!("expected a key, found '{}'", self.rest().trim()));
+        }
+        self.skip_whitespace();
+        if !self.eat('=') {
+            return Err(format!("expected '=' after key '{}'", key));
+        }
+
+        Ok(Entry {
+            key: key.to_string(),
+            value: self.value()?,
+            line: self.line,
+        })
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => self.string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(_) => {
+                let token = self.take_while(|c| c.is_ascii_alphanumeric() || "+-._".contains(c));
+                match token {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "" => Err(format!("expected a value, found '{}'", self.rest().trim())),
+                    _ => {
+                        let number = token.replace('_', "");
+                        match number.parse::<f64>() {
+                            Ok(_) => Ok(Value::Number(number)),
+                            Err(_) => Err(format!("invalid value '{}'", token)),
+                        }
+                    }
+                }
+            }
+            None => Err("expected a value".to_string()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.eat('"');
+        let mut result = String::new();
+        let mut chars = self.rest().char_indices();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += offset + 1;
+                    return Ok(result);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => result.push('\n'),
+                    Some((_, 't')) => result.push('\t'),
+                    Some((_, '"')) => result.push('"'),
+                    Some((_, '\\')) => result.push('\\'),
+                    Some((_, other)) => return Err(format!("unknown escape '\\{}'", other)),
+                    None => break,
+                },
+                _ => result.push(c),
+            }
+        }
+
+        Err("unterminated string".to_string())
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.eat('[');
+        let mut items = vec![];
+
+        loop {
+            self.skip_whitespace();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            if self.peek().is_none() {
+                return Err("unterminated array; arrays must fit on one line".to_string());
+            }
+
+            items.push(self.value()?);
+            self.skip_whitespace();
+            if !self.eat(',') {
+                if self.eat(']') {
+                    return Ok(Value::Array(items));
+                }
+                if self.peek().is_none() {
+                    return Err("unterminated array; arrays must fit on one line".to_string());
+                }
+                return Err(format!(
+                    "expected ',' or ']' in array, found '{}'",
+                    self.rest().trim()
+                ));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, String> {
+        self.eat('{');
+        let mut entries: Vec<Entry> = vec![];
+
+        loop {
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Ok(Value::Table(entries));
+            }
+            if self.peek().is_none() {
+                return Err(
+                    "unterminated inline table; inline tables must fit on one line".to_string(),
+                );
+            }
+
+            let entry = self.entry()?;
+            if entries.iter().any(|e| e.key == entry.key) {
+                return Err(format!("key '{}' is set twice in inline table", entry.key));
+            }
+            entries.push(entry);
+
+            self.skip_whitespace();
+            if !self.eat(',') {
+                if self.eat('}') {
+                    return Ok(Value::Table(entries));
+                }
+                if self.peek().is_none() {
+                    return Err(
+                        "unterminated inline table; inline tables must fit on one line".to_string(),
+                    );
+                }
+                return Err(format!(
+                    "expected ',' or '}}' in inline table, found '{}'",
+                    self.rest().trim()
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(text: &str) -> Value {
+        Value::Number(text.to_string())
+    }
+
+    fn error_of(source: &str) -> (usize, String) {
+        match parse_document(source, "test.toml") {
+            Err(SceneError::Parse { line, message, .. }) => (line, message),
+            Err(err) => panic!("unexpected error {}", err),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn parses_tables_and_values() {
+        let source = r#"
+            title = "two \"spheres\"" # trailing comment
+            [camera]
+            origin = [3, 3.5, -1e-1]
+            [[object]]
+            visible = true
+            [[object]]
+            nested = [[1, 2], []]
+            material = { type = "metal", roughness = 1_000 }
+        "#;
+        let tables = parse_document(source, "test.toml").unwrap();
+        let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["", "camera", "object", "object"]);
+
+        assert_eq!(
+            tables[0].entries[0].value,
+            Value::String("two \"spheres\"".to_string())
+        );
+        assert_eq!(tables[1].line, 3);
+        assert_eq!(tables[1].entries[0].line, 4);
+        assert_eq!(
+            tables[1].entries[0].value,
+            Value::Array(vec![number("3"), number("3.5"), number("-1e-1")])
+        );
+        assert_eq!(tables[2].entries[0].value, Value::Bool(true));
+        assert_eq!(
+            tables[3].entries[0].value,
+            Value::Array(vec![
+                Value::Array(vec![number("1"), number("2")]),
+                Value::Array(vec![])
+            ])
+        );
+        assert_eq!(
+            tables[3].entries[1].value,
+            Value::Table(vec![
+                Entry {
+                    key: "type".to_string(),
+                    value: Value::String("metal".to_string()),
+                    line: 9,
+                },
+                Entry {
+                    key: "roughness".to_string(),
+                    value: number("1000"),
+                    line: 9,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_line_numbers() {
+        assert_eq!(
+            error_of("a = 1\nb = \n"),
+            (2, "expected a value".to_string())
+        );
+        assert_eq!(
+            error_of("a = 1\n\nb = [1, 2\n"),
+            (
+                3,
+                "unterminated array; arrays must fit on one line".to_string()
+            )
+        );
+        assert_eq!(
+            error_of("[camera\n"),
+            (1, "unterminated table header 'camera'".to_string())
+        );
+        assert_eq!(
+            error_of("[a]\n[a]\n"),
+            (2, "table 'a' is already defined on line 1".to_string())
+        );
+        assert_eq!(
+            error_of("a = 1\na = 2\n"),
+            (2, "key 'a' is already set on line 1".to_string())
+        );
+        assert_eq!(
+            error_of("a = \"open\n"),
+            (1, "unterminated string".to_string())
+        );
+        assert_eq!(
+            error_of("a = 1 2\n"),
+            (1, "unexpected text after value: '2'".to_string())
+        );
+        assert_eq!(
+            error_of("a = 1.2.3\n"),
+            (1, "invalid value '1.2.3'".to_string())
+        );
+        assert_eq!(
+            error_of("= 4\n"),
+            (1, "expected a key, found '= 4'".to_string())
+        );
+        assert_eq!(
+            error_of("a = { b = 1, b = 2 }\n"),
+            (1, "key 'b' is set twice in inline table".to_string())
+        );
+    }
+}
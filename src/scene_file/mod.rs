@@ -0,0 +1,1818 @@
+//! Text scene descriptions, written in a small subset of TOML.
+//!
+//! A scene file has these sections, all optional except `[camera]`:
+//!
+//! ```toml
+//! [render]                  # defaults shown
+//! width = 200
+//! height = 100
+//! samples = 100             # samples per pixel
+//! max_depth = 50
+//! seed = 0
+//!
+//! [camera]
+//! origin = [3, 3, 3]
+//! look_at = [0, 0, 0]
+//! up = [0, 1, 0]            # default
+//! fov = 20                  # vertical, in degrees
+//! aperture = 0.0            # default: pinhole
+//! focus_distance = 5.2      # default: distance from origin to look_at
+//! shutter_open = 0.0        # default; rays are fired at random times between
+//! shutter_close = 1.0       # these two (default 0), blurring moving objects
+//!
+//! [background]
+//! type = "gradient"         # default: white straight down to light blue straight up
+//! nadir = [1, 1, 1]
+//! zenith = [0.5, 0.7, 1]
+//!
+//! [[texture]]               # named textures, for any material color
+//! name = "tiles"
+//! type = "checker"
+//! even = [0.9, 0.9, 0.9]    # a color, texture name or inline texture table
+//! odd = "wood"              # textures may use textures defined above them
+//! size = 0.5                # default 1
+//!
+//! [[material]]              # named materials, shared by any number of objects and
+//!                           # numbered from 1 for ID passes, then inline ones
+//! name = "glass"
+//! type = "dielectric"
+//! refractive_index = 1.5
+//!
+//! [[object]]
+//! type = "sphere"
+//! center = [0, 1, 0]
+//! radius = 1
+//! material = "glass"        # a material name, or an inline table such as
+//!                           # { type = "lambertian", albedo = [0.8, 0.3, 0.3] }
+//! scale = [1, 2, 1]         # optional transform of any object: scaled, then
+//! rotate = [0, 45, 0]       # rotated about x, y and z in degrees,
+//! translate = [0, 0, -1]    # then moved
+//! ```
+//!
+//! | Kind       | `type`          | Keys                                           |
+//! |------------|-----------------|------------------------------------------------|
+//! | texture    | `constant`      | `color`                                        |
+//! | texture    | `checker`       | `even`, `odd`, `size` (default 1)              |
+//! | texture    | `image`         | `file` (PNG, JPEG, ...), `wrap` (`repeat`, `mirror`, `clamp`) |
+//! | texture    | `marble`        | `base`, `vein`, `scale` (1), `distortion` (10), `octaves` (7) |
+//! | texture    | `wood`        | `light`, `dark`, `scale` (4), `distortion` (0.6), `octaves` (4) |
+//! | texture    | `clouds`        | `sky`, `cloud`, `scale` (1), `coverage` (0.5), `octaves` (6) |
+//! | material   | `lambertian`    | `albedo`                                       |
+//! | material   | `metal`         | `albedo`, `roughness` (default 0)              |
+//! | material   | `dielectric`    | `refractive_index`                             |
+//! | material   | `conductor`     | `preset` or `eta` and `k`, `roughness` (0)     |
+//! | material   | `diffuse_light` | `emit`                                         |
+//! | material   | `isotropic`     | `albedo` (for media: scatters evenly in all directions) |
+//! | material   | `henyey_greenstein` | `albedo`, `anisotropy` (-1 back to 1 forward, default 0) |
+//! | object     | `sphere`        | `center`, `radius`, `material`                 |
+//! | object     | `moving_sphere` | `centers`, `times` (evenly 0 to 1), `radius`, `material` |
+//! | object     | `triangle`      | `vertices` (three points), `material`          |
+//! | object     | `rect`          | `min`, `max` (corners, equal along one axis), `material` |
+//! | object     | `box`           | `min`, `max`, `material`                       |
+//! | object     | `plane`         | `point`, `normal`, `material` (infinite)       |
+//! | object     | `disk`          | `center`, `normal`, `radius`, `material`       |
+//! | object     | `cylinder`      | `base`, `top`, `radius`, `capped` (default true), `material` |
+//! | object     | `cone`          | `base`, `apex`, `radius`, `capped` (default true), `material` |
+//! | object     | `torus`         | `center`, `axis`, `major_radius`, `minor_radius`, `material` |
+//! | object     | `constant_medium` | `boundary` (inline object), `density`, `material` |
+//! | object     | `grid_medium`   | `min`, `max`, `density` (1), `material`, `file` or noise keys |
+//! | object     | `mesh`          | `file` (OBJ), `material` (for faces with no MTL material) |
+//! | background | `gradient`      | `nadir`, `zenith`                              |
+//! | background | `solid`         | `color`                                        |
+//! | background | `black`         |                                                |
+//! | background | `environment`   | `file` (`.hdr` or `.pfm`), `intensity` (default 1) |
+//!
+//! Material colors (`albedo`, `emit`) and checker colors are either `[r, g, b]` or a texture. Noise
+//! texture colors are `[r, g, b]` with natural-looking defaults, and the numbers in parentheses are
+//! defaults; noise textures also take a `seed` (0), which always gives the same pattern.
+//! Image textures wrap with `repeat` unless `wrap` says otherwise.
+//! A `grid_medium` reads its densities from a Mitsuba `.vol` `file` (whose bounds are the default
+//! `min` and `max`) or a headerless 8-bit one with a `resolution` such as `[64, 64, 64]`. Without a
+//! file it is a noise cloud with `resolution` ([64, 64, 64]), `frequency` (4), `coverage` (0.5),
+//! `octaves` (5) and `seed` (0).
+//! A `constant_medium` boundary must be convex and needs no `material`.
+//! A `conductor` is a GGX microfacet metal whose complex refractive index `eta + ik` is given per
+//! RGB channel or by a `preset` of `gold`, `copper` or `aluminium`; `roughness_u` and `roughness_v`
+//! override `roughness` along and across the tangent, which circles the y axis.
+//! A torus's `axis` defaults to [0, 1, 0], and a cone's `radius` is that of its base.
+//! Relative file paths are resolved against the scene file's directory. Values are numbers,
+//! `"strings"`, `true`/`false`, `[arrays]` and `{ inline = "tables" }`; arrays and inline tables
+//! must fit on one line.
+//! `#` starts a comment. Unknown sections, keys and material names are errors.
+
+mod document;
+
+use self::document::{parse_document, Entry, Table, Value};
+use scene::background::{Background, BlackBackground, GradientBackground, SolidBackground};
+use scene::camera::Camera;
+use scene::conductor::Conductor;
+use scene::cone::Cone;
+use scene::constant_medium::ConstantMedium;
+use scene::cuboid::Cuboid;
+use scene::cylinder::Cylinder;
+use scene::dielectric::Dielectric;
+use scene::diffuse_light::DiffuseLight;
+use scene::disk::Disk;
+use scene::environment_map::EnvironmentMap;
+use scene::grid_medium::GridMedium;
+use scene::hitable::Hitable;
+use scene::instance::Instance;
+use scene::lambertian::Lambertian;
+use scene::material::Material;
+use scene::material_id::MaterialIds;
+use scene::metal::Metal;
+use scene::moving_sphere::MovingSphere;
+use scene::noise_texture::{CloudsTexture, MarbleTexture, WoodTexture};
+use scene::obj_loader::{load_obj, ObjError};
+use scene::phase_function::{HenyeyGreenstein, Isotropic};
+use scene::plane::Plane;
+use scene::rect::{AxisRect, RectPlane};
+use scene::sphere::Sphere;
+use scene::texture::{CheckerTexture, ConstantTexture, ImageTexture, Texture, WrapMode};
+use scene::torus::Torus;
+use scene::triangle::Triangle;
+use scene::voxel_grid::VoxelGrid;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use vector_math::aabb::Aabb;
+use vector_math::matrix4::Matrix4;
+use vector_math::vec3::Vec3;
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(PathBuf, io::Error),
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    Obj(ObjError),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            SceneError::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+            SceneError::Obj(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    pub width: u32,
+    pub height: u32,
+    pub sample_count: u32,
+    pub max_depth: u32,
+    pub seed: u64,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            width: 200,
+            height: 100,
+            sample_count: 100,
+            max_depth: 50,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraSettings {
+    pub origin: Vec3,
+    pub look_at: Vec3,
+    pub up: Vec3,
+    pub fov: f32,
+    pub aperture: f32,
+    pub focus_distance: f32,
+    pub shutter_open: f32,
+    pub shutter_close: f32,
+}
+
+impl CameraSettings {
+    pub fn build(&self, aspect: f32) -> Camera {
+        Camera::new(
+            self.origin,
+            self.look_at,
+            self.up,
+            self.fov,
+            aspect,
+            self.aperture,
+            self.focus_distance,
+        )
+        .with_shutter(self.shutter_open, self.shutter_close)
+    }
+}
+
+pub struct SceneDescription {
+    pub render: RenderOptions,
+    pub camera: CameraSettings,
+    pub background: Box<dyn Background>,
+    pub objects: Vec<Box<dyn Hitable>>,
+}
+
+pub fn load_scene(path: &Path) -> Result<SceneDescription, SceneError> {
+    let source = fs::read_to_string(path).map_err(|err| SceneError::Io(path.to_path_buf(), err))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_scene(&source, &path.display().to_string(), base_dir)
+}
+
+/// Parses a scene; `file` names the source in errors and `base_dir` resolves relative paths.
+pub fn parse_scene(
+    source: &str,
+    file: &str,
+    base_dir: &Path,
+) -> Result<SceneDescription, SceneError> {
+    let tables = parse_document(source, file)?;
+    let context = Context {
+        file,
+        base_dir,
+        material_ids: RefCell::new(MaterialIds::default()),
+    };
+
+    let mut render = RenderOptions::default();
+    let mut camera = None;
+    let mut background: Box<dyn Background> = Box::new(GradientBackground::sky());
+    let mut library = Library {
+        textures: HashMap::new(),
+        materials: HashMap::new(),
+    };
+
+    // Textures and materials first, so objects may refer to materials defined further down.
+    for table in tables.iter().filter(|t| t.name == "texture") {
+        let mut reader = context.table(table);
+        let name: String = reader.required("name")?;
+        let texture = reader.texture(&library)?;
+        reader.finish()?;
+
+        if let Some((line, _)) = library.textures.get(&name) {
+            return Err(context.error(
+                table.line,
+                format!("texture '{}' is already defined on line {}", name, line),
+            ));
+        }
+        library.textures.insert(name, (table.line, texture));
+    }
+
+    for table in tables.iter().filter(|t| t.name == "material") {
+        let mut reader = context.table(table);
+        let name: String = reader.required("name")?;
+        let material = reader.material(&library)?;
+        reader.finish()?;
+
+        if let Some((line, _)) = library.materials.get(&name) {
+            return Err(context.error(
+                table.line,
+                format!("material '{}' is already defined on line {}", name, line),
+            ));
+        }
+        library.materials.insert(name, (table.line, material));
+    }
+
+    let mut objects: Vec<Box<dyn Hitable>> = vec![];
+    for table in &tables {
+        let mut reader = context.table(table);
+        match table.name.as_str() {
+            "" => {}
+            "render" => {
+                render.width = reader.optional("width")?.unwrap_or(render.width);
+                render.height = reader.optional("height")?.unwrap_or(render.height);
+                render.sample_count = reader.optional("samples")?.unwrap_or(render.sample_count);
+                render.max_depth = reader.optional("max_depth")?.unwrap_or(render.max_depth);
+                render.seed = reader.optional("seed")?.unwrap_or(render.seed);
+            }
+            "camera" => {
+                let origin: Vec3 = reader.required("origin")?;
+                let look_at: Vec3 = reader.required("look_at")?;
+                let fov: f32 = reader.required("fov")?;
+                if fov <= 0.0 || fov >= 180.0 {
+                    let line = reader.entry("fov").map_or(table.line, |e| e.line);
+                    let message = "'fov' must be between 0 and 180 degrees".to_string();
+                    return Err(context.error(line, message));
+                }
+                camera = Some(CameraSettings {
+                    origin,
+                    look_at,
+                    up: reader
+                        .optional("up")?
+                        .unwrap_or_else(|| Vec3::new(0.0, 1.0, 0.0)),
+                    fov,
+                    aperture: reader.optional("aperture")?.unwrap_or(0.0),
+                    focus_distance: reader
+                        .optional("focus_distance")?
+                        .unwrap_or_else(|| (origin - look_at).magnitude()),
+                    shutter_open: reader.optional("shutter_open")?.unwrap_or(0.0),
+                    shutter_close: reader.optional("shutter_close")?.unwrap_or(0.0),
+                });
+            }
+            "background" => background = reader.background()?,
+            "texture" | "material" => continue,
+            "object" => objects.append(&mut reader.object(&library)?),
+            other => return Err(context.error(table.line, format!("unknown section '{}'", other))),
+        }
+        reader.finish()?;
+    }
+
+    let camera =
+        camera.ok_or_else(|| context.error(1, "scene has no [camera] section".to_string()))?;
+    if render.width == 0 || render.height == 0 || render.sample_count == 0 {
+        return Err(context.error(
+            1,
+            "render width, height and samples must be positive".to_string(),
+        ));
+    }
+
+    Ok(SceneDescription {
+        render,
+        camera,
+        background,
+        objects,
+    })
+}
+
+/// Named textures and materials, with the line each was defined on.
+struct Library {
+    textures: HashMap<String, (usize, Arc<dyn Texture>)>,
+    materials: HashMap<String, (usize, Arc<dyn Material>)>,
+}
+
+struct Context<'a> {
+    file: &'a str,
+    base_dir: &'a Path,
+    /// Numbers materials in the order they are read, which is the order they are defined.
+    material_ids: RefCell<MaterialIds>,
+}
+
+impl<'a> Context<'a> {
+    fn error(&self, line: usize, message: String) -> SceneError {
+        SceneError::Parse {
+            file: self.file.to_string(),
+            line,
+            message,
+        }
+    }
+
+    fn table(&'a self, table: &'a Table) -> TableReader<'a> {
+        let description = if table.name.is_empty() {
+            "top level".to_string()
+        } else if table.is_array {
+            format!("[[{}]]", table.name)
+        } else {
+            format!("[{}]", table.name)
+        };
+        TableReader {
+            context: self,
+            description,
+            line: table.line,
+            entries: &table.entries,
+            used: vec![false; table.entries.len()],
+            default_material: None,
+        }
+    }
+}
+
+/// Conversion from a document value; `Err` holds a description of what was expected.
+trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, String>;
+}
+
+fn parse_number<T: FromStr>(value: &Value, expected: &str) -> Result<T, String> {
+    match value {
+        Value::Number(text) => text.parse().map_err(|_| expected.to_string()),
+        _ => Err(expected.to_string()),
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        parse_number(value, "a number")
+    }
+}
+
+impl FromValue for u32 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        parse_number(value, "a non-negative integer")
+    }
+}
+
+impl FromValue for u64 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        parse_number(value, "a non-negative integer")
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Bool(b) => Ok(*b),
+            _ => Err("true or false".to_string()),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            _ => Err("a string".to_string()),
+        }
+    }
+}
+
+impl FromValue for Vec3 {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Array(items) if items.len() == 3 => {
+                let component = |v: &Value| {
+                    f32::from_value(v).map_err(|_| "an array of three numbers".to_string())
+                };
+                Ok(Vec3::new(
+                    component(&items[0])?,
+                    component(&items[1])?,
+                    component(&items[2])?,
+                ))
+            }
+            _ => Err("an array of three numbers".to_string()),
+        }
+    }
+}
+
+impl FromValue for [Vec3; 3] {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let expected = "an array of three points";
+        match value {
+            Value::Array(items) if items.len() == 3 => {
+                let point = |v: &Value| Vec3::from_value(v).map_err(|_| expected.to_string());
+                Ok([point(&items[0])?, point(&items[1])?, point(&items[2])?])
+            }
+            _ => Err(expected.to_string()),
+        }
+    }
+}
+
+impl FromValue for [u32; 3] {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let expected = "an array of three non-negative integers";
+        match value {
+            Value::Array(items) if items.len() == 3 => {
+                let count = |v: &Value| u32::from_value(v).map_err(|_| expected.to_string());
+                Ok([count(&items[0])?, count(&items[1])?, count(&items[2])?])
+            }
+            _ => Err(expected.to_string()),
+        }
+    }
+}
+
+impl FromValue for Vec<f32> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let expected = "an array of numbers";
+        match value {
+            Value::Array(items) => items
+                .iter()
+                .map(|v| f32::from_value(v).map_err(|_| expected.to_string()))
+                .collect(),
+            _ => Err(expected.to_string()),
+        }
+    }
+}
+
+impl FromValue for Vec<Vec3> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let expected = "an array of points";
+        match value {
+            Value::Array(items) => items
+                .iter()
+                .map(|v| Vec3::from_value(v).map_err(|_| expected.to_string()))
+                .collect(),
+            _ => Err(expected.to_string()),
+        }
+    }
+}
+
+struct TableReader<'a> {
+    context: &'a Context<'a>,
+    description: String,
+    line: usize,
+    entries: &'a [Entry],
+    used: Vec<bool>,
+    /// Material for objects that leave out `material`, where one is not needed.
+    default_material: Option<Arc<dyn Material>>,
+}
+
+impl<'a> TableReader<'a> {
+    fn entry(&mut self, key: &str) -> Option<&'a Entry> {
+        let index = self.entries.iter().position(|e| e.key == key)?;
+        self.used[index] = true;
+        Some(&self.entries[index])
+    }
+
+    fn optional<T: FromValue>(&mut self, key: &str) -> Result<Option<T>, SceneError> {
+        match self.entry(key) {
+            Some(entry) => T::from_value(&entry.value).map(Some).map_err(|expected| {
+                self.context.error(
+                    entry.line,
+                    format!(
+                        "'{}' must be {}, found {}",
+                        key,
+                        expected,
+                        entry.value.type_name()
+                    ),
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn required<T: FromValue>(&mut self, key: &str) -> Result<T, SceneError> {
+        match self.optional(key)? {
+            Some(value) => Ok(value),
+            None => Err(self.missing(key)),
+        }
+    }
+
+    fn missing(&self, key: &str) -> SceneError {
+        self.context.error(
+            self.line,
+            format!("{} is missing required key '{}'", self.description, key),
+        )
+    }
+
+    /// Reports the first key that nothing asked for.
+    fn finish(&self) -> Result<(), SceneError> {
+        match self.entries.iter().zip(&self.used).find(|(_, &used)| !used) {
+            Some((entry, _)) => Err(self.context.error(
+                entry.line,
+                format!("unknown key '{}' in {}", entry.key, self.description),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn kind(&mut self) -> Result<(String, usize), SceneError> {
+        let kind = self.required("type")?;
+        let line = self.entry("type").map_or(self.line, |e| e.line);
+        Ok((kind, line))
+    }
+
+    fn path(&mut self, key: &str) -> Result<(PathBuf, usize), SceneError> {
+        let file: String = self.required(key)?;
+        let line = self.entry(key).map_or(self.line, |e| e.line);
+        Ok((self.context.base_dir.join(file), line))
+    }
+
+    /// A reader for an inline table found at `line`.
+    fn inline(&self, description: &str, line: usize, entries: &'a [Entry]) -> TableReader<'a> {
+        TableReader {
+            context: self.context,
+            description: description.to_string(),
+            line,
+            entries,
+            used: vec![false; entries.len()],
+            default_material: None,
+        }
+    }
+
+    fn texture(&mut self, library: &Library) -> Result<Arc<dyn Texture>, SceneError> {
+        let (kind, line) = self.kind()?;
+        let texture: Arc<dyn Texture> = match kind.as_str() {
+            "constant" => {
+                let color: Vec3 = self.required("color")?;
+                Arc::new(ConstantTexture::new(color.x, color.y, color.z))
+            }
+            "checker" => {
+                let even = self.color("even", library)?;
+                let odd = self.color("odd", library)?;
+                Arc::new(CheckerTexture::new(
+                    even,
+                    odd,
+                    self.optional("size")?.unwrap_or(1.0),
+                ))
+            }
+            "image" => {
+                let (path, file_line) = self.path("file")?;
+                let wrap = match self.optional::<String>("wrap")? {
+                    None => WrapMode::Repeat,
+                    Some(name) => WrapMode::from_name(&name).ok_or_else(|| {
+                        let line = self.entry("wrap").map_or(self.line, |e| e.line);
+                        self.context
+                            .error(line, format!("unknown wrap mode '{}'", name))
+                    })?,
+                };
+                let texture = ImageTexture::load(&path, wrap).map_err(|err| {
+                    self.context.error(
+                        file_line,
+                        format!("cannot load '{}': {}", path.display(), err),
+                    )
+                })?;
+                Arc::new(texture)
+            }
+            "marble" => {
+                let mut marble = MarbleTexture::new(self.optional("seed")?.unwrap_or(0));
+                marble.base = self.optional("base")?.unwrap_or(marble.base);
+                marble.vein = self.optional("vein")?.unwrap_or(marble.vein);
+                marble.scale = self.optional("scale")?.unwrap_or(marble.scale);
+                marble.distortion = self.optional("distortion")?.unwrap_or(marble.distortion);
+                marble.octaves = self.optional("octaves")?.unwrap_or(marble.octaves);
+                Arc::new(marble)
+            }
+            "wood" => {
+                let mut wood = WoodTexture::new(self.optional("seed")?.unwrap_or(0));
+                wood.light = self.optional("light")?.unwrap_or(wood.light);
+                wood.dark = self.optional("dark")?.unwrap_or(wood.dark);
+                wood.scale = self.optional("scale")?.unwrap_or(wood.scale);
+                wood.distortion = self.optional("distortion")?.unwrap_or(wood.distortion);
+                wood.octaves = self.optional("octaves")?.unwrap_or(wood.octaves);
+                Arc::new(wood)
+            }
+            "clouds" => {
+                let mut clouds = CloudsTexture::new(self.optional("seed")?.unwrap_or(0));
+                clouds.sky = self.optional("sky")?.unwrap_or(clouds.sky);
+                clouds.cloud = self.optional("cloud")?.unwrap_or(clouds.cloud);
+                clouds.scale = self.optional("scale")?.unwrap_or(clouds.scale);
+                clouds.coverage = self.optional("coverage")?.unwrap_or(clouds.coverage);
+                clouds.octaves = self.optional("octaves")?.unwrap_or(clouds.octaves);
+                Arc::new(clouds)
+            }
+            other => {
+                return Err(self
+                    .context
+                    .error(line, format!("unknown texture type '{}'", other)))
+            }
+        };
+        Ok(texture)
+    }
+
+    /// A required color: `[r, g, b]`, the name of a `[[texture]]` or an inline texture table.
+    fn color(&mut self, key: &str, library: &Library) -> Result<Arc<dyn Texture>, SceneError> {
+        let entry = self.entry(key).ok_or_else(|| self.missing(key))?;
+        match &entry.value {
+            Value::Array(_) => {
+                let color: Vec3 = self.required(key)?;
+                Ok(Arc::new(ConstantTexture::new(color.x, color.y, color.z)))
+            }
+            Value::String(name) => match library.textures.get(name) {
+                Some((_, texture)) => Ok(texture.clone()),
+                None => Err(self
+                    .context
+                    .error(entry.line, format!("unknown texture '{}'", name))),
+            },
+            Value::Table(entries) => {
+                let mut inline = self.inline("inline texture", entry.line, entries);
+                let texture = inline.texture(library)?;
+                inline.finish()?;
+                Ok(texture)
+            }
+            other => Err(self.context.error(
+                entry.line,
+                format!(
+                    "'{}' must be a color, texture name or inline texture, found {}",
+                    key,
+                    other.type_name()
+                ),
+            )),
+        }
+    }
+
+    fn material(&mut self, library: &Library) -> Result<Arc<dyn Material>, SceneError> {
+        let (kind, line) = self.kind()?;
+        let material: Arc<dyn Material> = match kind.as_str() {
+            "lambertian" => Arc::new(Lambertian::textured(self.color("albedo", library)?)),
+            "metal" => {
+                let albedo = self.color("albedo", library)?;
+                let roughness = self.optional("roughness")?.unwrap_or(0.0);
+                Arc::new(Metal::textured(albedo, roughness))
+            }
+            "dielectric" => {
+                let refractive_index: f32 = self.required("refractive_index")?;
+                if refractive_index <= 0.0 {
+                    let line = self.entry("refractive_index").map_or(self.line, |e| e.line);
+                    let message = "'refractive_index' must be positive".to_string();
+                    return Err(self.context.error(line, message));
+                }
+                Arc::new(Dielectric::new(refractive_index))
+            }
+            "conductor" => {
+                let mut conductor = match self.optional::<String>("preset")? {
+                    Some(name) => Conductor::from_name(&name, 0.0).ok_or_else(|| {
+                        let line = self.entry("preset").map_or(self.line, |e| e.line);
+                        self.context
+                            .error(line, format!("unknown conductor preset '{}'", name))
+                    })?,
+                    None => Conductor::new(self.required("eta")?, self.required("k")?, 0.0),
+                };
+                let roughness = self.optional("roughness")?.unwrap_or(0.0);
+                conductor.roughness_u = self.optional("roughness_u")?.unwrap_or(roughness);
+                conductor.roughness_v = self.optional("roughness_v")?.unwrap_or(roughness);
+                Arc::new(conductor)
+            }
+            "diffuse_light" => Arc::new(DiffuseLight::textured(self.color("emit", library)?)),
+            "isotropic" => Arc::new(Isotropic::textured(self.color("albedo", library)?)),
+            "henyey_greenstein" => {
+                let albedo = self.color("albedo", library)?;
+                let anisotropy: f32 = self.optional("anisotropy")?.unwrap_or(0.0);
+                if anisotropy <= -1.0 || anisotropy >= 1.0 {
+                    let line = self.entry("anisotropy").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'anisotropy' must be between -1 and 1".to_string()));
+                }
+                Arc::new(HenyeyGreenstein::textured(albedo, anisotropy))
+            }
+            other => {
+                return Err(self
+                    .context
+                    .error(line, format!("unknown material type '{}'", other)))
+            }
+        };
+        Ok(self.context.material_ids.borrow_mut().assign(material))
+    }
+
+    /// A `material` key holding either the name of a `[[material]]` or an inline material table.
+    fn material_reference(
+        &mut self,
+        library: &Library,
+    ) -> Result<Option<Arc<dyn Material>>, SceneError> {
+        let entry = match self.entry("material") {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        match &entry.value {
+            Value::String(name) => match library.materials.get(name) {
+                Some((_, material)) => Ok(Some(material.clone())),
+                None => Err(self
+                    .context
+                    .error(entry.line, format!("unknown material '{}'", name))),
+            },
+            Value::Table(entries) => {
+                let mut inline = self.inline("inline material", entry.line, entries);
+                let material = inline.material(library)?;
+                inline.finish()?;
+                Ok(Some(material))
+            }
+            other => Err(self.context.error(
+                entry.line,
+                format!(
+                    "'material' must be a material name or inline table, found {}",
+                    other.type_name()
+                ),
+            )),
+        }
+    }
+
+    /// The `material` of a surface, required unless the table has a default material.
+    fn surface_material(&mut self, library: &Library) -> Result<Arc<dyn Material>, SceneError> {
+        match self.material_reference(library)? {
+            Some(material) => Ok(material),
+            None => self
+                .default_material
+                .clone()
+                .ok_or_else(|| self.missing("material")),
+        }
+    }
+
+    fn object(&mut self, library: &Library) -> Result<Vec<Box<dyn Hitable>>, SceneError> {
+        let (kind, line) = self.kind()?;
+        let objects: Vec<Box<dyn Hitable>> = match kind.as_str() {
+            "sphere" => {
+                let center: Vec3 = self.required("center")?;
+                let radius = self.required("radius")?;
+                let material = self.surface_material(library)?;
+                vec![Box::new(Sphere::new(
+                    center.x, center.y, center.z, radius, material,
+                ))]
+            }
+            "moving_sphere" => {
+                let centers: Vec<Vec3> = self.required("centers")?;
+                let line = self.entry("centers").map_or(self.line, |e| e.line);
+                if centers.is_empty() {
+                    return Err(self.context.error(line, "'centers' is empty".to_string()));
+                }
+                let last = (centers.len() - 1).max(1) as f32;
+                let times: Vec<f32> = match self.optional("times")? {
+                    Some(times) => times,
+                    None => (0..centers.len()).map(|i| i as f32 / last).collect(),
+                };
+                let line = self.entry("times").map_or(line, |e| e.line);
+                if times.len() != centers.len() {
+                    return Err(self
+                        .context
+                        .error(line, "'times' must have one entry per center".to_string()));
+                }
+                if times.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    return Err(self
+                        .context
+                        .error(line, "'times' must be increasing".to_string()));
+                }
+                let radius = self.required("radius")?;
+                let material = self.surface_material(library)?;
+                let keyframes = times.into_iter().zip(centers).collect();
+                vec![Box::new(MovingSphere::keyframed(
+                    keyframes, radius, material,
+                ))]
+            }
+            "triangle" => {
+                let [v0, v1, v2]: [Vec3; 3] = self.required("vertices")?;
+                let material = self.surface_material(library)?;
+                vec![Box::new(Triangle::new(v0, v1, v2, material))]
+            }
+            "rect" => {
+                let min: Vec3 = self.required("min")?;
+                let max: Vec3 = self.required("max")?;
+                let plane = match (min.x == max.x, min.y == max.y, min.z == max.z) {
+                    (false, false, true) => RectPlane::Xy,
+                    (true, false, false) => RectPlane::Yz,
+                    (false, true, false) => RectPlane::Xz,
+                    _ => {
+                        let line = self.entry("max").map_or(self.line, |e| e.line);
+                        let message =
+                            "'min' and 'max' must differ along exactly two axes".to_string();
+                        return Err(self.context.error(line, message));
+                    }
+                };
+                let material = self.surface_material(library)?;
+                let rect = match plane {
+                    RectPlane::Xy => {
+                        AxisRect::new(plane, min.x, max.x, min.y, max.y, min.z, material)
+                    }
+                    RectPlane::Yz => {
+                        AxisRect::new(plane, min.y, max.y, min.z, max.z, min.x, material)
+                    }
+                    RectPlane::Xz => {
+                        AxisRect::new(plane, min.x, max.x, min.z, max.z, min.y, material)
+                    }
+                };
+                vec![Box::new(rect)]
+            }
+            "box" => {
+                let min: Vec3 = self.required("min")?;
+                let max: Vec3 = self.required("max")?;
+                let material = self.surface_material(library)?;
+                vec![Box::new(Cuboid::new(min, max, material))]
+            }
+            "plane" => {
+                let point: Vec3 = self.required("point")?;
+                let normal: Vec3 = self.required("normal")?;
+                if normal == Vec3::zero() {
+                    let line = self.entry("normal").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'normal' must not be zero".to_string()));
+                }
+                let material = self.surface_material(library)?;
+                vec![Box::new(Plane::new(point, normal, material))]
+            }
+            "disk" => {
+                let center: Vec3 = self.required("center")?;
+                let normal: Vec3 = self.required("normal")?;
+                if normal == Vec3::zero() {
+                    let line = self.entry("normal").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'normal' must not be zero".to_string()));
+                }
+                let radius: f32 = self.required("radius")?;
+                if radius <= 0.0 {
+                    let line = self.entry("radius").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'radius' must be positive".to_string()));
+                }
+                let material = self.surface_material(library)?;
+                vec![Box::new(Disk::new(center, normal, radius, material))]
+            }
+            "cylinder" | "cone" => {
+                let end_key = if kind == "cylinder" { "top" } else { "apex" };
+                let base: Vec3 = self.required("base")?;
+                let end: Vec3 = self.required(end_key)?;
+                if base == end {
+                    let line = self.entry(end_key).map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, format!("'base' and '{}' must differ", end_key)));
+                }
+                let radius: f32 = self.required("radius")?;
+                if radius <= 0.0 {
+                    let line = self.entry("radius").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'radius' must be positive".to_string()));
+                }
+                let capped = self.optional("capped")?.unwrap_or(true);
+                let material = self.surface_material(library)?;
+                if kind == "cylinder" {
+                    vec![Box::new(Cylinder::new(base, end, radius, capped, material))]
+                } else {
+                    vec![Box::new(Cone::new(base, end, radius, capped, material))]
+                }
+            }
+            "torus" => {
+                let center: Vec3 = self.required("center")?;
+                let axis = self
+                    .optional("axis")?
+                    .unwrap_or_else(|| Vec3::new(0.0, 1.0, 0.0));
+                if axis == Vec3::zero() {
+                    let line = self.entry("axis").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'axis' must not be zero".to_string()));
+                }
+                let major_radius: f32 = self.required("major_radius")?;
+                let minor_radius: f32 = self.required("minor_radius")?;
+                for &(key, radius) in &[
+                    ("major_radius", major_radius),
+                    ("minor_radius", minor_radius),
+                ] {
+                    if radius <= 0.0 {
+                        let line = self.entry(key).map_or(self.line, |e| e.line);
+                        return Err(self
+                            .context
+                            .error(line, format!("'{}' must be positive", key)));
+                    }
+                }
+                let material = self.surface_material(library)?;
+                vec![Box::new(Torus::new(
+                    center,
+                    axis,
+                    major_radius,
+                    minor_radius,
+                    material,
+                ))]
+            }
+            "constant_medium" => {
+                let density: f32 = self.required("density")?;
+                if density <= 0.0 {
+                    let line = self.entry("density").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'density' must be positive".to_string()));
+                }
+                let phase_function = self
+                    .material_reference(library)?
+                    .unwrap_or_else(|| Arc::new(Isotropic::new(1.0, 1.0, 1.0)));
+                let entry = self
+                    .entry("boundary")
+                    .ok_or_else(|| self.missing("boundary"))?;
+                let entries = match &entry.value {
+                    Value::Table(entries) => entries,
+                    other => {
+                        let message = format!(
+                            "'boundary' must be an inline object table, found {}",
+                            other.type_name()
+                        );
+                        return Err(self.context.error(entry.line, message));
+                    }
+                };
+                // The boundary's own material is never seen, so it may be left out.
+                let mut inline = self.inline("boundary", entry.line, entries);
+                inline.default_material = Some(phase_function.clone());
+                let mut boundary = inline.object(library)?;
+                inline.finish()?;
+                if boundary.len() != 1 {
+                    let message = "'boundary' must be a single closed shape".to_string();
+                    return Err(self.context.error(entry.line, message));
+                }
+                vec![Box::new(ConstantMedium::new(
+                    boundary.remove(0),
+                    density,
+                    phase_function,
+                ))]
+            }
+            "grid_medium" => {
+                let density: f32 = self.optional("density")?.unwrap_or(1.0);
+                if density <= 0.0 {
+                    let line = self.entry("density").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'density' must be positive".to_string()));
+                }
+                let phase_function = self
+                    .material_reference(library)?
+                    .unwrap_or_else(|| Arc::new(Isotropic::new(1.0, 1.0, 1.0)));
+                let resolution: Option<[u32; 3]> = self.optional("resolution")?;
+                if resolution.is_some_and(|r| r.contains(&0)) {
+                    let line = self.entry("resolution").map_or(self.line, |e| e.line);
+                    return Err(self
+                        .context
+                        .error(line, "'resolution' must be positive".to_string()));
+                }
+                let resolution = resolution.map(|[x, y, z]| [x as usize, y as usize, z as usize]);
+
+                let (grid, file_bounds) = if self.entry("file").is_some() {
+                    let (path, file_line) = self.path("file")?;
+                    let is_vol = path
+                        .extension()
+                        .is_some_and(|e| e.eq_ignore_ascii_case("vol"));
+                    let loaded = if is_vol {
+                        VoxelGrid::load_vol(&path).map(|(grid, bounds)| (grid, Some(bounds)))
+                    } else {
+                        let resolution = resolution.ok_or_else(|| self.missing("resolution"))?;
+                        VoxelGrid::load_raw(&path, resolution).map(|grid| (grid, None))
+                    };
+                    loaded.map_err(|err| {
+                        self.context.error(
+                            file_line,
+                            format!("cannot load '{}': {}", path.display(), err),
+                        )
+                    })?
+                } else {
+                    let grid = VoxelGrid::cloud(
+                        resolution.unwrap_or([64, 64, 64]),
+                        self.optional("seed")?.unwrap_or(0),
+                        self.optional("frequency")?.unwrap_or(4.0),
+                        self.optional("octaves")?.unwrap_or(5),
+                        self.optional("coverage")?.unwrap_or(0.5),
+                    );
+                    (grid, None)
+                };
+
+                let min = match self.optional("min")? {
+                    Some(min) => min,
+                    None => file_bounds
+                        .map(|b| b.min)
+                        .ok_or_else(|| self.missing("min"))?,
+                };
+                let max: Vec3 = match self.optional("max")? {
+                    Some(max) => max,
+                    None => file_bounds
+                        .map(|b| b.max)
+                        .ok_or_else(|| self.missing("max"))?,
+                };
+                // Written so that NaN bounds fail too.
+                if !(max.x > min.x && max.y > min.y && max.z > min.z) {
+                    let line = self.entry("max").map_or(self.line, |e| e.line);
+                    let message = "'max' must be greater than 'min' along every axis".to_string();
+                    return Err(self.context.error(line, message));
+                }
+                vec![Box::new(GridMedium::new(
+                    grid,
+                    Aabb::new(min, max),
+                    density,
+                    phase_function,
+                ))]
+            }
+            "mesh" => {
+                let (path, _) = self.path("file")?;
+                let material = self.material_reference(library)?;
+                let mut ids = self.context.material_ids.borrow_mut();
+                let material = match material {
+                    Some(material) => material,
+                    None => ids.assign(Arc::new(Lambertian::new(0.5, 0.5, 0.5))),
+                };
+                load_obj(&path, material, &mut ids)
+                    .map_err(SceneError::Obj)?
+                    .into_iter()
+                    .map(|mesh| Box::new(mesh) as Box<dyn Hitable>)
+                    .collect()
+            }
+            other => {
+                return Err(self
+                    .context
+                    .error(line, format!("unknown object type '{}'", other)))
+            }
+        };
+
+        match self.transform()? {
+            Some(to_world) => Ok(objects
+                .into_iter()
+                .map(|object| {
+                    Box::new(Instance::new(Arc::from(object), to_world)) as Box<dyn Hitable>
+                })
+                .collect()),
+            None => Ok(objects),
+        }
+    }
+
+    /// The optional `scale`, `rotate` and `translate` keys of an object, applied in that order.
+    fn transform(&mut self) -> Result<Option<Matrix4>, SceneError> {
+        let scale: Option<Vec3> = self.optional("scale")?;
+        let rotate: Option<Vec3> = self.optional("rotate")?;
+        let translate: Option<Vec3> = self.optional("translate")?;
+        if scale.is_none() && rotate.is_none() && translate.is_none() {
+            return Ok(None);
+        }
+
+        let mut to_world = Matrix4::identity();
+        if let Some(scale) = scale {
+            to_world = Matrix4::scaling(scale);
+        }
+        if let Some(degrees) = rotate {
+            to_world = Matrix4::rotation(Vec3::new(1.0, 0.0, 0.0), degrees.x) * to_world;
+            to_world = Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), degrees.y) * to_world;
+            to_world = Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), degrees.z) * to_world;
+        }
+        if let Some(offset) = translate {
+            to_world = Matrix4::translation(offset) * to_world;
+        }
+        // Rotations and translations can always be undone, so only the scale can make this fail.
+        if to_world.inverse().is_none() {
+            let line = self.entry("scale").map_or(self.line, |e| e.line);
+            return Err(self.context.error(
+                line,
+                "'scale' must not be zero or near zero along any axis".to_string(),
+            ));
+        }
+        Ok(Some(to_world))
+    }
+
+    fn background(&mut self) -> Result<Box<dyn Background>, SceneError> {
+        let (kind, line) = self.kind()?;
+        let background: Box<dyn Background> = match kind.as_str() {
+            "gradient" => {
+                let sky = GradientBackground::sky();
+                Box::new(GradientBackground::new(
+                    self.optional("nadir")?.unwrap_or(sky.nadir),
+                    self.optional("zenith")?.unwrap_or(sky.zenith),
+                ))
+            }
+            "solid" => {
+                let color: Vec3 = self.required("color")?;
+                Box::new(SolidBackground::new(color.x, color.y, color.z))
+            }
+            "black" => Box::new(BlackBackground),
+            "environment" => {
+                let (path, file_line) = self.path("file")?;
+                let mut map = EnvironmentMap::load(&path).map_err(|err| {
+                    self.context.error(
+                        file_line,
+                        format!("cannot load '{}': {}", path.display(), err),
+                    )
+                })?;
+                map.intensity = self.optional("intensity")?.unwrap_or(1.0);
+                Box::new(map)
+            }
+            other => {
+                return Err(self
+                    .context
+                    .error(line, format!("unknown background type '{}'", other)))
+            }
+        };
+        Ok(background)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vector_math::aabb::Aabb;
+    use vector_math::ray::Ray;
+
+    fn parse(source: &str) -> Result<SceneDescription, SceneError> {
+        parse_scene(source, "test.toml", Path::new(""))
+    }
+
+    fn error_of(source: &str) -> String {
+        match parse(source) {
+            Err(err) => err.to_string(),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    const CAMERA: &str = "[camera]\norigin = [0, 0, 1]\nlook_at = [0, 0, 0]\nfov = 90\n";
+
+    #[test]
+    fn parses_scene() {
+        let source = format!(
+            "{}{}",
+            CAMERA,
+            r#"
+            [render]
+            width = 64
+            seed = 18446744073709551615
+
+            [[object]]
+            type = "sphere"
+            center = [0, 0, -2]
+            radius = 0.5
+            material = "red"
+
+            [[object]]
+            type = "triangle"
+            vertices = [[0, 0, 0], [1, 0, 0], [0, 1, 0]]
+            material = { type = "metal", albedo = [1, 1, 1], roughness = 0.25 }
+
+            [[material]]
+            name = "red"
+            type = "lambertian"
+            albedo = [0.8, 0.1, 0.1]
+
+            [background]
+            type = "solid"
+            color = [0.1, 0.2, 0.3]
+            "#
+        );
+        let scene = parse(&source).unwrap();
+
+        assert_eq!(scene.render.width, 64);
+        assert_eq!(scene.render.height, 100);
+        assert_eq!(scene.render.seed, u64::MAX);
+        assert_eq!(scene.camera.up, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(scene.camera.focus_distance, 1.0);
+        assert_eq!(scene.objects.len(), 2);
+
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+        let hit = scene.objects[0].hit_test(&ray, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 1.5);
+        assert_eq!(scene.background.color(&ray), Vec3::new(0.1, 0.2, 0.3));
+
+        // Named materials are numbered before inline ones.
+        assert_eq!(hit.material.id(), 1);
+        let ray = Ray::new(Vec3::new(0.2, 0.2, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            scene.objects[1]
+                .hit_test(&ray, 0.0, f32::MAX)
+                .unwrap()
+                .material
+                .id(),
+            2
+        );
+    }
+
+    #[test]
+    fn parses_textures() {
+        let source = format!(
+            "{}{}",
+            CAMERA,
+            r#"
+            [[texture]]
+            name = "white"
+            type = "constant"
+            color = [1, 1, 1]
+
+            [[texture]]
+            name = "tiles"
+            type = "checker"
+            even = "white"
+            odd = { type = "constant", color = [0, 0, 0] }
+            size = 2
+
+            [[object]]
+            type = "sphere"
+            center = [0, 0, -2]
+            radius = 0.5
+            material = { type = "lambertian", albedo = "tiles" }
+            "#
+        );
+        let scene = parse(&source).unwrap();
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = scene.objects[0].hit_test(&ray, 0.0, f32::MAX).unwrap();
+        // The sphere's front, at z = -1.5, is in an odd cell and its back, at z = -2.5, in an even.
+        assert_eq!(hit.material.albedo(&hit), Vec3::zero());
+        let behind = Ray::new(Vec3::new(0.0, 0.0, -4.0), Vec3::new(0.0, 0.0, 1.0));
+        let hit = scene.objects[0].hit_test(&behind, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.material.albedo(&hit), Vec3::new(1.0, 1.0, 1.0));
+
+        let material = |albedo: &str| {
+            format!(
+                "{}[[material]]\nname = \"m\"\ntype = \"lambertian\"\nalbedo = {}\n",
+                CAMERA, albedo
+            )
+        };
+        assert_eq!(
+            error_of(&material("\"wood\"")),
+            "test.toml:8: unknown texture 'wood'"
+        );
+        assert_eq!(
+            error_of(&material("0.5")),
+            "test.toml:8: 'albedo' must be a color, texture name or inline texture, found a number"
+        );
+        assert_eq!(
+            error_of(&material(
+                "{ type = \"image\", file = \"wood.png\", wrap = \"tile\" }"
+            )),
+            "test.toml:8: unknown wrap mode 'tile'"
+        );
+        assert!(
+            error_of(&material("{ type = \"image\", file = \"missing.png\" }"))
+                .starts_with("test.toml:8: cannot load 'missing.png'")
+        );
+    }
+
+    #[test]
+    fn parses_motion() {
+        let source = r#"
+            [camera]
+            origin = [0, 0, 1]
+            look_at = [0, 0, 0]
+            fov = 90
+            shutter_close = 0.5
+
+            [[object]]
+            type = "moving_sphere"
+            centers = [[0, 0, -2], [0, 2, -2], [2, 2, -2]]
+            radius = 0.5
+            material = { type = "lambertian", albedo = [0.5, 0.5, 0.5] }
+        "#;
+        let scene = parse(source).unwrap();
+        assert_eq!(
+            (scene.camera.shutter_open, scene.camera.shutter_close),
+            (0.0, 0.5)
+        );
+        let at =
+            |time: f32| Ray::at_time(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, -1.0), time);
+        // Times default to 0, 0.5 and 1, so the sphere passes y = 1 at time 0.25.
+        assert!(scene.objects[0]
+            .hit_test(&at(0.25), 0.0, f32::MAX)
+            .is_some());
+        assert!(scene.objects[0]
+            .hit_test(&at(0.75), 0.0, f32::MAX)
+            .is_none());
+
+        let object = |body: &str| {
+            format!(
+                "{}[[object]]\ntype = \"moving_sphere\"\nradius = 1\nmaterial = \"m\"\n{}",
+                CAMERA, body
+            )
+        };
+        assert_eq!(
+            error_of(&object("centers = [[0, 0, 0], [1, 0, 0]]\ntimes = [0]\n")),
+            "test.toml:10: 'times' must have one entry per center"
+        );
+        assert_eq!(
+            error_of(&object(
+                "centers = [[0, 0, 0], [1, 0, 0]]\ntimes = [1, 0]\n"
+            )),
+            "test.toml:10: 'times' must be increasing"
+        );
+        assert_eq!(
+            error_of(&object("centers = [0, 0, 0]\n")),
+            "test.toml:9: 'centers' must be an array of points, found an array"
+        );
+    }
+
+    #[test]
+    fn parses_flat_shapes_and_boxes() {
+        let source = format!(
+            "{}{}",
+            CAMERA,
+            r#"
+            [[material]]
+            name = "white"
+            type = "lambertian"
+            albedo = [0.7, 0.7, 0.7]
+
+            [[object]]
+            type = "rect"
+            min = [-1, 2, -1]
+            max = [1, 2, 1]
+            material = { type = "diffuse_light", emit = [4, 4, 4] }
+
+            [[object]]
+            type = "box"
+            min = [-0.5, 0, -0.5]
+            max = [0.5, 1, 0.5]
+            material = "white"
+
+            [[object]]
+            type = "plane"
+            point = [0, 0, 0]
+            normal = [0, 1, 0]
+            material = "white"
+
+            [[object]]
+            type = "disk"
+            center = [0, 0, -3]
+            normal = [0, 0, 1]
+            radius = 2
+            material = "white"
+            "#
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(scene.objects.len(), 4);
+        let up = Ray::new(Vec3::new(0.0, 1.5, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let hit = scene.objects[0].hit_test(&up, 0.0, f32::MAX).unwrap();
+        assert_eq!(hit.t, 0.5);
+        assert!(hit.light.is_some());
+        assert_eq!(
+            scene.objects[1].bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(-0.5, 0.0, -0.5),
+                Vec3::new(0.5, 1.0, 0.5)
+            ))
+        );
+        assert!(scene.objects[2].bounding_box().is_none());
+        let back = Ray::new(Vec3::new(1.0, 1.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            scene.objects[3].hit_test(&back, 0.0, f32::MAX).unwrap().t,
+            3.0
+        );
+
+        let rect = format!(
+            "{}[[object]]\ntype = \"rect\"\nmin = [0, 0, 0]\nmax = [1, 1, 1]\nmaterial = \"m\"\n",
+            CAMERA
+        );
+        assert_eq!(
+            error_of(&rect),
+            "test.toml:8: 'min' and 'max' must differ along exactly two axes"
+        );
+
+        let disk = |normal: &str, radius: &str| {
+            format!(
+                "{}[[object]]\ntype = \"disk\"\ncenter = [0, 0, 0]\nnormal = {}\nradius = {}\n\
+                 material = \"m\"\n",
+                CAMERA, normal, radius
+            )
+        };
+        assert_eq!(
+            error_of(&disk("[0, 0, 0]", "1")),
+            "test.toml:8: 'normal' must not be zero"
+        );
+        assert_eq!(
+            error_of(&disk("[0, 1, 0]", "0")),
+            "test.toml:9: 'radius' must be positive"
+        );
+        let plane = format!(
+            "{}[[object]]\ntype = \"plane\"\npoint = [0, 0, 0]\nnormal = [0, 0, 0]\n\
+             material = \"m\"\n",
+            CAMERA
+        );
+        assert_eq!(error_of(&plane), "test.toml:8: 'normal' must not be zero");
+    }
+
+    #[test]
+    fn parses_curved_shapes() {
+        let source = format!(
+            "{}{}",
+            CAMERA,
+            r#"
+            [[material]]
+            name = "white"
+            type = "lambertian"
+            albedo = [0.7, 0.7, 0.7]
+
+            [[object]]
+            type = "cylinder"
+            base = [0, 0, -5]
+            top = [0, 2, -5]
+            radius = 0.5
+            capped = false
+            material = "white"
+
+            [[object]]
+            type = "cone"
+            base = [3, 0, -5]
+            apex = [3, 2, -5]
+            radius = 1
+            material = "white"
+
+            [[object]]
+            type = "torus"
+            center = [0, 0, -10]
+            axis = [0, 0, 1]
+            major_radius = 2
+            minor_radius = 0.25
+            material = "white"
+            "#
+        );
+        let scene = parse(&source).unwrap();
+        let down = Ray::new(Vec3::new(0.0, 5.0, -5.0), Vec3::new(0.0, -1.0, 0.0));
+        // Uncapped, so the ray passes straight through.
+        assert!(scene.objects[0].hit_test(&down, 0.0, f32::MAX).is_none());
+        let down = Ray::new(Vec3::new(3.0, 5.0, -5.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!((scene.objects[1].hit_test(&down, 0.0, f32::MAX).unwrap().t - 3.0).abs() < 1e-5);
+        let forward = Ray::new(Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(
+            (scene.objects[2]
+                .hit_test(&forward, 0.0, f32::MAX)
+                .unwrap()
+                .t
+                - 9.75)
+                .abs()
+                < 1e-3
+        );
+
+        let cylinder = |extra: &str| {
+            format!(
+                "{}[[object]]\ntype = \"cylinder\"\nbase = [0, 0, 0]\ntop = [0, 0, 0]\n\
+                 radius = 1\n{}",
+                CAMERA, extra
+            )
+        };
+        assert_eq!(
+            error_of(&cylinder("")),
+            "test.toml:8: 'base' and 'top' must differ"
+        );
+        let cone = format!(
+            "{}[[object]]\ntype = \"cone\"\nbase = [0, 0, 0]\napex = [0, 1, 0]\nradius = -1\n",
+            CAMERA
+        );
+        assert_eq!(error_of(&cone), "test.toml:9: 'radius' must be positive");
+
+        let torus = |axis: &str, minor_radius: &str| {
+            format!(
+                "{}[[object]]\ntype = \"torus\"\ncenter = [0, 0, 0]\naxis = {}\nmajor_radius = 1\n\
+                 minor_radius = {}\n",
+                CAMERA, axis, minor_radius
+            )
+        };
+        assert_eq!(
+            error_of(&torus("[0, 0, 0]", "0.5")),
+            "test.toml:8: 'axis' must not be zero"
+        );
+        assert_eq!(
+            error_of(&torus("[0, 1, 0]", "0")),
+            "test.toml:10: 'minor_radius' must be positive"
+        );
+    }
+
+    #[test]
+    fn parses_conductors() {
+        let source = format!(
+            "{}{}",
+            CAMERA,
+            r#"
+            [[object]]
+            type = "sphere"
+            center = [0, 0, -5]
+            radius = 1
+            material = { type = "conductor", preset = "gold" }
+
+            [[object]]
+            type = "sphere"
+            center = [0, 0, -10]
+            radius = 1
+            material = "rough"
+
+            [[material]]
+            name = "rough"
+            type = "conductor"
+            eta = [1, 1, 1]
+            k = [3, 3, 3]
+            roughness = 0.3
+            roughness_v = 0.6
+            "#
+        );
+        let scene = parse(&source).unwrap();
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+        let hit = scene.objects[0].hit_test(&ray, 0.0, f32::MAX).unwrap();
+        assert!(hit.material.is_specular());
+        assert_eq!(hit.material.albedo(&hit), Conductor::gold(0.0).fresnel(1.0));
+        let hit = scene.objects[1].hit_test(&ray, 5.0, f32::MAX).unwrap();
+        assert!(!hit.material.is_specular());
+        // (1 - 1)² + 3² over (1 + 1)² + 3².
+        assert!((hit.material.albedo(&hit).x - 9.0 / 13.0).abs() < 1e-5);
+
+        let object = format!(
+            "{}[[object]]\ntype = \"sphere\"\ncenter = [0, 0, 0]\nradius = 1\n\
+             material = {{ type = \"conductor\", preset = \"tin\" }}\n",
+            CAMERA
+        );
+        assert_eq!(
+            error_of(&object),
+            "test.toml:9: unknown conductor preset 'tin'"
+        );
+    }
+
+    #[test]
+    fn parses_media() {
+        let source = format!(
+            "{}{}",
+            CAMERA,
+            r#"
+            [[object]]
+            type = "constant_medium"
+            boundary = { type = "box", min = [-1, -1, -6], max = [1, 1, -4] }
+            density = 1000
+
+            [[object]]
+            type = "constant_medium"
+            boundary = { type = "sphere", center = [0, 0, -10], radius = 1, translate = [5, 0, 0] }
+            density = 0.5
+            material = { type = "henyey_greenstein", albedo = [0.9, 0.9, 0.9], anisotropy = 0.6 }
+            "#
+        );
+        let scene = parse(&source).unwrap();
+        // Dense enough to scatter right at the boundary.
+        let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+        let hit = scene.objects[0].hit_test(&ray, 0.0, f32::MAX).unwrap();
+        assert!((hit.t - 4.0).abs() < 0.05);
+        assert!(hit.material.is_volumetric());
+        let bounds = scene.objects[1].bounding_box().unwrap();
+        assert!((bounds.centroid() - Vec3::new(5.0, 0.0, -10.0)).magnitude() < 1e-5);
+
+        let medium = |boundary: &str, material: &str| {
+            format!(
+                "{}[[object]]\ntype = \"constant_medium\"\ndensity = 1\nboundary = {}\n{}",
+                CAMERA, boundary, material
+            )
+        };
+        let sphere = "{ type = \"sphere\", center = [0, 0, 0], radius = 1 }";
+        assert_eq!(
+            error_of(&medium("[0, 0, 0]", "")),
+            "test.toml:8: 'boundary' must be an inline object table, found an array"
+        );
+        let forward =
+            "material = { type = \"henyey_greenstein\", albedo = [1, 1, 1], anisotropy = 1 }";
+        assert_eq!(
+            error_of(&medium(sphere, forward)),
+            "test.toml:9: 'anisotropy' must be between -1 and 1"
+        );
+    }
+
+    #[test]
+    fn parses_grid_media() {
+        // A 2x1x1 grid, empty on the left and full on the right.
+        let raw = std::env::temp_dir().join("scene_file_parses_grid_media.raw");
+        fs::write(&raw, [0u8, 255]).unwrap();
+        let source = format!(
+            "{}[[object]]\ntype = \"grid_medium\"\nfile = \"{}\"\nresolution = [2, 1, 1]\n\
+             min = [-1, -1, -1]\nmax = [1, 1, 1]\ndensity = 1000\n{}",
+            CAMERA,
+            raw.display(),
+            r#"
+            [[object]]
+            type = "grid_medium"
+            min = [0, 0, -10]
+            max = [4, 4, -6]
+            resolution = [8, 8, 8]
+            seed = 2
+            "#
+        );
+        let scene = parse(&source).unwrap();
+        fs::remove_file(&raw).unwrap();
+        let left = Ray::new(Vec3::new(-0.75, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(scene.objects[0].transmittance(&left, 0.0, f32::MAX), 1.0);
+        let right = Ray::new(Vec3::new(0.75, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = scene.objects[0].hit_test(&right, 0.0, f32::MAX).unwrap();
+        assert!(hit.t > 4.0 && hit.t < 4.1);
+        assert_eq!(
+            scene.objects[1].bounding_box(),
+            Some(Aabb::new(
+                Vec3::new(0.0, 0.0, -10.0),
+                Vec3::new(4.0, 4.0, -6.0)
+            ))
+        );
+
+        let grid = |extra: &str| format!("{}[[object]]\ntype = \"grid_medium\"\n{}", CAMERA, extra);
+        assert_eq!(
+            error_of(&grid("min = [0, 0, 0]")),
+            "test.toml:5: [[object]] is missing required key 'max'"
+        );
+        assert_eq!(
+            error_of(&grid("min = [0, 0, 0]\nmax = [1, 0, 1]")),
+            "test.toml:8: 'max' must be greater than 'min' along every axis"
+        );
+        assert_eq!(
+            error_of(&grid(
+                "file = \"cloud.raw\"\nmin = [0, 0, 0]\nmax = [1, 1, 1]"
+            )),
+            "test.toml:5: [[object]] is missing required key 'resolution'"
+        );
+    }
+
+    #[test]
+    fn parses_object_transforms() {
+        let source = format!(
+            "{}{}",
+            CAMERA,
+            r#"
+            [[object]]
+            type = "sphere"
+            center = [1, 0, 0]
+            radius = 0.5
+            material = { type = "lambertian", albedo = [0.5, 0.5, 0.5] }
+            scale = [2, 2, 2]
+            rotate = [0, 0, 90]
+            translate = [0, 0, -4]
+            "#
+        );
+        let scene = parse(&source).unwrap();
+        // Scaled to radius 1 around (2, 0, 0), turned to (0, 2, 0), then moved back.
+        let bounds = scene.objects[0].bounding_box().unwrap();
+        assert!((bounds.centroid() - Vec3::new(0.0, 2.0, -4.0)).magnitude() < 1e-5);
+        let ray = Ray::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = scene.objects[0].hit_test(&ray, 0.0, f32::MAX).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-5);
+
+        let object = format!(
+            "{}[[object]]\ntype = \"sphere\"\ncenter = [0, 0, 0]\nradius = 1\n\
+             material = {{ type = \"dielectric\", refractive_index = 1.5 }}\nscale = [1, 0, 1]\n",
+            CAMERA
+        );
+        assert_eq!(
+            error_of(&object),
+            "test.toml:10: 'scale' must not be zero or near zero along any axis"
+        );
+        let tiny = object.replace("scale = [1, 0, 1]", "scale = [1e-14, 1e-14, 1e-14]");
+        assert_eq!(
+            error_of(&tiny),
+            "test.toml:10: 'scale' must not be zero or near zero along any axis"
+        );
+    }
+
+    #[test]
+    fn parses_noise_textures() {
+        let source = format!(
+            "{}{}",
+            CAMERA,
+            r#"
+            [[object]]
+            type = "sphere"
+            center = [0, 0, -2]
+            radius = 0.5
+            material = { type = "lambertian", albedo = { type = "marble", seed = 5, scale = 3 } }
+            "#
+        );
+        let scene = parse(&source).unwrap();
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = scene.objects[0].hit_test(&ray, 0.0, f32::MAX).unwrap();
+        let mut marble = MarbleTexture::new(5);
+        marble.scale = 3.0;
+        assert_eq!(hit.material.albedo(&hit), marble.value(hit.uv, &hit.point));
+
+        for texture in &["wood", "clouds"] {
+            let source = format!(
+                "{}[[texture]]\nname = \"t\"\ntype = \"{}\"\nseed = 1\n",
+                CAMERA, texture
+            );
+            assert!(parse(&source).is_ok());
+        }
+        let unknown_key = format!(
+            "{}[[texture]]\nname = \"t\"\ntype = \"wood\"\nvein = [0, 0, 0]\n",
+            CAMERA
+        );
+        assert!(parse(&unknown_key).is_err());
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        let object = |body: &str| format!("{}[[object]]\ntype = \"sphere\"\n{}", CAMERA, body);
+
+        assert_eq!(
+            error_of(&object(
+                "center = [0, 0, 0]\nradius = 1\nmaterial = \"missing\"\n"
+            )),
+            "test.toml:9: unknown material 'missing'"
+        );
+        assert_eq!(
+            error_of(&object("center = [0, 0]\n")),
+            "test.toml:7: 'center' must be an array of three numbers, found an array"
+        );
+        assert_eq!(
+            error_of(&object("center = [0, 0, 0]\nmaterial = \"x\"\n")),
+            "test.toml:5: [[object]] is missing required key 'radius'"
+        );
+        let glass = "material = { type = \"dielectric\", refractive_index = 1.5 }\n";
+        assert_eq!(
+            error_of(&object(&format!(
+                "center = [0, 0, 0]\nradius = 1\ncolour = 2\n{}",
+                glass
+            ))),
+            "test.toml:9: unknown key 'colour' in [[object]]"
+        );
+        assert_eq!(
+            error_of(&object(
+                "center = [0, 0, 0]\nradius = 1\nmaterial = { type = \"plastic\" }\n"
+            )),
+            "test.toml:9: unknown material type 'plastic'"
+        );
+        assert_eq!(
+            error_of(&format!("{}[render]\nwidth = -3\n", CAMERA)),
+            "test.toml:6: 'width' must be a non-negative integer, found a number"
+        );
+        assert_eq!(
+            error_of(&format!(
+                "{}[[material]]\nname = \"a\"\ntype = \"dielectric\"\n",
+                CAMERA
+            )),
+            "test.toml:5: [[material]] is missing required key 'refractive_index'"
+        );
+        assert_eq!(
+            error_of(&format!(
+                "{}[[material]]\nname = \"a\"\ntype = \"dielectric\"\nrefractive_index = 0\n",
+                CAMERA
+            )),
+            "test.toml:8: 'refractive_index' must be positive"
+        );
+        for fov in &["0", "-10", "180", "270"] {
+            assert_eq!(
+                error_of(&CAMERA.replace("fov = 90", &format!("fov = {}", fov))),
+                "test.toml:4: 'fov' must be between 0 and 180 degrees"
+            );
+        }
+        assert_eq!(
+            error_of("[lights]\n"),
+            "test.toml:1: unknown section 'lights'"
+        );
+        assert_eq!(
+            error_of("[render]\n"),
+            "test.toml:1: scene has no [camera] section"
+        );
+    }
+}
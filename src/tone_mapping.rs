@@ -0,0 +1,133 @@
+use vector_math::vec3::{luminance, Vec3};
+
+/// Operator that compresses linear radiance into the displayable `[0, 1]` range.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ToneMap {
+    /// Clips each channel at 1.
+    Clamp,
+    /// `L / (1 + L)` on luminance, keeping the hue.
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve.
+    Aces,
+}
+
+impl ToneMap {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "clamp" => Some(ToneMap::Clamp),
+            "reinhard" => Some(ToneMap::Reinhard),
+            "aces" => Some(ToneMap::Aces),
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, color: Vec3) -> Vec3 {
+        let mapped = match self {
+            ToneMap::Clamp => color,
+            ToneMap::Reinhard => {
+                let l = luminance(color);
+                if l > 0.0 {
+                    color * (1.0 / (1.0 + l))
+                } else {
+                    Vec3::zero()
+                }
+            }
+            ToneMap::Aces => {
+                // The fit is only meaningful for non-negative input.
+                let curve = |x: f32| {
+                    let x = x.max(0.0);
+                    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+                };
+                Vec3::new(curve(color.x), curve(color.y), curve(color.z))
+            }
+        };
+        Vec3::new(saturate(mapped.x), saturate(mapped.y), saturate(mapped.z))
+    }
+}
+
+/// Clamps to `[0, 1]`, sending NaN to 0.
+fn saturate(x: f32) -> f32 {
+    if x > 0.0 {
+        x.min(1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Radiance multiplier for an exposure adjustment of `stops`.
+pub fn exposure_scale(stops: f32) -> f32 {
+    f32::powf(2.0, stops)
+}
+
+/// sRGB transfer function, from linear `[0, 1]` to encoded `[0, 1]`.
+pub fn srgb_encode(linear: f32) -> f32 {
+    if linear <= 0.003_130_8 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn srgb_decode(encoded: f32) -> f32 {
+    if encoded <= 0.040_45 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Tone maps and sRGB-encodes a linear color to 8 bits per channel.
+pub fn to_srgb8(color: Vec3, tone_map: ToneMap, exposure_stops: f32) -> (u8, u8, u8) {
+    let mapped = tone_map.apply(color * exposure_scale(exposure_stops));
+    let quantize = |c: f32| (255.0 * srgb_encode(c) + 0.5) as u8;
+    (quantize(mapped.x), quantize(mapped.y), quantize(mapped.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_round_trip() {
+        assert_eq!(srgb_encode(0.0), 0.0);
+        assert!((srgb_encode(1.0) - 1.0).abs() < 1e-6);
+        assert!((srgb_encode(0.18) - 0.4614).abs() < 1e-4);
+        for &x in &[0.001, 0.002, 0.01, 0.2, 0.5, 0.9] {
+            assert!((srgb_decode(srgb_encode(x)) - x).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn tone_maps_stay_in_range() {
+        let bright = Vec3::new(50.0, 4.0, 0.5);
+        for &operator in &[ToneMap::Clamp, ToneMap::Reinhard, ToneMap::Aces] {
+            let mapped = operator.apply(bright);
+            assert!(mapped.x <= 1.0 && mapped.y <= 1.0 && mapped.z <= 1.0);
+            assert_eq!(operator.apply(Vec3::zero()), Vec3::zero());
+            assert_eq!(operator.apply(Vec3::new(f32::NAN, -1.0, 0.0)), Vec3::zero());
+        }
+
+        assert_eq!(ToneMap::Clamp.apply(bright), Vec3::new(1.0, 1.0, 0.5));
+        // Reinhard keeps channel ratios for colors that do not clip.
+        let grey = ToneMap::Reinhard.apply(Vec3::new(1.0, 1.0, 1.0));
+        assert!((grey.x - 0.5).abs() < 1e-6 && grey.x == grey.z);
+        assert!((ToneMap::Aces.apply(Vec3::new(1.0, 1.0, 1.0)).x - 0.8038).abs() < 1e-3);
+    }
+
+    #[test]
+    fn exposure_and_quantization() {
+        assert_eq!(exposure_scale(-1.0), 0.5);
+        assert_eq!(
+            to_srgb8(Vec3::new(1.0, 0.0, 2.0), ToneMap::Clamp, 0.0),
+            (255, 0, 255)
+        );
+        assert_eq!(
+            to_srgb8(Vec3::new(0.5, 0.5, 0.5), ToneMap::Clamp, 1.0),
+            (255, 255, 255)
+        );
+        assert_eq!(
+            to_srgb8(Vec3::new(0.2159, 0.2159, 0.2159), ToneMap::Clamp, 0.0).0,
+            128
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use super::ray::Ray;
+use super::vec3::Vec3;
+
+/// Axis-aligned bounding box.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    /// An inverted box that any `union` or `grow` will replace.
+    pub fn empty() -> Self {
+        Aabb {
+            min: Vec3::new(f32::MAX, f32::MAX, f32::MAX),
+            max: Vec3::new(f32::MIN, f32::MIN, f32::MIN),
+        }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn grow(&self, point: Vec3) -> Aabb {
+        Aabb::new(self.min.min(point), self.max.max(point))
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        0.5 * (self.min + self.max)
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let d = self.max - self.min;
+        if d.x < 0.0 || d.y < 0.0 || d.z < 0.0 {
+            return 0.0;
+        }
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    /// Slab test against the interval `(t_min, t_max)`.
+    pub fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> bool {
+        self.clip(ray, t_min, t_max).is_some()
+    }
+
+    /// The part of `(t_min, t_max)` for which the ray is inside the box.
+    pub fn clip(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+
+        for axis in 0..3 {
+            let inv_d = 1.0 / ray.direction[axis];
+            let mut t0 = (self.min[axis] - ray.origin[axis]) * inv_d;
+            let mut t1 = (self.max[axis] - ray.origin[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // f32::max/min discard the NaN produced by 0 * inf for rays lying in a slab plane.
+            t_min = t0.max(t_min);
+            t_max = t1.min(t_max);
+            if t_max < t_min {
+                return None;
+            }
+        }
+
+        Some((t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn aabb_union() {
+        let a = unit_box();
+        let b = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 3.0, 0.5));
+        let u = a.union(&b);
+        assert_eq!(u.min, Vec3::new(-1.0, -1.0, -1.0));
+        assert_eq!(u.max, Vec3::new(2.0, 3.0, 1.0));
+        assert_eq!(Aabb::empty().union(&a), a);
+    }
+
+    #[test]
+    fn aabb_surface_area() {
+        assert_eq!(unit_box().surface_area(), 24.0);
+        assert_eq!(Aabb::empty().surface_area(), 0.0);
+    }
+
+    #[test]
+    fn aabb_hit() {
+        let b = unit_box();
+        let toward = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let away = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let miss = Ray::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(b.hit(&toward, 0.0, f32::MAX));
+        assert!(!b.hit(&toward, 0.0, 3.0));
+        assert!(!b.hit(&away, 0.0, f32::MAX));
+        assert!(!b.hit(&miss, 0.0, f32::MAX));
+        assert_eq!(b.clip(&toward, 0.0, f32::MAX), Some((4.0, 6.0)));
+        assert_eq!(b.clip(&toward, 5.0, 5.5), Some((5.0, 5.5)));
+    }
+}
@@ -0,0 +1,169 @@
+/// Piecewise-constant distribution over `[0, 1)` proportional to `func`.
+#[derive(Clone, Debug)]
+pub struct Distribution1D {
+    func: Vec<f32>,
+    cdf: Vec<f32>,
+    integral: f32,
+}
+
+impl Distribution1D {
+    pub fn new(func: Vec<f32>) -> Self {
+        let n = func.len();
+        assert!(n > 0, "distribution needs at least one bucket");
+
+        let mut cdf = vec![0.0; n + 1];
+        for i in 0..n {
+            cdf[i + 1] = cdf[i] + func[i].abs() / n as f32;
+        }
+
+        let integral = cdf[n];
+        if integral == 0.0 {
+            // Nothing to prefer, so sample uniformly.
+            for (i, c) in cdf.iter_mut().enumerate() {
+                *c = i as f32 / n as f32;
+            }
+        } else {
+            for c in cdf.iter_mut() {
+                *c /= integral;
+            }
+        }
+
+        Distribution1D {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.func.len()
+    }
+
+    pub fn integral(&self) -> f32 {
+        self.integral
+    }
+
+    /// Maps uniform `u` to `(x, pdf, bucket)`.
+    pub fn sample(&self, u: f32) -> (f32, f32, usize) {
+        // Last bucket whose cdf start is <= u.
+        let n = self.count();
+        let mut lo = 0;
+        let mut hi = n;
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if self.cdf[mid] <= u {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+
+        let width = self.cdf[lo + 1] - self.cdf[lo];
+        let du = if width > 0.0 {
+            (u - self.cdf[lo]) / width
+        } else {
+            0.0
+        };
+        let x = ((lo as f32 + du.clamp(0.0, 1.0)) / n as f32).min(1.0 - f32::EPSILON);
+        (x, self.pdf(lo), lo)
+    }
+
+    /// Density of bucket `index` with respect to `x`.
+    pub fn pdf(&self, index: usize) -> f32 {
+        if self.integral == 0.0 {
+            1.0
+        } else {
+            self.func[index].abs() / self.integral
+        }
+    }
+}
+
+/// Piecewise-constant distribution over the unit square, stored as rows of `func[v][u]`.
+#[derive(Clone, Debug)]
+pub struct Distribution2D {
+    conditional: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    pub fn new(func: &[f32], width: usize, height: usize) -> Self {
+        assert_eq!(func.len(), width * height);
+
+        let conditional: Vec<Distribution1D> = func
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(conditional.iter().map(|row| row.integral()).collect());
+
+        Distribution2D {
+            conditional,
+            marginal,
+        }
+    }
+
+    /// Maps uniform `(u1, u2)` to `((u, v), pdf)`.
+    pub fn sample(&self, u1: f32, u2: f32) -> ((f32, f32), f32) {
+        let (v, pdf_v, row) = self.marginal.sample(u2);
+        let (u, pdf_u, _) = self.conditional[row].sample(u1);
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    pub fn pdf(&self, u: f32, v: f32) -> f32 {
+        let row = ((v * self.marginal.count() as f32) as usize).min(self.marginal.count() - 1);
+        let conditional = &self.conditional[row];
+        let column = ((u * conditional.count() as f32) as usize).min(conditional.count() - 1);
+        conditional.pdf(column) * self.marginal.pdf(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distribution_1d_follows_weights() {
+        let d = Distribution1D::new(vec![1.0, 3.0, 0.0, 4.0]);
+        assert_eq!(d.integral(), 2.0);
+
+        let (x, pdf, index) = d.sample(0.0);
+        assert_eq!((x, pdf, index), (0.0, 0.5, 0));
+
+        // The first half of u covers buckets 0 and 1 in a 1:3 ratio.
+        let (x, pdf, index) = d.sample(0.3);
+        assert_eq!((pdf, index), (1.5, 1));
+        assert!((0.25..0.5).contains(&x));
+
+        // Empty buckets are never chosen.
+        let (_, _, index) = d.sample(0.5);
+        assert_eq!(index, 3);
+    }
+
+    #[test]
+    fn distribution_1d_uniform_when_empty() {
+        let d = Distribution1D::new(vec![0.0; 4]);
+        let (x, pdf, index) = d.sample(0.6);
+        assert_eq!(index, 2);
+        assert_eq!(pdf, 1.0);
+        assert!((x - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn distribution_2d_pdf_matches_sample() {
+        let func = [0.0, 1.0, 2.0, 3.0, 0.0, 10.0];
+        let d = Distribution2D::new(&func, 3, 2);
+
+        for i in 0..20 {
+            for j in 0..20 {
+                let ((u, v), pdf) = d.sample((i as f32 + 0.5) / 20.0, (j as f32 + 0.5) / 20.0);
+                assert!((d.pdf(u, v) - pdf).abs() < 1e-4);
+                assert!(pdf > 0.0);
+            }
+        }
+
+        // Density integrates to one over the square.
+        let total: f32 = (0..6)
+            .map(|k| d.pdf((k % 3) as f32 / 3.0 + 0.1, (k / 3) as f32 / 2.0 + 0.1) / 6.0)
+            .sum();
+        assert!((total - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,79 @@
+use super::aabb::Aabb;
+use super::ray::Ray;
+use super::vec3::Vec3;
+
+/// Local coordinates for shapes that are symmetric about an axis: `origin` at the local origin and
+/// the unit `axis` as local y.
+#[derive(Copy, Clone, Debug)]
+pub struct AxisFrame {
+    pub origin: Vec3,
+    pub axis: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+}
+
+impl AxisFrame {
+    pub fn new(origin: Vec3, axis: Vec3) -> Self {
+        let axis = axis.normalized();
+        let (tangent, bitangent) = axis.orthonormal_basis();
+        AxisFrame {
+            origin,
+            axis,
+            tangent,
+            bitangent,
+        }
+    }
+
+    pub fn to_local_vector(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(v.dot(self.tangent), v.dot(self.axis), v.dot(self.bitangent))
+    }
+
+    /// The same ray in local coordinates, with distances along it unchanged.
+    pub fn to_local_ray(&self, ray: &Ray) -> Ray {
+        Ray::at_time(
+            self.to_local_vector(&(ray.origin - self.origin)),
+            self.to_local_vector(&ray.direction),
+            ray.time,
+        )
+    }
+
+    pub fn to_world_vector(&self, v: &Vec3) -> Vec3 {
+        v.x * self.tangent + v.y * self.axis + v.z * self.bitangent
+    }
+
+    /// Bounds of the circle of `radius` around the axis at local height `y`.
+    pub fn circle_bounds(&self, y: f32, radius: f32) -> Aabb {
+        let center = self.origin + y * self.axis;
+        let extent = |c: f32| radius * f32::sqrt((1.0 - c * c).max(0.0));
+        let e = Vec3::new(
+            extent(self.axis.x),
+            extent(self.axis.y),
+            extent(self.axis.z),
+        );
+        Aabb::new(center - e, center + e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axis_frame_round_trip() {
+        let frame = AxisFrame::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 1.0, 0.0));
+        let ray = Ray::new(
+            Vec3::new(1.0, 2.0, 3.0) + Vec3::new(2.0, 2.0, 0.0),
+            Vec3::new(0.0, 0.0, 5.0),
+        );
+        let local = frame.to_local_ray(&ray);
+        assert!((local.origin - Vec3::new(0.0, f32::sqrt(8.0), 0.0)).magnitude() < 1e-5);
+        assert!((local.direction.magnitude() - 5.0).abs() < 1e-5);
+        assert!((frame.to_world_vector(&local.direction) - ray.direction).magnitude() < 1e-5);
+
+        let bounds = AxisFrame::new(Vec3::zero(), Vec3::new(0.0, 0.0, 2.0)).circle_bounds(1.0, 2.0);
+        assert_eq!(
+            bounds,
+            Aabb::new(Vec3::new(-2.0, -2.0, 1.0), Vec3::new(2.0, 2.0, 1.0))
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use super::vec3::Vec3;
+use std::ops::Mul;
+
+/// Row-major 4x4 matrix for affine transforms of column vectors, so `a * b` applies `b` first.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Matrix4 {
+    pub m: [[f32; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn new(m: [[f32; 4]; 4]) -> Self {
+        Matrix4 { m }
+    }
+
+    pub fn identity() -> Self {
+        Matrix4::scaling(Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        Matrix4::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scaling(factors: Vec3) -> Self {
+        Matrix4::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counter-clockwise rotation by `degrees` when looking down `axis` toward the origin.
+    pub fn rotation(axis: Vec3, degrees: f32) -> Self {
+        let a = axis.normalized();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let c = 1.0 - cos;
+        Matrix4::new([
+            [
+                cos + a.x * a.x * c,
+                a.x * a.y * c - a.z * sin,
+                a.x * a.z * c + a.y * sin,
+                0.0,
+            ],
+            [
+                a.y * a.x * c + a.z * sin,
+                cos + a.y * a.y * c,
+                a.y * a.z * c - a.x * sin,
+                0.0,
+            ],
+            [
+                a.z * a.x * c - a.y * sin,
+                a.z * a.y * c + a.x * sin,
+                cos + a.z * a.z * c,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Matrix4::new(m)
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting; `None` for singular matrices and ones whose
+    /// inverse does not fit in an `f32`.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.m;
+        let mut inverse = Matrix4::identity().m;
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))
+                .unwrap();
+            if a[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(column, pivot);
+            inverse.swap(column, pivot);
+
+            let scale = 1.0 / a[column][column];
+            for j in 0..4 {
+                a[column][j] *= scale;
+                inverse[column][j] *= scale;
+            }
+            for row in 0..4 {
+                let factor = a[row][column];
+                if row == column || factor == 0.0 {
+                    continue;
+                }
+                for j in 0..4 {
+                    a[row][j] -= factor * a[column][j];
+                    inverse[row][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+        if inverse.iter().flatten().any(|x| !x.is_finite()) {
+            return None;
+        }
+        Some(Matrix4::new(inverse))
+    }
+
+    /// Applies the full affine transform, taking the bottom row to be `[0, 0, 0, 1]`.
+    pub fn transform_point(&self, p: &Vec3) -> Vec3 {
+        self.transform_vector(p) + Vec3::new(self.m[0][3], self.m[1][3], self.m[2][3])
+    }
+
+    /// Applies the linear part only, as for directions and offsets.
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        let row = |r: &[f32; 4]| r[0] * v.x + r[1] * v.y + r[2] * v.z;
+        Vec3::new(row(&self.m[0]), row(&self.m[1]), row(&self.m[2]))
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Matrix4) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        Matrix4::new(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).magnitude() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn matrix4_transforms_points_and_vectors() {
+        let p = Vec3::new(1.0, 2.0, 3.0);
+        let translate = Matrix4::translation(Vec3::new(1.0, -1.0, 0.5));
+        assert_eq!(translate.transform_point(&p), Vec3::new(2.0, 1.0, 3.5));
+        assert_eq!(translate.transform_vector(&p), p);
+
+        let rotate = Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), 90.0);
+        assert_near(
+            rotate.transform_vector(&Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let rotate = Matrix4::rotation(Vec3::new(0.0, 2.0, 0.0), 90.0);
+        assert_near(
+            rotate.transform_vector(&Vec3::new(0.0, 0.0, 1.0)),
+            Vec3::new(1.0, 0.0, 0.0),
+        );
+
+        // Scaling first, then translating.
+        let both = translate * Matrix4::scaling(Vec3::new(2.0, 2.0, 2.0));
+        assert_eq!(both.transform_point(&p), Vec3::new(3.0, 3.0, 6.5));
+        assert_eq!(Matrix4::identity() * both, both);
+        assert_eq!(both.transpose().transpose(), both);
+    }
+
+    #[test]
+    fn matrix4_inverse() {
+        let m = Matrix4::translation(Vec3::new(3.0, 0.0, -2.0))
+            * Matrix4::rotation(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            * Matrix4::scaling(Vec3::new(0.5, 2.0, -1.0));
+        let inverse = m.inverse().unwrap();
+        let p = Vec3::new(0.3, -4.0, 2.0);
+        assert_near(inverse.transform_point(&m.transform_point(&p)), p);
+        let product = m * inverse;
+        for (i, row) in product.m.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((value - expected).abs() < 1e-5);
+            }
+        }
+
+        assert!(Matrix4::scaling(Vec3::new(1.0, 0.0, 1.0))
+            .inverse()
+            .is_none());
+        assert!(Matrix4::scaling(Vec3::new(1e-14, 1e-14, 1e-14))
+            .inverse()
+            .is_none());
+        assert!(Matrix4::scaling(Vec3::new(f32::NAN, 1.0, 1.0))
+            .inverse()
+            .is_none());
+    }
+}
@@ -1,3 +1,9 @@
+pub mod aabb;
+pub mod distribution;
+pub mod frame;
+pub mod matrix4;
+pub mod noise;
+pub mod polynomial;
 pub mod random_methods;
 pub mod ray;
 pub mod vec3;
@@ -0,0 +1,146 @@
+//! Real roots of low-degree polynomials, in double precision for shapes that need it.
+
+use std::f64::consts::PI;
+
+/// Real roots of `x² + b x + c`, in increasing order.
+fn solve_monic_quadratic(b: f64, c: f64) -> Vec<f64> {
+    let discriminant = b * b - 4.0 * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    // Avoids cancellation between `-b` and the square root.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return vec![0.0, 0.0];
+    }
+    let (r0, r1) = (q, c / q);
+    vec![r0.min(r1), r0.max(r1)]
+}
+
+/// Real roots of `x³ + a x² + b x + c`, in no particular order.
+pub fn solve_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+    let shift = a / 3.0;
+    if r * r < q * q * q {
+        let theta = (r / (q * q * q).sqrt()).clamp(-1.0, 1.0).acos();
+        let scale = -2.0 * q.sqrt();
+        vec![
+            scale * (theta / 3.0).cos() - shift,
+            scale * ((theta + 2.0 * PI) / 3.0).cos() - shift,
+            scale * ((theta - 2.0 * PI) / 3.0).cos() - shift,
+        ]
+    } else {
+        let big_a = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let big_b = if big_a == 0.0 { 0.0 } else { q / big_a };
+        vec![big_a + big_b - shift]
+    }
+}
+
+/// Real roots of `x⁴ + a x³ + b x² + c x + d` by Ferrari's method, polished with Newton steps and
+/// returned in increasing order. Repeated roots may be reported once or twice.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    // Depress to y⁴ + p y² + q y + r with x = y - a/4.
+    let a2 = a * a;
+    let p = b - 3.0 * a2 / 8.0;
+    let q = c - a * b / 2.0 + a2 * a / 8.0;
+    let r = d - a * c / 4.0 + a2 * b / 16.0 - 3.0 * a2 * a2 / 256.0;
+
+    let mut roots = vec![];
+    if q.abs() < 1e-12 {
+        // Biquadratic: a quadratic in y².
+        for z in solve_monic_quadratic(p, r) {
+            if z >= 0.0 {
+                roots.push(z.sqrt());
+                roots.push(-z.sqrt());
+            }
+        }
+    } else {
+        // Any positive root m of the resolvent cubic splits the quartic into two quadratics. One
+        // exists because the cubic is negative at zero.
+        let m = solve_cubic(p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .fold(0.0, f64::max);
+        if m > 0.0 {
+            let s = (2.0 * m).sqrt();
+            roots.extend(solve_monic_quadratic(s, p / 2.0 + m - q / (2.0 * s)));
+            roots.extend(solve_monic_quadratic(-s, p / 2.0 + m + q / (2.0 * s)));
+        }
+    }
+
+    let f = |x: f64| (((x + a) * x + b) * x + c) * x + d;
+    let df = |x: f64| ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+    // A step off a near-zero slope can overflow, so only finite steps are kept, and roots that were
+    // never finite are dropped.
+    let mut roots: Vec<f64> = roots
+        .into_iter()
+        .map(|y| {
+            let mut x = y - a / 4.0;
+            for _ in 0..2 {
+                let next = x - f(x) / df(x);
+                if next.is_finite() {
+                    x = next;
+                }
+            }
+            x
+        })
+        .filter(|x| x.is_finite())
+        .collect();
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(mut actual: Vec<f64>, expected: &[f64]) {
+        actual.sort_by(f64::total_cmp);
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (x, e) in actual.iter().zip(expected) {
+            assert!((x - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn cubic_roots() {
+        // (x - 1)(x - 2)(x + 3) = x³ - 7x + 6
+        assert_roots(solve_cubic(0.0, -7.0, 6.0), &[-3.0, 1.0, 2.0]);
+        // (x - 2)(x² + 1) = x³ - 2x² + x - 2
+        assert_roots(solve_cubic(-2.0, 1.0, -2.0), &[2.0]);
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4) = x⁴ - 10x³ + 35x² - 50x + 24
+        assert_roots(
+            solve_quartic(-10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        // (x² - 4)(x² - 9), which is biquadratic.
+        assert_roots(
+            solve_quartic(0.0, -13.0, 0.0, 36.0),
+            &[-3.0, -2.0, 2.0, 3.0],
+        );
+        // (x + 0.5)(x - 7)(x² + 1)
+        assert_roots(solve_quartic(-6.5, -2.5, -6.5, -3.5), &[-0.5, 7.0]);
+        // x⁴ + 1 has no real roots.
+        assert_roots(solve_quartic(0.0, 0.0, 0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn quartic_roots_are_always_finite() {
+        // x⁴ has a zero slope at its root, where a Newton step would divide by zero.
+        assert!(solve_quartic(0.0, 0.0, 0.0, 0.0)
+            .iter()
+            .all(|x| x.abs() < 1e-9));
+        let degenerate = [
+            (f64::NAN, 1.0, 1.0, -1.0),
+            (1e300, -1e300, 1e300, -1e300),
+            (0.0, f64::INFINITY, 0.0, -1.0),
+        ];
+        for &(a, b, c, d) in &degenerate {
+            assert!(solve_quartic(a, b, c, d).iter().all(|x| x.is_finite()));
+        }
+    }
+}
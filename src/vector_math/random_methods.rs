@@ -1,10 +1,30 @@
 use super::vec3::Vec3;
+use rand::distributions::{Distribution, Standard};
 use rand::prelude::*;
+use rand::rngs::SmallRng;
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::seed_from_u64(0));
+}
+
+/// Restarts this thread's random sequence. The renderer reseeds per pixel so that output does not
+/// depend on which thread rendered which pixel.
+pub fn seed_thread_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
+
+pub fn random<T>() -> T
+where
+    Standard: Distribution<T>,
+{
+    RNG.with(|rng| rng.borrow_mut().gen())
+}
 
 pub fn random_in_unit_sphere() -> Vec3 {
-    let mut rng = thread_rng();
     loop {
-        let p = 2.0 * Vec3::new(rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>()) - Vec3::new(1.0, 1.0, 1.0);
+        let p = 2.0 * Vec3::new(random::<f32>(), random::<f32>(), random::<f32>())
+            - Vec3::new(1.0, 1.0, 1.0);
 
         if p.squared_magnitude() < 1.0 {
             return p;
@@ -12,13 +32,34 @@ pub fn random_in_unit_sphere() -> Vec3 {
     }
 }
 
+/// Uniformly distributed on the surface of the unit sphere.
+pub fn random_unit_vector() -> Vec3 {
+    let z = 1.0 - 2.0 * random::<f32>();
+    let r = f32::sqrt((1.0 - z * z).max(0.0));
+    let phi = 2.0 * std::f32::consts::PI * random::<f32>();
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
 pub fn random_in_unit_disk() -> Vec3 {
-    let mut rng = thread_rng();
     loop {
-        let p = 2.0 * Vec3::new(rng.gen::<f32>(), rng.gen::<f32>(), 0.0) - Vec3::new(1.0, 1.0, 0.0);
+        let p = 2.0 * Vec3::new(random::<f32>(), random::<f32>(), 0.0) - Vec3::new(1.0, 1.0, 0.0);
 
         if p.squared_magnitude() < 1.0 {
             return p;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_sequence_repeats() {
+        seed_thread_rng(5);
+        let a: Vec<f32> = (0..8).map(|_| random()).collect();
+        seed_thread_rng(5);
+        let b: Vec<f32> = (0..8).map(|_| random()).collect();
+        assert_eq!(a, b);
+    }
+}
@@ -4,11 +4,21 @@ use super::vec3::Vec3;
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
+    /// When the ray was fired, for scenes with moving objects.
+    pub time: f32,
 }
 
 impl Ray {
     pub fn new(origin: Vec3, direction: Vec3) -> Self {
-        Ray { origin, direction }
+        Ray::at_time(origin, direction, 0.0)
+    }
+
+    pub fn at_time(origin: Vec3, direction: Vec3, time: f32) -> Self {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
     }
 
     pub fn point_at(&self, t: f32) -> Vec3 {
@@ -2,6 +2,7 @@ use std::ops::Add;
 use std::ops::AddAssign;
 use std::ops::Div;
 use std::ops::DivAssign;
+use std::ops::Index;
 use std::ops::Mul;
 use std::ops::MulAssign;
 use std::ops::Neg;
@@ -60,6 +61,44 @@ impl Vec3 {
             z: self.x * rhs.y - self.y * rhs.x,
         }
     }
+
+    /// Component-wise minimum.
+    pub fn min(&self, rhs: Vec3) -> Vec3 {
+        Vec3::new(self.x.min(rhs.x), self.y.min(rhs.y), self.z.min(rhs.z))
+    }
+
+    /// Component-wise maximum.
+    pub fn max(&self, rhs: Vec3) -> Vec3 {
+        Vec3::new(self.x.max(rhs.x), self.y.max(rhs.y), self.z.max(rhs.z))
+    }
+
+    /// Two unit vectors that form a right-handed orthonormal basis with this unit vector
+    /// (Duff et al., "Building an Orthonormal Basis, Revisited").
+    pub fn orthonormal_basis(&self) -> (Vec3, Vec3) {
+        let sign = 1.0f32.copysign(self.z);
+        let a = -1.0 / (sign + self.z);
+        let b = self.x * self.y * a;
+        let tangent = Vec3::new(1.0 + sign * self.x * self.x * a, sign * b, -sign * self.x);
+        let bitangent = Vec3::new(b, sign + self.y * self.y * a, -self.y);
+        (tangent, bitangent)
+    }
+}
+
+/// Rec. 709 luminance of a linear RGB color.
+pub fn luminance(color: Vec3) -> f32 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+impl Index<usize> for Vec3 {
+    type Output = f32;
+    fn index(&self, axis: usize) -> &f32 {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 axis out of range: {}", axis),
+        }
+    }
 }
 
 impl Neg for Vec3 {
@@ -224,6 +263,12 @@ mod operator_tests {
         assert_eq!(a / 2.0, Vec3::new(1.0, 3.0, 5.0));
     }
 
+    #[test]
+    fn vec3_index() {
+        let a = Vec3::new(1.0, 3.0, 5.0);
+        assert_eq!((a[0], a[1], a[2]), (1.0, 3.0, 5.0));
+    }
+
     #[test]
     fn vec3_add_assign() {
         let mut a = Vec3::new(1.0, 3.0, 5.0);
@@ -298,4 +343,26 @@ mod method_tests {
         let z = Vec3::new(0.0, 0.0, 1.0);
         assert_eq!(z.cross(y), Vec3::new(-2.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn vec3_min_max() {
+        let a = Vec3::new(1.0, 5.0, -2.0);
+        let b = Vec3::new(3.0, 2.0, -4.0);
+        assert_eq!(a.min(b), Vec3::new(1.0, 2.0, -4.0));
+        assert_eq!(a.max(b), Vec3::new(3.0, 5.0, -2.0));
+    }
+
+    #[test]
+    fn vec3_orthonormal_basis() {
+        for &n in &[
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(2.0, -3.0, 6.0).normalized(),
+        ] {
+            let (t, b) = n.orthonormal_basis();
+            assert!((t.magnitude() - 1.0).abs() < 1e-6 && (b.magnitude() - 1.0).abs() < 1e-6);
+            assert!(t.dot(n).abs() < 1e-6 && b.dot(n).abs() < 1e-6 && t.dot(b).abs() < 1e-6);
+            assert!((t.cross(b) - n).magnitude() < 1e-6);
+        }
+    }
 }